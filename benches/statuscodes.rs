@@ -0,0 +1,44 @@
+//! Measures the cost of converting between `StatusCode` and its numeric value.
+//!
+//! Run with `cargo bench --bench statuscodes`.
+
+extern crate http;
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use http::StatusCode;
+
+const ITERATIONS: u32 = 1_000_000;
+
+fn bench<F: FnMut()>(name: &str, mut f: F) {
+	let start = Instant::now();
+	for _ in 0..ITERATIONS {
+		f();
+	}
+	let elapsed = start.elapsed();
+	println!("{:<24} {:>8.2} ns/iter", name, elapsed.as_secs_f64() * 1e9 / ITERATIONS as f64);
+}
+
+fn main() {
+	bench("from_u16 (registered)", || {
+		black_box(StatusCode::from_u16(black_box(429)));
+	});
+	bench("from_u16 (extension)", || {
+		black_box(StatusCode::from_u16(black_box(471)));
+	});
+	bench("to_u16", || {
+		black_box(black_box(StatusCode::TooManyRequests).to_u16());
+	});
+	bench("class", || {
+		black_box(black_box(StatusCode::TooManyRequests).class());
+	});
+	bench("reason_phrase", || {
+		black_box(black_box(StatusCode::TooManyRequests).reason_phrase());
+	});
+	bench("round trip 100..600", || {
+		for n in 100..600 {
+			black_box(StatusCode::from_u16(black_box(n)).to_u16());
+		}
+	});
+}
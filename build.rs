@@ -0,0 +1,141 @@
+//! Generates the StatusCode enum from the registry of status codes.
+//!
+//! `src/http/statuscodes/registry.csv` lists one registered status code per line. For each code,
+//! `src/http/statuscodes/NNN.md` may hold its documentation. The output is an invocation of the
+//! `status_codes!` macro defined in `src/http/statuscodes.rs`, which is included from there.
+
+use std::collections::HashSet;
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+const REGISTRY_DIR: &str = "src/http/statuscodes";
+const HEADER: &str = "code,variant,phrase,reference,class,cacheable";
+
+struct Entry {
+	code: u16,
+	variant: String,
+	phrase: String,
+	reference: String,
+	cacheable: bool,
+}
+
+fn main() {
+	let dir = Path::new(REGISTRY_DIR);
+	println!("cargo:rerun-if-changed={}", dir.display());
+
+	let registry = dir.join("registry.csv");
+	let text = fs::read_to_string(&registry)
+		.unwrap_or_else(|err| panic!("{}: {}", registry.display(), err));
+	let entries = parse_registry(&text)
+		.unwrap_or_else(|(line, err)| panic!("{}:{}: {}", registry.display(), line, err));
+
+	let mut out = String::new();
+	out.push_str("status_codes! {\n");
+	write_docs(&mut out, &read_docs(&dir.join("extension.md")).unwrap_or_default());
+	out.push_str("\tExtension;\n");
+	for entry in &entries {
+		let title = format!("{} - {} (<{}>)", entry.code, entry.phrase, entry.reference);
+		let prose = read_docs(&dir.join(format!("{}.md", entry.code)));
+		let docs = match prose {
+			Some(prose) => format!("{}\n\n{}", title, prose),
+			None => title,
+		};
+		write_docs(&mut out, &docs);
+		writeln!(out, "\t{} = {}, {:?}, {:?}, {};", entry.variant, entry.code, entry.phrase,
+			entry.reference, entry.cacheable).unwrap();
+	}
+	out.push_str("}\n");
+
+	let dest = Path::new(&env::var("OUT_DIR").unwrap()).join("statuscodes.rs");
+	fs::write(&dest, out).unwrap_or_else(|err| panic!("{}: {}", dest.display(), err));
+}
+
+/// Parses the registry, returning the line number and a message on error.
+fn parse_registry(text: &str) -> Result<Vec<Entry>, (usize, String)> {
+	let mut entries: Vec<Entry> = Vec::new();
+	let mut codes = HashSet::new();
+	let mut variants = HashSet::new();
+	let mut seen_header = false;
+
+	for (i, line) in text.lines().enumerate() {
+		let line_number = i + 1;
+		let line = line.trim();
+		if line.is_empty() || line.starts_with('#') {
+			continue;
+		}
+		if !seen_header {
+			if line != HEADER {
+				return Err((line_number, format!("expected header \"{}\"", HEADER)));
+			}
+			seen_header = true;
+			continue;
+		}
+
+		let entry = parse_entry(line).map_err(|err| (line_number, err))?;
+		if !codes.insert(entry.code) {
+			return Err((line_number, format!("duplicate code {}", entry.code)));
+		}
+		if !variants.insert(entry.variant.clone()) {
+			return Err((line_number, format!("duplicate variant {}", entry.variant)));
+		}
+		if entries.last().is_some_and(|last| last.code > entry.code) {
+			return Err((line_number, "codes must be listed in ascending order".to_string()));
+		}
+		entries.push(entry);
+	}
+
+	Ok(entries)
+}
+
+fn parse_entry(line: &str) -> Result<Entry, String> {
+	let fields: Vec<&str> = line.split(',').map(|field| field.trim()).collect();
+	let (code, variant, phrase, reference, class, cacheable) = match fields[..] {
+		[code, variant, phrase, reference, class, cacheable] =>
+			(code, variant, phrase, reference, class, cacheable),
+		_ => return Err(format!("expected 6 fields, found {}", fields.len())),
+	};
+
+	let code: u16 = match code.parse() {
+		Ok(code) if (100..600).contains(&code) => code,
+		_ => return Err(format!("invalid code \"{}\"", code)),
+	};
+	let valid_variant = variant.starts_with(|c: char| c.is_ascii_uppercase()) &&
+		variant.chars().all(|c| c.is_ascii_alphanumeric());
+	if !valid_variant || variant == "Extension" {
+		return Err(format!("invalid variant name \"{}\"", variant));
+	}
+	if phrase.is_empty() || !phrase.bytes().all(|b| b == b' ' || b.is_ascii_graphic()) {
+		return Err(format!("invalid reason phrase \"{}\"", phrase));
+	}
+	if !reference.starts_with("http://") && !reference.starts_with("https://") {
+		return Err(format!("invalid reference \"{}\"", reference));
+	}
+	if class != format!("{}xx", code / 100) {
+		return Err(format!("class \"{}\" does not match code {}", class, code));
+	}
+	let cacheable = match cacheable {
+		"yes" => true,
+		"no" => false,
+		_ => return Err(format!("cacheable must be \"yes\" or \"no\", found \"{}\"", cacheable)),
+	};
+
+	Ok(Entry {
+		code,
+		variant: variant.to_string(),
+		phrase: phrase.to_string(),
+		reference: reference.to_string(),
+		cacheable,
+	})
+}
+
+fn read_docs(path: &Path) -> Option<String> {
+	fs::read_to_string(path).ok().map(|docs| docs.trim_end().to_string())
+}
+
+fn write_docs(out: &mut String, docs: &str) {
+	for line in docs.lines() {
+		writeln!(out, "\t#[doc = {:?}]", line).unwrap();
+	}
+}
@@ -0,0 +1,682 @@
+//! The chunked transfer coding
+//! ([Section 4.1 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-4.1)).
+//!
+//! The chunked transfer coding wraps the payload body in order to transfer it as a series of
+//! chunks, each with its own size indicator, followed by an OPTIONAL trailer containing header
+//! fields. Chunked enables content streams of unknown size to be transferred as a sequence of
+//! length-delimited buffers, which enables the sender to retain connection persistence and the
+//! recipient to know when it has received the entire message.
+//!
+//! ```c
+//! chunked-body   = *chunk
+//!                  last-chunk
+//!                  trailer-part
+//!                  CRLF
+//!
+//! chunk          = chunk-size [ chunk-ext ] CRLF
+//!                  chunk-data CRLF
+//! chunk-size     = 1*HEXDIG
+//! last-chunk     = 1*("0") [ chunk-ext ] CRLF
+//!
+//! chunk-ext      = *( ";" chunk-ext-name [ "=" chunk-ext-val ] )
+//! chunk-ext-name = token
+//! chunk-ext-val  = token / quoted-string
+//!
+//! trailer-part   = *( header-field CRLF )
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, Read, Write};
+use std::str;
+
+use header::{HeaderMap, HeaderName, HeaderValue};
+use parser::{self, DEFAULT_MAX_HEADERS};
+use token;
+
+/// The default limit on the length of a chunk-size line or trailer field line, in bytes.
+pub const DEFAULT_MAX_CHUNK_LINE_LENGTH: usize = 4096;
+
+/// The header fields that a sender MUST NOT generate in a trailer, because they are needed for
+/// message framing, routing, request modifiers, authentication, response control data, or to
+/// process the payload
+/// ([Section 4.1.2 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-4.1.2)).
+pub const FORBIDDEN_TRAILERS: &[HeaderName] = &[
+	HeaderName::TRANSFER_ENCODING,
+	HeaderName::CONTENT_LENGTH,
+	HeaderName::HOST,
+	HeaderName::CACHE_CONTROL,
+	HeaderName::EXPECT,
+	HeaderName::MAX_FORWARDS,
+	HeaderName::PRAGMA,
+	HeaderName::RANGE,
+	HeaderName::TE,
+	HeaderName::AUTHORIZATION,
+	HeaderName::SET_COOKIE,
+	HeaderName::AGE,
+	HeaderName::EXPIRES,
+	HeaderName::DATE,
+	HeaderName::LOCATION,
+	HeaderName::RETRY_AFTER,
+	HeaderName::VARY,
+	HeaderName::WARNING,
+	HeaderName::CONTENT_ENCODING,
+	HeaderName::CONTENT_TYPE,
+	HeaderName::CONTENT_RANGE,
+	HeaderName::TRAILER,
+];
+
+/// A ChunkExtension is a name and optional value sent along with the size of a chunk.
+///
+/// A recipient MUST ignore unrecognized chunk extensions, so they are parsed and made available
+/// but do not affect the decoded body.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ChunkExtension {
+	/// The chunk-ext-name.
+	pub name: String,
+	/// The chunk-ext-val, with a quoted-string unescaped.
+	pub value: Option<String>,
+}
+
+/// A ChunkedDecoder reads the payload body from a chunked message body.
+///
+/// The decoder reads exactly the chunked body from the underlying reader, so that the bytes that
+/// follow it, e.g. the next message on a persistent connection, are left in the reader. Once
+/// `read` returns 0 the trailer fields are available from `trailers`.
+///
+/// Errors in the chunked body are reported as `io::Error`s of kind `InvalidData` wrapping a
+/// `ChunkedError`, or of kind `UnexpectedEof` if the body ends early.
+///
+/// ```
+/// use std::io::Read;
+/// use http::ChunkedDecoder;
+///
+/// let body = b"4\r\nWiki\r\n5;lang=en\r\npedia\r\n0\r\nX-Checksum: 42\r\n\r\n";
+/// let mut decoder = ChunkedDecoder::new(&body[..]);
+/// let mut payload = String::new();
+/// decoder.read_to_string(&mut payload).unwrap();
+/// assert_eq!("Wikipedia", payload);
+/// assert_eq!("42", decoder.trailers().get("X-Checksum").unwrap());
+/// ```
+#[derive(Debug)]
+pub struct ChunkedDecoder<R> {
+	reader: R,
+	state: DecoderState,
+	extensions: Vec<ChunkExtension>,
+	trailers: HeaderMap,
+	trailer_count: usize,
+	decoded: u64,
+	max_line_length: usize,
+	max_trailers: usize,
+	max_body_size: Option<u64>,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum DecoderState {
+	/// Expecting a chunk-size line.
+	Size,
+	/// In the chunk-data, with the given number of octets left.
+	Data(u64),
+	/// Expecting the CRLF that ends the chunk-data.
+	DataEnd,
+	/// Expecting trailer fields, or the empty line that ends the body.
+	Trailer,
+	/// The whole chunked body has been read.
+	Done,
+}
+
+impl<R: BufRead> ChunkedDecoder<R> {
+	/// Creates a decoder reading a chunked body from `reader`, with the default limits.
+	pub fn new(reader: R) -> ChunkedDecoder<R> {
+		ChunkedDecoder {
+			reader,
+			state: DecoderState::Size,
+			extensions: Vec::new(),
+			trailers: HeaderMap::new(),
+			trailer_count: 0,
+			decoded: 0,
+			max_line_length: DEFAULT_MAX_CHUNK_LINE_LENGTH,
+			max_trailers: DEFAULT_MAX_HEADERS,
+			max_body_size: None,
+		}
+	}
+
+	/// Sets the limit on the length of a chunk-size line, including its chunk extensions, and of
+	/// a trailer field line.
+	pub fn max_line_length(mut self, max: usize) -> ChunkedDecoder<R> {
+		self.max_line_length = max;
+		self
+	}
+
+	/// Sets the limit on the number of trailer fields.
+	pub fn max_trailers(mut self, max: usize) -> ChunkedDecoder<R> {
+		self.max_trailers = max;
+		self
+	}
+
+	/// Sets the limit on the size of the decoded payload body, which is unlimited by default.
+	pub fn max_body_size(mut self, max: u64) -> ChunkedDecoder<R> {
+		self.max_body_size = Some(max);
+		self
+	}
+
+	/// Returns the chunk extensions of the chunk being read.
+	pub fn extensions(&self) -> &[ChunkExtension] {
+		&self.extensions
+	}
+
+	/// Returns the trailer fields, which are complete once the whole body has been read.
+	///
+	/// Fields that are forbidden in a trailer are left out, as a recipient MUST NOT merge them
+	/// into the header section.
+	pub fn trailers(&self) -> &HeaderMap {
+		&self.trailers
+	}
+
+	/// Returns true once the whole chunked body, including the trailer, has been read.
+	pub fn is_done(&self) -> bool {
+		self.state == DecoderState::Done
+	}
+
+	/// Returns the underlying reader, positioned after the chunked body if it has been read.
+	pub fn into_inner(self) -> R {
+		self.reader
+	}
+
+	/// Reads a line, without its CRLF.
+	fn read_line(&mut self) -> io::Result<Vec<u8>> {
+		let mut line = Vec::new();
+		loop {
+			let (done, used) = {
+				let buf = self.reader.fill_buf()?;
+				if buf.is_empty() {
+					return Err(io::ErrorKind::UnexpectedEof.into());
+				}
+				match buf.iter().position(|&b| b == b'\n') {
+					Some(i) => {
+						line.extend_from_slice(&buf[..i]);
+						(true, i + 1)
+					}
+					None => {
+						line.extend_from_slice(buf);
+						(false, buf.len())
+					}
+				}
+			};
+			self.reader.consume(used);
+
+			// Allow for the CR of the line ending.
+			if line.len() > self.max_line_length + 1 {
+				return Err(ChunkedError::LineTooLong.into());
+			}
+			if done {
+				break;
+			}
+		}
+
+		match line.pop() {
+			Some(b'\r') if line.len() <= self.max_line_length => Ok(line),
+			Some(b'\r') => Err(ChunkedError::LineTooLong.into()),
+			_ => Err(ChunkedError::InvalidLineEnding.into()),
+		}
+	}
+
+	fn read_size(&mut self) -> io::Result<()> {
+		let line = self.read_line()?;
+		let digits = line.iter().take_while(|b| b.is_ascii_hexdigit()).count();
+		if digits == 0 {
+			return Err(ChunkedError::InvalidChunkSize.into());
+		}
+		let size = str::from_utf8(&line[..digits]).ok()
+			.and_then(|digits| u64::from_str_radix(digits, 16).ok())
+			.ok_or(ChunkedError::InvalidChunkSize)?;
+
+		self.extensions = str::from_utf8(&line[digits..]).ok()
+			.and_then(parse_extensions)
+			.ok_or(ChunkedError::InvalidExtension)?;
+
+		if self.max_body_size.is_some_and(|max| self.decoded.saturating_add(size) > max) {
+			return Err(ChunkedError::BodyTooLarge.into());
+		}
+
+		self.state = if size == 0 { DecoderState::Trailer } else { DecoderState::Data(size) };
+		Ok(())
+	}
+
+	fn read_trailer(&mut self) -> io::Result<()> {
+		let line = self.read_line()?;
+		if line.is_empty() {
+			self.state = DecoderState::Done;
+			return Ok(());
+		}
+
+		self.trailer_count += 1;
+		if self.trailer_count > self.max_trailers {
+			return Err(ChunkedError::TooManyTrailers.into());
+		}
+		if line[0] == b' ' || line[0] == b'\t' {
+			return Err(ChunkedError::InvalidTrailer.into());
+		}
+		let field = parser::parse_header_field(&line).map_err(|_| ChunkedError::InvalidTrailer)?;
+		let name = HeaderName::from_bytes(field.name.as_bytes())
+			.map_err(|_| ChunkedError::InvalidTrailer)?;
+		let value = HeaderValue::from_bytes(field.value).map_err(|_| ChunkedError::InvalidTrailer)?;
+		if !FORBIDDEN_TRAILERS.contains(&name) {
+			self.trailers.append(name, value);
+		}
+		Ok(())
+	}
+}
+
+impl<R: BufRead> Read for ChunkedDecoder<R> {
+	fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+		loop {
+			match self.state {
+				DecoderState::Size => self.read_size()?,
+				DecoderState::Data(remaining) => {
+					if out.is_empty() {
+						return Ok(0);
+					}
+					let n = {
+						let buf = self.reader.fill_buf()?;
+						if buf.is_empty() {
+							return Err(io::ErrorKind::UnexpectedEof.into());
+						}
+						let n = buf.len().min(out.len()).min(remaining.min(usize::MAX as u64) as usize);
+						out[..n].copy_from_slice(&buf[..n]);
+						n
+					};
+					self.reader.consume(n);
+					self.decoded += n as u64;
+					let remaining = remaining - n as u64;
+					self.state = if remaining == 0 { DecoderState::DataEnd } else { DecoderState::Data(remaining) };
+					return Ok(n);
+				}
+				DecoderState::DataEnd => {
+					if !self.read_line()?.is_empty() {
+						return Err(ChunkedError::InvalidLineEnding.into());
+					}
+					self.state = DecoderState::Size;
+				}
+				DecoderState::Trailer => self.read_trailer()?,
+				DecoderState::Done => return Ok(0),
+			}
+		}
+	}
+}
+
+/// Parses the chunk extensions that follow the chunk-size, allowing whitespace around the
+/// delimiters as some senders generate it.
+fn parse_extensions(mut s: &str) -> Option<Vec<ChunkExtension>> {
+	let mut extensions = Vec::new();
+	loop {
+		s = s.trim_start_matches(is_bws);
+		if s.is_empty() {
+			return Some(extensions);
+		}
+		s = s.strip_prefix(';')?.trim_start_matches(is_bws);
+
+		let name_len = s.bytes().take_while(|&b| token::is_tchar(b)).count();
+		if name_len == 0 {
+			return None;
+		}
+		let name = s[..name_len].to_string();
+		s = s[name_len..].trim_start_matches(is_bws);
+
+		let value = match s.strip_prefix('=') {
+			Some(rest) => {
+				s = rest.trim_start_matches(is_bws);
+				if s.starts_with('"') {
+					let (value, rest) = token::parse_quoted_string(s)?;
+					s = rest;
+					Some(value)
+				} else {
+					let value_len = s.bytes().take_while(|&b| token::is_tchar(b)).count();
+					if value_len == 0 {
+						return None;
+					}
+					let value = s[..value_len].to_string();
+					s = &s[value_len..];
+					Some(value)
+				}
+			}
+			None => None,
+		};
+		extensions.push(ChunkExtension { name, value });
+	}
+}
+
+fn is_bws(c: char) -> bool {
+	c == ' ' || c == '\t'
+}
+
+/// A ChunkedEncoder writes a payload body to a chunked message body.
+///
+/// Each call to `write` sends the given bytes as one chunk; wrap the encoder in a `BufWriter` to
+/// send larger chunks. The body must be ended with `finish` or `finish_with_trailers`, which send
+/// the zero-length last chunk; that alone is also a valid empty body, e.g. for a 205 (Reset
+/// Content) response.
+///
+/// ```
+/// use std::io::Write;
+/// use http::ChunkedEncoder;
+///
+/// let mut encoder = ChunkedEncoder::new(Vec::new());
+/// encoder.write_all(b"Wiki").unwrap();
+/// encoder.write_all(b"pedia").unwrap();
+/// assert_eq!(b"4\r\nWiki\r\n5\r\npedia\r\n0\r\n\r\n".to_vec(), encoder.finish().unwrap());
+/// ```
+#[derive(Debug)]
+pub struct ChunkedEncoder<W: Write> {
+	writer: W,
+}
+
+impl<W: Write> ChunkedEncoder<W> {
+	/// Creates an encoder writing a chunked body to `writer`.
+	pub fn new(writer: W) -> ChunkedEncoder<W> {
+		ChunkedEncoder { writer }
+	}
+
+	/// Writes `data` as one chunk with the given chunk extensions.
+	///
+	/// Nothing is written for empty `data`, as a zero-length chunk would end the body.
+	pub fn write_chunk(&mut self, data: &[u8], extensions: &[ChunkExtension]) -> io::Result<()> {
+		if data.is_empty() {
+			return Ok(());
+		}
+		write!(self.writer, "{:X}", data.len())?;
+		write_extensions(&mut self.writer, extensions)?;
+		self.writer.write_all(b"\r\n")?;
+		self.writer.write_all(data)?;
+		self.writer.write_all(b"\r\n")
+	}
+
+	/// Ends the body with the last chunk and an empty trailer, and returns the underlying writer.
+	pub fn finish(self) -> io::Result<W> {
+		self.finish_with_trailers(&HeaderMap::new())
+	}
+
+	/// Ends the body with the last chunk and the given trailer fields, and returns the underlying
+	/// writer.
+	///
+	/// Returns an error of kind `InvalidInput` if a trailer field is one that is forbidden in a
+	/// trailer.
+	pub fn finish_with_trailers(mut self, trailers: &HeaderMap) -> io::Result<W> {
+		if trailers.iter().any(|(name, _)| FORBIDDEN_TRAILERS.contains(name)) {
+			return Err(io::Error::new(io::ErrorKind::InvalidInput, ChunkedError::InvalidTrailer));
+		}
+
+		self.writer.write_all(b"0\r\n")?;
+		for (name, value) in trailers {
+			self.writer.write_all(name.as_str().as_bytes())?;
+			self.writer.write_all(b": ")?;
+			self.writer.write_all(value.as_bytes())?;
+			self.writer.write_all(b"\r\n")?;
+		}
+		self.writer.write_all(b"\r\n")?;
+		self.writer.flush()?;
+		Ok(self.writer)
+	}
+}
+
+impl<W: Write> Write for ChunkedEncoder<W> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.write_chunk(buf, &[])?;
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.writer.flush()
+	}
+}
+
+fn write_extensions<W: Write>(w: &mut W, extensions: &[ChunkExtension]) -> io::Result<()> {
+	for extension in extensions {
+		if !token::is_token(extension.name.as_bytes()) {
+			return Err(io::Error::new(io::ErrorKind::InvalidInput, ChunkedError::InvalidExtension));
+		}
+		write!(w, ";{}", extension.name)?;
+
+		match extension.value {
+			Some(ref value) if token::is_token(value.as_bytes()) => write!(w, "={}", value)?,
+			Some(ref value) => {
+				if value.chars().any(|c| c != '\t' && c.is_ascii_control()) {
+					return Err(io::Error::new(io::ErrorKind::InvalidInput, ChunkedError::InvalidExtension));
+				}
+				let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+				write!(w, "=\"{}\"", escaped)?;
+			}
+			None => {}
+		}
+	}
+	Ok(())
+}
+
+/// An error in a chunked body.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ChunkedError {
+	/// The chunk-size is not a hexadecimal number, or is too large.
+	InvalidChunkSize,
+	/// The chunk extensions do not match their grammar.
+	InvalidExtension,
+	/// A line does not end with CRLF, or the chunk-data is longer than the chunk-size.
+	InvalidLineEnding,
+	/// A trailer field is not a valid header field, or is forbidden in a trailer.
+	InvalidTrailer,
+	/// A chunk-size line or trailer field line is longer than the limit.
+	LineTooLong,
+	/// There are more trailer fields than the limit.
+	TooManyTrailers,
+	/// The payload body is larger than the limit.
+	BodyTooLarge,
+}
+
+impl fmt::Display for ChunkedError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let description = match *self {
+			ChunkedError::InvalidChunkSize => "invalid chunk-size",
+			ChunkedError::InvalidExtension => "invalid chunk extension",
+			ChunkedError::InvalidLineEnding => "invalid line ending in chunked body",
+			ChunkedError::InvalidTrailer => "invalid trailer field",
+			ChunkedError::LineTooLong => "chunk-size or trailer line too long",
+			ChunkedError::TooManyTrailers => "too many trailer fields",
+			ChunkedError::BodyTooLarge => "chunked body too large",
+		};
+		f.write_str(description)
+	}
+}
+
+impl Error for ChunkedError {}
+
+impl From<ChunkedError> for io::Error {
+	fn from(err: ChunkedError) -> io::Error {
+		io::Error::new(io::ErrorKind::InvalidData, err)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::io::{self, Read};
+
+	fn decode(body: &[u8]) -> io::Result<Vec<u8>> {
+		use super::ChunkedDecoder;
+
+		let mut decoded = Vec::new();
+		ChunkedDecoder::new(body).read_to_end(&mut decoded)?;
+		Ok(decoded)
+	}
+
+	fn decode_error(body: &[u8]) -> Option<super::ChunkedError> {
+		let err = decode(body).unwrap_err();
+		err.get_ref().and_then(|err| err.downcast_ref()).cloned()
+	}
+
+	#[test]
+	fn chunked_decoder_test() {
+		let test_cases = vec![
+			(&b"0\r\n\r\n"[..], &b""[..]),
+			(b"000\r\n\r\n", b""),
+			(b"3\r\nabc\r\n0\r\n\r\n", b"abc"),
+			(b"a\r\n0123456789\r\n1\r\nX\r\n0\r\n\r\n", b"0123456789X"),
+			(b"A;x\r\n0123456789\r\n0;y=\"z\"\r\n\r\n", b"0123456789"),
+			(b"3 ; name = value\t;flag\r\nabc\r\n0\r\n\r\n", b"abc"),
+			(b"00000000000000003\r\nabc\r\n0\r\n\r\n", b"abc"),
+		];
+
+		for (body, expect) in test_cases {
+			assert_eq!(expect.to_vec(), decode(body).unwrap(), "{:?}", body);
+		}
+	}
+
+	#[test]
+	fn chunked_decoder_streaming_test() {
+		use std::io::BufReader;
+		use super::{ChunkExtension, ChunkedDecoder};
+
+		// A reader that hands out a single byte at a time.
+		let body = &b"5;a=\"b c\"\r\nhello\r\n6\r\n world\r\n0\r\nX-Checksum: 42\r\n\r\nNEXT"[..];
+		let mut decoder = ChunkedDecoder::new(BufReader::with_capacity(1, body));
+
+		let mut decoded = Vec::new();
+		let mut buf = [0; 3];
+		loop {
+			let n = decoder.read(&mut buf).unwrap();
+			if n == 0 {
+				break;
+			}
+			if decoded.is_empty() {
+				let expect = ChunkExtension { name: "a".to_string(), value: Some("b c".to_string()) };
+				assert_eq!(&[expect][..], decoder.extensions());
+			}
+			decoded.extend_from_slice(&buf[..n]);
+		}
+
+		assert_eq!(b"hello world".to_vec(), decoded);
+		assert!(decoder.is_done());
+		assert_eq!("42", decoder.trailers().get("x-checksum").unwrap());
+
+		// The bytes after the chunked body are left in the reader.
+		let mut rest = Vec::new();
+		decoder.into_inner().read_to_end(&mut rest).unwrap();
+		assert_eq!(b"NEXT".to_vec(), rest);
+	}
+
+	#[test]
+	fn chunked_decoder_trailers_test() {
+		use super::ChunkedDecoder;
+
+		let body = &b"0\r\nA: 1\r\nContent-Length: 9\r\na: 2\r\nTransfer-Encoding: gzip\r\nB:\r\n\r\n"[..];
+		let mut decoder = ChunkedDecoder::new(body);
+		decoder.read_to_end(&mut Vec::new()).unwrap();
+
+		let trailers = decoder.trailers();
+		assert_eq!(vec!["1", "2"], trailers.get_all("A").map(|v| v.to_str().unwrap()).collect::<Vec<_>>());
+		assert_eq!("", trailers.get("B").unwrap());
+		assert!(!trailers.contains_key("Content-Length"));
+		assert!(!trailers.contains_key("Transfer-Encoding"));
+	}
+
+	#[test]
+	fn chunked_decoder_error_test() {
+		use super::ChunkedError;
+
+		let test_cases = vec![
+			(&b"\r\n"[..], ChunkedError::InvalidChunkSize),
+			(b"x\r\n", ChunkedError::InvalidChunkSize),
+			(b"-1\r\n", ChunkedError::InvalidChunkSize),
+			(b"+1\r\na\r\n0\r\n\r\n", ChunkedError::InvalidChunkSize),
+			(b"10000000000000000\r\n", ChunkedError::InvalidChunkSize),
+			(b"3x\r\nabc\r\n0\r\n\r\n", ChunkedError::InvalidExtension),
+			(b"3;\r\nabc\r\n0\r\n\r\n", ChunkedError::InvalidExtension),
+			(b"3;a=\r\nabc\r\n0\r\n\r\n", ChunkedError::InvalidExtension),
+			(b"3;a=\"b\r\nabc\r\n0\r\n\r\n", ChunkedError::InvalidExtension),
+			(b"3\nabc\r\n0\r\n\r\n", ChunkedError::InvalidLineEnding),
+			(b"3\r\nabcd\r\n0\r\n\r\n", ChunkedError::InvalidLineEnding),
+			(b"3\r\nabc0\r\n\r\n", ChunkedError::InvalidLineEnding),
+			(b"0\r\nA b\r\n\r\n", ChunkedError::InvalidTrailer),
+			(b"0\r\nA: b\r\n c\r\n\r\n", ChunkedError::InvalidTrailer),
+			(b"0\r\nA: b\x00\r\n\r\n", ChunkedError::InvalidTrailer),
+		];
+
+		for (body, expect) in test_cases {
+			assert_eq!(Some(expect), decode_error(body), "{:?}", body);
+		}
+
+		for body in [&b""[..], b"3\r\nab", b"3\r\nabc", b"0\r\n", b"0\r\nA: b\r\n"] {
+			assert_eq!(io::ErrorKind::UnexpectedEof, decode(body).unwrap_err().kind(), "{:?}", body);
+		}
+	}
+
+	#[test]
+	fn chunked_decoder_limits_test() {
+		use super::{ChunkedDecoder, ChunkedError};
+
+		let limited = |body: &'static [u8]| {
+			let mut decoder = ChunkedDecoder::new(body).max_line_length(8).max_trailers(1).max_body_size(5);
+			let err = decoder.read_to_end(&mut Vec::new()).unwrap_err();
+			err.get_ref().and_then(|err| err.downcast_ref::<ChunkedError>()).cloned()
+		};
+
+		assert_eq!(Some(ChunkedError::LineTooLong), limited(b"3;abcdefgh\r\n"));
+		assert_eq!(Some(ChunkedError::LineTooLong), limited(b"0\r\nA: 123456\r\n\r\n"));
+		assert_eq!(Some(ChunkedError::TooManyTrailers), limited(b"0\r\nA: 1\r\nB: 2\r\n\r\n"));
+		assert_eq!(Some(ChunkedError::BodyTooLarge), limited(b"3\r\nabc\r\n3\r\nabc\r\n0\r\n\r\n"));
+		assert_eq!(Some(ChunkedError::BodyTooLarge), limited(b"fffffff\r\n"));
+
+		let mut decoder = ChunkedDecoder::new(&b"5;abcdef\r\nabcde\r\n0\r\nA: 12345\r\n\r\n"[..])
+			.max_line_length(8)
+			.max_trailers(1)
+			.max_body_size(5);
+		let mut decoded = Vec::new();
+		decoder.read_to_end(&mut decoded).unwrap();
+		assert_eq!(b"abcde".to_vec(), decoded);
+	}
+
+	#[test]
+	fn chunked_encoder_test() {
+		use std::io::Write;
+		use header::{HeaderMap, HeaderName, HeaderValue};
+		use super::{ChunkExtension, ChunkedDecoder, ChunkedEncoder};
+
+		let mut encoder = ChunkedEncoder::new(Vec::new());
+		encoder.write_all(b"").unwrap();
+		encoder.write_all(b"0123456789abcdef!").unwrap();
+		let extensions = vec![
+			ChunkExtension { name: "a".to_string(), value: None },
+			ChunkExtension { name: "b".to_string(), value: Some("c".to_string()) },
+			ChunkExtension { name: "d".to_string(), value: Some("e \"f\"".to_string()) },
+		];
+		encoder.write_chunk(b"xyz", &extensions).unwrap();
+		let mut trailers = HeaderMap::new();
+		trailers.append(HeaderName::from_static("X-Checksum"), HeaderValue::from_static("42"));
+		let body = encoder.finish_with_trailers(&trailers).unwrap();
+
+		let expect = "11\r\n0123456789abcdef!\r\n3;a;b=c;d=\"e \\\"f\\\"\"\r\nxyz\r\n0\r\nX-Checksum: 42\r\n\r\n";
+		assert_eq!(expect, String::from_utf8(body.clone()).unwrap());
+
+		let mut decoder = ChunkedDecoder::new(&body[..]);
+		let mut decoded = Vec::new();
+		decoder.read_to_end(&mut decoded).unwrap();
+		assert_eq!(b"0123456789abcdef!xyz".to_vec(), decoded);
+		assert_eq!(trailers, *decoder.trailers());
+
+		// The last chunk alone is an empty body.
+		assert_eq!(b"0\r\n\r\n".to_vec(), ChunkedEncoder::new(Vec::new()).finish().unwrap());
+	}
+
+	#[test]
+	fn chunked_encoder_error_test() {
+		use std::io;
+		use header::{HeaderMap, HeaderName, HeaderValue};
+		use super::{ChunkExtension, ChunkedEncoder};
+
+		let mut trailers = HeaderMap::new();
+		trailers.append(HeaderName::CONTENT_LENGTH, HeaderValue::from(3));
+		let err = ChunkedEncoder::new(Vec::new()).finish_with_trailers(&trailers).unwrap_err();
+		assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+
+		let extensions = vec![ChunkExtension { name: "a b".to_string(), value: None }];
+		let err = ChunkedEncoder::new(Vec::new()).write_chunk(b"x", &extensions).unwrap_err();
+		assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+	}
+}
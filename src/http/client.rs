@@ -0,0 +1,499 @@
+//! A blocking HTTP/1.1 client.
+
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+use chunked::{ChunkedDecoder, ChunkedError};
+use framing::{self, Framing, FramingError};
+use message::{Request, Response};
+use method::Method;
+use parser::{ParseError, Parser, Status, DEFAULT_MAX_HEADERS, DEFAULT_MAX_LINE_LENGTH};
+use serializer::{self, SerializeError};
+use statuscodes::{StatusCode, StatusCodeClass};
+use typed_headers::Expect;
+use upgrade::{self, Upgraded};
+
+/// The default limit on the size of a response body, in bytes.
+pub const DEFAULT_MAX_RESPONSE_BODY_SIZE: u64 = 64 * 1024 * 1024;
+
+/// The default time to wait for 100 (Continue) before sending the body of a request that expects
+/// it.
+pub const DEFAULT_EXPECT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A Client sends requests to a server over one connection, and reads the responses.
+///
+/// A request with an "Expect: 100-continue" header field and a body is sent in two steps: the
+/// request-line and header fields first, then the body once the server answers with 100
+/// (Continue), or once the expect timeout has passed without an answer, since a server that
+/// does not support the expectation may never send one. A server that answers with a final status
+/// instead never receives the body.
+///
+/// ```no_run
+/// use http::{Client, Expect, Host, Method, Request};
+///
+/// let mut client = Client::connect("127.0.0.1:8080").unwrap();
+/// let request = Request::builder()
+///     .method(Method::Put)
+///     .typed_header(Host::new("127.0.0.1", Some(8080)).unwrap())
+///     .typed_header(Expect::continue_100())
+///     .body(vec![0; 1 << 20]);
+/// let response = client.send(&request).unwrap();
+/// println!("{}", response.status);
+/// ```
+#[derive(Debug)]
+pub struct Client {
+	reader: BufReader<TcpStream>,
+	expect_timeout: Duration,
+	max_line_length: usize,
+	max_headers: usize,
+	max_body_size: u64,
+}
+
+impl Client {
+	/// Creates a client connected to the given address.
+	pub fn connect<A: ToSocketAddrs>(addr: A) -> io::Result<Client> {
+		TcpStream::connect(addr).map(Client::from_stream)
+	}
+
+	/// Creates a client sending requests on a connected stream, with the default limits.
+	pub fn from_stream(stream: TcpStream) -> Client {
+		Client {
+			reader: BufReader::new(stream),
+			expect_timeout: DEFAULT_EXPECT_TIMEOUT,
+			max_line_length: DEFAULT_MAX_LINE_LENGTH,
+			max_headers: DEFAULT_MAX_HEADERS,
+			max_body_size: DEFAULT_MAX_RESPONSE_BODY_SIZE,
+		}
+	}
+
+	/// Sets how long to wait for 100 (Continue) before sending the body anyway.
+	pub fn expect_timeout(mut self, timeout: Duration) -> Client {
+		self.expect_timeout = timeout;
+		self
+	}
+
+	/// Sets the limit on the length of the status-line and of each header field line.
+	pub fn max_line_length(mut self, max: usize) -> Client {
+		self.max_line_length = max;
+		self
+	}
+
+	/// Sets the limit on the number of header fields in a response.
+	pub fn max_headers(mut self, max: usize) -> Client {
+		self.max_headers = max;
+		self
+	}
+
+	/// Sets the limit on the size of a response body.
+	pub fn max_body_size(mut self, max: u64) -> Client {
+		self.max_body_size = max;
+		self
+	}
+
+	/// Returns the connection.
+	pub fn get_ref(&self) -> &TcpStream {
+		self.reader.get_ref()
+	}
+
+	/// Sends a request and reads the final response to it, skipping any 1xx (Informational)
+	/// responses other than 101 (Switching Protocols).
+	///
+	/// If the server answers a request that expects 100 (Continue) with a final status, the body
+	/// is not sent, and the connection cannot be used for further requests.
+	pub fn send<B: AsRef<[u8]>>(&mut self, request: &Request<B>) -> Result<Response, ClientError> {
+		let body = request.body.as_ref();
+		let framing = {
+			let mut writer = BufWriter::new(self.reader.get_ref());
+			let framing = serializer::write_request_head(&mut writer, request, Some(body.len() as u64))?;
+			writer.flush()?;
+			framing
+		};
+
+		let expects_continue = request.headers.get_typed::<Expect>()
+			.is_ok_and(|expect| expect.is_some_and(|expect| expect.is_continue()));
+		if expects_continue && framing != Framing::Empty {
+			if let Some(response) = self.wait_for_continue(&request.method)? {
+				return Ok(response);
+			}
+		}
+
+		{
+			let mut writer = BufWriter::new(self.reader.get_ref());
+			serializer::write_body(&mut writer, framing, body)?;
+			writer.flush()?;
+		}
+
+		loop {
+			let response = self.read_response(&request.method)?;
+			if !is_interim(response.status) {
+				return Ok(response);
+			}
+		}
+	}
+
+	/// Sends a request asking to upgrade the connection, and returns the connection once the server
+	/// switches to protocols the request asked for.
+	///
+	/// The request needs an Upgrade header field and the "upgrade" connection option. If the
+	/// server answers with anything else, the response is returned as `UpgradeRefused`.
+	pub fn upgrade<B: AsRef<[u8]>>(mut self, request: &Request<B>)
+		-> Result<(Response, Upgraded), ClientError>
+	{
+		let response = self.send(request)?;
+		if response.status != StatusCode::SwitchingProtocols ||
+			!upgrade::is_valid_switch(request, &response)
+		{
+			return Err(ClientError::UpgradeRefused(Box::new(response)));
+		}
+		Ok((response, self.into_upgraded()))
+	}
+
+	/// Returns the connection, with the bytes already read from it, e.g. after `send` returned a
+	/// 101 (Switching Protocols) response.
+	pub fn into_upgraded(self) -> Upgraded {
+		let buffered = self.reader.buffer().to_vec();
+		Upgraded::new(self.reader.into_inner(), buffered)
+	}
+
+	/// Waits up to the expect timeout for the server to answer a request whose body has not been
+	/// sent, returning its final response if it sends one.
+	fn wait_for_continue(&mut self, method: &Method) -> Result<Option<Response>, ClientError> {
+		let deadline = Instant::now() + self.expect_timeout;
+		loop {
+			if !self.wait_for_data(deadline)? {
+				return Ok(None);
+			}
+			let response = self.read_response(method)?;
+			if response.status == StatusCode::Continue {
+				return Ok(None);
+			}
+			if !is_interim(response.status) {
+				return Ok(Some(response));
+			}
+		}
+	}
+
+	/// Waits until there are bytes to read or the deadline passes, returning whether there are.
+	fn wait_for_data(&mut self, deadline: Instant) -> io::Result<bool> {
+		let now = Instant::now();
+		if now >= deadline {
+			return Ok(false);
+		}
+
+		let timeout = self.reader.get_ref().read_timeout()?;
+		self.reader.get_ref().set_read_timeout(Some(deadline - now))?;
+		let available = self.reader.fill_buf().map(|buf| !buf.is_empty());
+		self.reader.get_ref().set_read_timeout(timeout)?;
+
+		match available {
+			Ok(true) => Ok(true),
+			Ok(false) => Err(io::ErrorKind::UnexpectedEof.into()),
+			Err(ref err) if err.kind() == io::ErrorKind::WouldBlock ||
+				err.kind() == io::ErrorKind::TimedOut => Ok(false),
+			Err(err) => Err(err),
+		}
+	}
+
+	/// Reads a response to a request with the given method.
+	fn read_response(&mut self, method: &Method) -> Result<Response, ClientError> {
+		let mut response = self.read_head()?;
+		let framing = framing::response_framing(method, response.status, &response.headers)?;
+
+		match framing {
+			Framing::Empty => {}
+			Framing::Length(len) if len > self.max_body_size => return Err(ClientError::BodyTooLarge),
+			Framing::Length(len) => {
+				(&mut self.reader).take(len).read_to_end(&mut response.body)?;
+				if (response.body.len() as u64) < len {
+					return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+				}
+			}
+			Framing::Chunked => {
+				let mut decoder = ChunkedDecoder::new(&mut self.reader)
+					.max_line_length(self.max_line_length)
+					.max_trailers(self.max_headers)
+					.max_body_size(self.max_body_size);
+				if let Err(err) = decoder.read_to_end(&mut response.body) {
+					return Err(match err.get_ref().and_then(|err| err.downcast_ref::<ChunkedError>()) {
+						Some(&ChunkedError::BodyTooLarge) => ClientError::BodyTooLarge,
+						Some(&err) => ClientError::Chunked(err),
+						None => ClientError::Io(err),
+					});
+				}
+				for (name, value) in decoder.trailers() {
+					response.headers.append(name.clone(), value.clone());
+				}
+			}
+			Framing::CloseDelimited => {
+				(&mut self.reader).take(self.max_body_size + 1).read_to_end(&mut response.body)?;
+				if response.body.len() as u64 > self.max_body_size {
+					return Err(ClientError::BodyTooLarge);
+				}
+			}
+		}
+		Ok(response)
+	}
+
+	/// Reads the status-line and header fields, leaving the body in the reader.
+	fn read_head(&mut self) -> Result<Response, ClientError> {
+		let mut parser = Parser::new()
+			.max_line_length(self.max_line_length)
+			.max_headers(self.max_headers);
+		let mut buf = Vec::new();
+
+		loop {
+			let received = buf.len();
+			{
+				let available = self.reader.fill_buf()?;
+				if available.is_empty() {
+					return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+				}
+				buf.extend_from_slice(available);
+			}
+
+			match parser.parse_response(&buf)? {
+				Status::Partial => self.reader.consume(buf.len() - received),
+				Status::Complete(n, head) => {
+					self.reader.consume(n - received);
+					return Ok(Response {
+						version: head.version,
+						status: head.status,
+						headers: head.header_map(),
+						body: Vec::new(),
+					});
+				}
+			}
+		}
+	}
+}
+
+/// Returns true for the 1xx (Informational) responses that precede the final response.
+fn is_interim(status: StatusCode) -> bool {
+	status.class() == Some(StatusCodeClass::Informational) && status != StatusCode::SwitchingProtocols
+}
+
+/// An error returned when a request cannot be sent or its response cannot be received.
+#[derive(Debug)]
+pub enum ClientError {
+	/// The request cannot be serialized.
+	Serialize(SerializeError),
+	/// The response head is malformed or exceeds the limits.
+	Parse(ParseError),
+	/// The header fields of the response do not describe a valid body framing.
+	Framing(FramingError),
+	/// The chunked body of the response is malformed or exceeds the limits.
+	Chunked(ChunkedError),
+	/// The response body exceeds the size limit.
+	BodyTooLarge,
+	/// The server did not switch to a protocol the request asked to upgrade to.
+	UpgradeRefused(Box<Response>),
+	/// The connection failed or was closed.
+	Io(io::Error),
+}
+
+impl fmt::Display for ClientError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			ClientError::Serialize(ref err) => err.fmt(f),
+			ClientError::Parse(ref err) => err.fmt(f),
+			ClientError::Framing(ref err) => err.fmt(f),
+			ClientError::Chunked(ref err) => err.fmt(f),
+			ClientError::BodyTooLarge => f.write_str("response body too large"),
+			ClientError::UpgradeRefused(ref response) => {
+				write!(f, "upgrade refused with {}", response.status)
+			}
+			ClientError::Io(ref err) => err.fmt(f),
+		}
+	}
+}
+
+impl Error for ClientError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match *self {
+			ClientError::Serialize(ref err) => Some(err),
+			ClientError::Parse(ref err) => Some(err),
+			ClientError::Framing(ref err) => Some(err),
+			ClientError::Chunked(ref err) => Some(err),
+			ClientError::BodyTooLarge | ClientError::UpgradeRefused(_) => None,
+			ClientError::Io(ref err) => Some(err),
+		}
+	}
+}
+
+impl From<SerializeError> for ClientError {
+	fn from(err: SerializeError) -> ClientError {
+		match err {
+			SerializeError::Io(err) => ClientError::Io(err),
+			err => ClientError::Serialize(err),
+		}
+	}
+}
+
+impl From<ParseError> for ClientError {
+	fn from(err: ParseError) -> ClientError {
+		ClientError::Parse(err)
+	}
+}
+
+impl From<FramingError> for ClientError {
+	fn from(err: FramingError) -> ClientError {
+		ClientError::Framing(err)
+	}
+}
+
+impl From<io::Error> for ClientError {
+	fn from(err: io::Error) -> ClientError {
+		ClientError::Io(err)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::io::{BufRead, BufReader, Read, Write};
+	use std::net::{SocketAddr, TcpListener};
+	use std::thread;
+	use std::time::{Duration, Instant};
+
+	use message::{Request, Response};
+	use method::Method;
+	use server::{Handler, Server};
+	use statuscodes::StatusCode;
+	use super::{Client, ClientError};
+	use typed_headers::{ContentLength, Expect, Host};
+	use uri::RequestTarget;
+
+	fn start<H: Handler>(server: Server, handler: H) -> SocketAddr {
+		let addr = server.local_addr().unwrap();
+		thread::spawn(move || server.serve(handler));
+		addr
+	}
+
+	fn put(path: &str, body: &[u8]) -> Request {
+		Request::builder()
+			.method(Method::Put)
+			.target(RequestTarget::parse(&Method::Put, path.as_bytes()).unwrap())
+			.typed_header(Host::new("x", None).unwrap())
+			.typed_header(Expect::continue_100())
+			.body(body.to_vec())
+	}
+
+	fn echo(request: Request) -> Response {
+		Response::builder()
+			.typed_header(ContentLength(request.body.len() as u64))
+			.body(request.body)
+	}
+
+	#[test]
+	fn client_test() {
+		let addr = start(Server::bind("127.0.0.1:0").unwrap(), echo);
+		let mut client = Client::connect(addr).unwrap();
+
+		// Requests are sent on the same connection.
+		for body in [&b"first"[..], b"second", b""] {
+			let request = Request::builder()
+				.method(Method::Post)
+				.typed_header(Host::new("x", None).unwrap())
+				.body(body.to_vec());
+			let response = client.send(&request).unwrap();
+			assert_eq!(StatusCode::Ok, response.status);
+			assert_eq!(body, &response.body[..]);
+		}
+
+		let request = Request::builder()
+			.method(Method::Head)
+			.typed_header(Host::new("x", None).unwrap())
+			.body(Vec::new());
+		let response = client.send(&request).unwrap();
+		assert_eq!(Some(ContentLength(0)), response.headers.get_typed().unwrap());
+		assert!(response.body.is_empty());
+	}
+
+	#[test]
+	fn client_expect_continue_test() {
+		struct Uploads;
+
+		impl Handler for Uploads {
+			fn handle(&self, request: Request) -> Response {
+				echo(request)
+			}
+
+			fn expect_continue(&self, request: &Request) -> Result<(), Response> {
+				match request.target.path() {
+					"/forbidden" => Err(Response::builder().status(StatusCode::Forbidden).body(Vec::new())),
+					_ => Ok(()),
+				}
+			}
+		}
+
+		let addr = start(Server::bind("127.0.0.1:0").unwrap().max_body_size(4), Uploads);
+		let client = || Client::connect(addr).unwrap().expect_timeout(Duration::from_secs(10));
+
+		let started = Instant::now();
+		let response = client().send(&put("/", b"data")).unwrap();
+		assert_eq!(StatusCode::Ok, response.status);
+		assert_eq!(b"data", &response.body[..]);
+		assert!(started.elapsed() < Duration::from_secs(10));
+
+		let response = client().send(&put("/forbidden", b"data")).unwrap();
+		assert_eq!(StatusCode::Forbidden, response.status);
+
+		let response = client().send(&put("/", b"too large")).unwrap();
+		assert_eq!(StatusCode::PayloadTooLarge, response.status);
+	}
+
+	#[test]
+	fn client_expect_timeout_test() {
+		// A server that does not know the expectation waits for the body without answering.
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let addr = listener.local_addr().unwrap();
+		thread::spawn(move || {
+			let (stream, _) = listener.accept().unwrap();
+			let mut reader = BufReader::new(stream.try_clone().unwrap());
+			let mut line = String::new();
+			while line != "\r\n" {
+				line.clear();
+				reader.read_line(&mut line).unwrap();
+			}
+			let mut body = [0; 4];
+			reader.read_exact(&mut body).unwrap();
+			(&stream).write_all(b"HTTP/1.1 201 Created\r\nContent-Length: 4\r\n\r\n").unwrap();
+			(&stream).write_all(&body).unwrap();
+		});
+
+		let mut client = Client::connect(addr).unwrap().expect_timeout(Duration::from_millis(50));
+		let response = client.send(&put("/", b"data")).unwrap();
+		assert_eq!(StatusCode::Created, response.status);
+		assert_eq!(b"data", &response.body[..]);
+	}
+
+	#[test]
+	fn client_error_test() {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let addr = listener.local_addr().unwrap();
+		thread::spawn(move || {
+			let responses = [&b"HTTP/1.1 200 OK\r\nContent-Length: 9\r\n\r\n123456789"[..],
+				b"HTTP/1.1 2OO OK\r\n\r\n"];
+			for response in responses {
+				let (mut stream, _) = listener.accept().unwrap();
+				let mut request = [0; 64];
+				let _ = stream.read(&mut request).unwrap();
+				stream.write_all(response).unwrap();
+			}
+		});
+
+		let request = Request::builder().typed_header(Host::new("x", None).unwrap()).body(Vec::new());
+		let mut client = Client::connect(addr).unwrap().max_body_size(8);
+		match client.send(&request) {
+			Err(ClientError::BodyTooLarge) => {}
+			other => panic!("{:?}", other),
+		}
+		let mut client = Client::connect(addr).unwrap();
+		match client.send(&request) {
+			Err(ClientError::Parse(_)) => {}
+			other => panic!("{:?}", other),
+		}
+	}
+}
@@ -0,0 +1,339 @@
+use std::error::Error;
+use std::fmt;
+use std::str::{self, FromStr};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A HttpDate is a timestamp as used by the Date, Last-Modified and Retry-After header fields.
+///
+/// HTTP-date is case sensitive and MUST NOT include additional whitespace beyond that
+/// specifically included as SP in the grammar
+/// ([Section 7.1.1.1 of \[RFC7231\]](http://tools.ietf.org/html/rfc7231#section-7.1.1.1)).
+///
+/// ```c
+/// HTTP-date    = IMF-fixdate / obs-date
+/// IMF-fixdate  = day-name "," SP date1 SP time-of-day SP GMT
+///              ; fixed length/zone/capitalization subset of the format
+///              ; see Section 3.3 of [RFC5322]
+/// obs-date     = rfc850-date / asctime-date
+/// rfc850-date  = day-name-l "," SP date2 SP time-of-day SP GMT
+/// asctime-date = day-name SP date3 SP time-of-day SP year
+/// ```
+///
+/// A recipient that parses a timestamp value in an HTTP header field MUST accept all three
+/// HTTP-date formats. When a sender generates a header field that contains one or more timestamps
+/// defined as HTTP-date, the sender MUST generate those timestamps in the IMF-fixdate format, so
+/// `Display` always produces an IMF-fixdate, e.g. "Sun, 06 Nov 1994 08:49:37 GMT".
+///
+/// The timestamp has a resolution of one second and cannot be earlier than 1970 or later than
+/// the year 9999.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub struct HttpDate {
+	secs: u64,
+}
+
+const DAY_NAMES: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+const DAY_NAMES_LONG: [&str; 7] =
+	["Sunday", "Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday"];
+const MONTH_NAMES: [&str; 12] =
+	["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+/// The first second of the year 10000.
+const MAX_SECS: u64 = 253_402_300_800;
+
+impl HttpDate {
+	/// Returns the current time, truncated to whole seconds.
+	pub fn now() -> HttpDate {
+		HttpDate::from(SystemTime::now())
+	}
+
+	/// Returns the number of seconds since 1970-01-01 00:00:00 UTC.
+	pub fn as_secs(self) -> u64 {
+		self.secs
+	}
+
+	/// Parses a HTTP-date in any of the three formats.
+	pub fn parse(s: &[u8]) -> Result<HttpDate, InvalidHttpDate> {
+		let fields = match s.get(3) {
+			Some(&b',') => parse_imf_fixdate(s),
+			Some(&b' ') => parse_asctime_date(s),
+			_ => parse_rfc850_date(s),
+		};
+		let (year, month, day, time) = fields.ok_or(InvalidHttpDate)?;
+
+		if year < 1970 || month == 0 || month > 12 || day == 0 || day > days_in_month(year, month) {
+			return Err(InvalidHttpDate);
+		}
+		let secs = days_from_civil(year, month, day) * 86400 + time;
+		if secs >= MAX_SECS {
+			return Err(InvalidHttpDate);
+		}
+		Ok(HttpDate { secs })
+	}
+
+	fn civil(self) -> (u64, u64, u64) {
+		civil_from_days(self.secs / 86400)
+	}
+}
+
+impl From<SystemTime> for HttpDate {
+	/// Converts a SystemTime, clamping it to the range a HttpDate can represent.
+	fn from(time: SystemTime) -> HttpDate {
+		let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+		HttpDate { secs: secs.min(MAX_SECS - 1) }
+	}
+}
+
+impl From<HttpDate> for SystemTime {
+	fn from(date: HttpDate) -> SystemTime {
+		UNIX_EPOCH + Duration::from_secs(date.secs)
+	}
+}
+
+impl FromStr for HttpDate {
+	type Err = InvalidHttpDate;
+
+	fn from_str(s: &str) -> Result<HttpDate, InvalidHttpDate> {
+		HttpDate::parse(s.as_bytes())
+	}
+}
+
+impl fmt::Display for HttpDate {
+	/// Formats the date as an IMF-fixdate.
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let (year, month, day) = self.civil();
+		let weekday = (self.secs / 86400 + 4) % 7;
+		let time = self.secs % 86400;
+		write!(f, "{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT", DAY_NAMES[weekday as usize], day,
+			MONTH_NAMES[month as usize - 1], year, time / 3600, time / 60 % 60, time % 60)
+	}
+}
+
+/// An error returned when a HTTP-date cannot be parsed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct InvalidHttpDate;
+
+impl fmt::Display for InvalidHttpDate {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("invalid HTTP-date")
+	}
+}
+
+impl Error for InvalidHttpDate {}
+
+/// Year, month, day and the second of the day.
+type Fields = (u64, u64, u64, u64);
+
+/// IMF-fixdate, e.g. "Sun, 06 Nov 1994 08:49:37 GMT".
+///
+/// ```c
+/// date1 = day SP month SP year
+///       ; e.g., 02 Jun 1982
+/// ```
+fn parse_imf_fixdate(s: &[u8]) -> Option<Fields> {
+	if s.len() != 29 || !DAY_NAMES.contains(&ascii(&s[..3])?) || &s[3..5] != b", " ||
+		s[7] != b' ' || s[11] != b' ' || s[16] != b' ' || &s[25..] != b" GMT" {
+		return None;
+	}
+	Some((number(&s[12..16])?, month(&s[8..11])?, number(&s[5..7])?, time_of_day(&s[17..25])?))
+}
+
+/// rfc850-date, e.g. "Sunday, 06-Nov-94 08:49:37 GMT".
+///
+/// ```c
+/// date2 = day "-" month "-" 2DIGIT
+///       ; e.g., 02-Jun-82
+/// ```
+///
+/// Recipients of a timestamp value in rfc850-date format, which uses a two-digit year, MUST
+/// interpret a timestamp that appears to be more than 50 years in the future as representing the
+/// most recent year in the past that had the same last two digits.
+fn parse_rfc850_date(s: &[u8]) -> Option<Fields> {
+	let comma = s.iter().position(|&b| b == b',')?;
+	let (day_name, s) = s.split_at(comma);
+	if !DAY_NAMES_LONG.contains(&ascii(day_name)?) || s.len() != 24 || &s[..2] != b", " ||
+		s[4] != b'-' || s[8] != b'-' || s[11] != b' ' || &s[20..] != b" GMT" {
+		return None;
+	}
+
+	let (current_year, _, _) = HttpDate::now().civil();
+	let mut year = current_year / 100 * 100 + number(&s[9..11])?;
+	if year > current_year + 50 {
+		year -= 100;
+	}
+	Some((year, month(&s[5..8])?, number(&s[2..4])?, time_of_day(&s[12..20])?))
+}
+
+/// asctime-date, e.g. "Sun Nov  6 08:49:37 1994".
+///
+/// ```c
+/// date3 = month SP ( 2DIGIT / ( SP 1DIGIT ))
+///       ; e.g., Jun  2
+/// ```
+fn parse_asctime_date(s: &[u8]) -> Option<Fields> {
+	if s.len() != 24 || !DAY_NAMES.contains(&ascii(&s[..3])?) || s[3] != b' ' || s[7] != b' ' ||
+		s[10] != b' ' || s[19] != b' ' {
+		return None;
+	}
+	let day = match s[8] {
+		b' ' => number(&s[9..10])?,
+		_ => number(&s[8..10])?,
+	};
+	Some((number(&s[20..24])?, month(&s[4..7])?, day, time_of_day(&s[11..19])?))
+}
+
+/// ```c
+/// time-of-day = hour ":" minute ":" second
+///             ; 00:00:00 - 23:59:60 (leap second)
+/// ```
+fn time_of_day(s: &[u8]) -> Option<u64> {
+	if s[2] != b':' || s[5] != b':' {
+		return None;
+	}
+	let (hour, minute, second) = (number(&s[..2])?, number(&s[3..5])?, number(&s[6..])?);
+	if hour > 23 || minute > 59 || second > 60 {
+		return None;
+	}
+	// A leap second is folded into the next second, as a timestamp cannot represent it.
+	Some(hour * 3600 + minute * 60 + second)
+}
+
+fn month(s: &[u8]) -> Option<u64> {
+	let s = ascii(s)?;
+	MONTH_NAMES.iter().position(|&name| name == s).map(|i| i as u64 + 1)
+}
+
+fn number(s: &[u8]) -> Option<u64> {
+	if !s.iter().all(u8::is_ascii_digit) {
+		return None;
+	}
+	Some(s.iter().fold(0, |n, &d| n * 10 + (d - b'0') as u64))
+}
+
+fn ascii(s: &[u8]) -> Option<&str> {
+	str::from_utf8(s).ok()
+}
+
+fn is_leap_year(year: u64) -> bool {
+	year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+fn days_in_month(year: u64, month: u64) -> u64 {
+	match month {
+		2 if is_leap_year(year) => 29,
+		2 => 28,
+		4 | 6 | 9 | 11 => 30,
+		_ => 31,
+	}
+}
+
+/// Returns the number of days since 1970-01-01 of a date in the proleptic Gregorian calendar.
+///
+/// The algorithm is described in http://howardhinnant.github.io/date_algorithms.html and is
+/// simplified here to years from 1970 on.
+fn days_from_civil(year: u64, month: u64, day: u64) -> u64 {
+	let year = if month <= 2 { year - 1 } else { year };
+	let era = year / 400;
+	let year_of_era = year - era * 400;
+	let month_index = if month > 2 { month - 3 } else { month + 9 };
+	let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+	let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+	era * 146_097 + day_of_era - 719_468
+}
+
+/// The inverse of `days_from_civil`.
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+	let days = days + 719_468;
+	let era = days / 146_097;
+	let day_of_era = days - era * 146_097;
+	let year_of_era =
+		(day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+	let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+	let month_index = (5 * day_of_year + 2) / 153;
+	let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+	let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+	let year = year_of_era + era * 400;
+	(if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+#[cfg(test)]
+mod test {
+
+	#[test]
+	fn httpdate_parse_test() {
+		use super::HttpDate;
+
+		let test_cases = vec![
+			("Sun, 06 Nov 1994 08:49:37 GMT", 784_111_777),
+			("Sunday, 06-Nov-94 08:49:37 GMT", 784_111_777),
+			("Sun Nov  6 08:49:37 1994", 784_111_777),
+			("Sun Nov 06 08:49:37 1994", 784_111_777),
+			("Thu, 01 Jan 1970 00:00:00 GMT", 0),
+			("Tue, 29 Feb 2000 23:59:59 GMT", 951_868_799),
+			("Wed, 31 Dec 2014 23:59:60 GMT", 1_420_070_400),
+			("Fri, 31 Dec 9999 23:59:59 GMT", 253_402_300_799),
+		];
+
+		for (s, secs) in test_cases {
+			assert_eq!(Ok(secs), s.parse::<HttpDate>().map(HttpDate::as_secs), "{}", s);
+		}
+	}
+
+	#[test]
+	fn httpdate_parse_error_test() {
+		use super::{HttpDate, InvalidHttpDate};
+
+		let test_cases = vec![
+			"",
+			"Sun, 06 Nov 1994 08:49:37",
+			"Sun, 06 Nov 1994 08:49:37 UTC",
+			"sun, 06 Nov 1994 08:49:37 GMT",
+			"Sun, 06 nov 1994 08:49:37 GMT",
+			"Sun,  6 Nov 1994 08:49:37 GMT",
+			"Sun, 06 Nov 1994 24:00:00 GMT",
+			"Sun, 06 Nov 1994 08:60:00 GMT",
+			"Sun, 31 Nov 1994 08:49:37 GMT",
+			"Mon, 29 Feb 1900 08:49:37 GMT",
+			"Mon, 00 Nov 1994 08:49:37 GMT",
+			"Wed, 31 Dec 1969 23:59:59 GMT",
+			"Sun, 06 Nov 1994 08:49:37 GMT ",
+			"Sun, 06-Nov-94 08:49:37 GMT",
+			"Sunday, 06 Nov 1994 08:49:37 GMT",
+			"Sun Nov 6 08:49:37 1994",
+			"Sun, 06 Nov 1994 8:49:37 GMT",
+			"Fri, 31 Dec 9999 23:59:60 GMT",
+		];
+
+		for s in test_cases {
+			assert_eq!(Err(InvalidHttpDate), s.parse::<HttpDate>(), "{:?}", s);
+		}
+	}
+
+	#[test]
+	fn httpdate_display_test() {
+		use std::time::{Duration, UNIX_EPOCH};
+		use super::HttpDate;
+
+		let test_cases = vec![
+			(784_111_777, "Sun, 06 Nov 1994 08:49:37 GMT"),
+			(0, "Thu, 01 Jan 1970 00:00:00 GMT"),
+			(951_868_800, "Wed, 01 Mar 2000 00:00:00 GMT"),
+			(1_445_412_480, "Wed, 21 Oct 2015 07:28:00 GMT"),
+		];
+
+		for (secs, expect) in test_cases {
+			let date = HttpDate::from(UNIX_EPOCH + Duration::from_secs(secs));
+			assert_eq!(expect, date.to_string());
+			assert_eq!(Ok(date), expect.parse());
+		}
+	}
+
+	#[test]
+	fn httpdate_civil_round_trip_test() {
+		use super::{civil_from_days, days_from_civil};
+
+		for days in 0..(400 * 366) {
+			let (year, month, day) = civil_from_days(days);
+			assert_eq!(days, days_from_civil(year, month, day));
+		}
+	}
+}
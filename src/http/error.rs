@@ -0,0 +1,289 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use framing::FramingError;
+use parser::ParseError;
+use statuscodes::{InvalidStatusCode, StatusCode, StatusCodeClass};
+use statusline::StatusLineError;
+use typed_headers::InvalidHeader;
+use uri::UriError;
+
+/// An HttpError is an error that should be reported to the peer as a HTTP response.
+///
+/// It carries the status code of the response, an optional message describing the problem in
+/// more detail, and optionally the underlying error that caused it. Request handlers can return
+/// an HttpError, and propagate it with `?`, to have the server answer with the appropriate status.
+///
+/// Errors that convert into an HttpError through `From` map to the status a server or gateway
+/// would be expected to send: an `io::Error` is a 500 (Internal Server Error), while a malformed
+/// status code or status-line received from an upstream server is a 502 (Bad Gateway).
+#[derive(Debug)]
+pub struct HttpError {
+	status: StatusCode,
+	detail: Option<String>,
+	source: Option<Box<dyn Error + Send + Sync>>,
+}
+
+macro_rules! constructors {
+	($($(#[$attr:meta])* $name:ident => $status:ident,)*) => {
+		$(
+			$(#[$attr])*
+			pub fn $name() -> HttpError {
+				HttpError::new(StatusCode::$status)
+			}
+		)*
+	}
+}
+
+impl HttpError {
+	/// Creates an error that will be reported with the given status code.
+	///
+	/// The status code must be a 4xx (Client Error) or 5xx (Server Error) code, as any other code
+	/// would report the error as a success; the error is reported with 500 (Internal Server Error)
+	/// instead.
+	pub fn new(status: StatusCode) -> HttpError {
+		let status = match status.class() {
+			Some(StatusCodeClass::ClientError) | Some(StatusCodeClass::ServerError) => status,
+			_ => StatusCode::InternalServerError,
+		};
+		HttpError {
+			status,
+			detail: None,
+			source: None,
+		}
+	}
+
+	/// Attaches a message describing the error in more detail.
+	pub fn with_detail<S: Into<String>>(mut self, detail: S) -> HttpError {
+		self.detail = Some(detail.into());
+		self
+	}
+
+	/// Attaches the error that caused this error.
+	pub fn with_source<E: Into<Box<dyn Error + Send + Sync>>>(mut self, source: E) -> HttpError {
+		self.source = Some(source.into());
+		self
+	}
+
+	/// Returns the status code the error should be reported with.
+	pub fn status(&self) -> StatusCode {
+		self.status
+	}
+
+	/// Returns the message describing the error, if one was attached.
+	pub fn detail(&self) -> Option<&str> {
+		self.detail.as_deref()
+	}
+
+	constructors! {
+		/// Creates a 400 (Bad Request) error.
+		bad_request => BadRequest,
+		/// Creates a 401 (Unauthorized) error.
+		unauthorized => Unauthorized,
+		/// Creates a 403 (Forbidden) error.
+		forbidden => Forbidden,
+		/// Creates a 404 (Not Found) error.
+		not_found => NotFound,
+		/// Creates a 405 (Method Not Allowed) error.
+		method_not_allowed => MethodNotAllowed,
+		/// Creates a 406 (Not Acceptable) error.
+		not_acceptable => NotAcceptable,
+		/// Creates a 408 (Request Timeout) error.
+		request_timeout => RequestTimeout,
+		/// Creates a 409 (Conflict) error.
+		conflict => Conflict,
+		/// Creates a 410 (Gone) error.
+		gone => Gone,
+		/// Creates a 411 (Length Required) error.
+		length_required => LengthRequired,
+		/// Creates a 412 (Precondition Failed) error.
+		precondition_failed => PreconditionFailed,
+		/// Creates a 413 (Payload Too Large) error.
+		payload_too_large => PayloadTooLarge,
+		/// Creates a 414 (URI Too Long) error.
+		uri_too_long => UriTooLong,
+		/// Creates a 415 (Unsupported Media Type) error.
+		unsupported_media_type => UnsupportedMediaType,
+		/// Creates a 417 (Expectation Failed) error.
+		expectation_failed => ExpectationFailed,
+		/// Creates a 422 (Unprocessable Entity) error.
+		unprocessable_entity => UnprocessableEntity,
+		/// Creates a 426 (Upgrade Required) error.
+		upgrade_required => UpgradeRequired,
+		/// Creates a 428 (Precondition Required) error.
+		precondition_required => PreconditionRequired,
+		/// Creates a 429 (Too Many Requests) error.
+		too_many_requests => TooManyRequests,
+		/// Creates a 431 (Request Header Fields Too Large) error.
+		request_header_fields_too_large => RequestHeaderFieldsTooLarge,
+		/// Creates a 500 (Internal Server Error) error.
+		internal_server_error => InternalServerError,
+		/// Creates a 501 (Not Implemented) error.
+		not_implemented => NotImplemented,
+		/// Creates a 502 (Bad Gateway) error.
+		bad_gateway => BadGateway,
+		/// Creates a 503 (Service Unavailable) error.
+		service_unavailable => ServiceUnavailable,
+		/// Creates a 504 (Gateway Timeout) error.
+		gateway_timeout => GatewayTimeout,
+		/// Creates a 505 (HTTP Version Not Supported) error.
+		http_version_not_supported => HttpVersionNotSupported,
+	}
+}
+
+impl fmt::Display for HttpError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self.detail {
+			Some(ref detail) => write!(f, "{}: {}", self.status, detail),
+			None => write!(f, "{}", self.status),
+		}
+	}
+}
+
+impl Error for HttpError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match self.source {
+			Some(ref source) => Some(&**source),
+			None => None,
+		}
+	}
+}
+
+impl From<StatusCode> for HttpError {
+	fn from(status: StatusCode) -> HttpError {
+		HttpError::new(status)
+	}
+}
+
+impl From<io::Error> for HttpError {
+	fn from(err: io::Error) -> HttpError {
+		HttpError::internal_server_error().with_source(err)
+	}
+}
+
+impl From<InvalidHeader> for HttpError {
+	fn from(err: InvalidHeader) -> HttpError {
+		HttpError::bad_request().with_source(err)
+	}
+}
+
+impl From<UriError> for HttpError {
+	fn from(err: UriError) -> HttpError {
+		HttpError::new(err.status()).with_source(err)
+	}
+}
+
+impl From<FramingError> for HttpError {
+	fn from(err: FramingError) -> HttpError {
+		HttpError::bad_request().with_source(err)
+	}
+}
+
+impl From<ParseError> for HttpError {
+	fn from(err: ParseError) -> HttpError {
+		HttpError::new(err.status()).with_source(err)
+	}
+}
+
+impl From<InvalidStatusCode> for HttpError {
+	fn from(err: InvalidStatusCode) -> HttpError {
+		HttpError::bad_gateway().with_source(err)
+	}
+}
+
+impl From<StatusLineError> for HttpError {
+	fn from(err: StatusLineError) -> HttpError {
+		HttpError::bad_gateway().with_source(err)
+	}
+}
+
+#[cfg(test)]
+mod test {
+
+	#[test]
+	fn httperror_constructors_test() {
+		use super::HttpError;
+		use statuscodes::StatusCode;
+
+		let test_cases = vec![
+			(HttpError::bad_request(), StatusCode::BadRequest),
+			(HttpError::not_found(), StatusCode::NotFound),
+			(HttpError::conflict(), StatusCode::Conflict),
+			(HttpError::too_many_requests(), StatusCode::TooManyRequests),
+			(HttpError::internal_server_error(), StatusCode::InternalServerError),
+			(HttpError::service_unavailable(), StatusCode::ServiceUnavailable),
+		];
+
+		for (err, expect) in test_cases {
+			assert_eq!(expect, err.status());
+			assert_eq!(None, err.detail());
+		}
+	}
+
+	#[test]
+	fn httperror_new_test() {
+		use super::HttpError;
+		use statuscodes::StatusCode;
+
+		let test_cases = vec![
+			(StatusCode::NotFound, StatusCode::NotFound),
+			(StatusCode::from_u16(499), StatusCode::from_u16(499)),
+			(StatusCode::from_u16(599), StatusCode::from_u16(599)),
+			(StatusCode::Continue, StatusCode::InternalServerError),
+			(StatusCode::Ok, StatusCode::InternalServerError),
+			(StatusCode::MovedPermanently, StatusCode::InternalServerError),
+			(StatusCode::from_u16(600), StatusCode::InternalServerError),
+		];
+
+		for (status, expect) in test_cases {
+			assert_eq!(expect, HttpError::new(status).status(), "{}", status);
+			assert_eq!(expect, HttpError::from(status).status(), "{}", status);
+		}
+	}
+
+	#[test]
+	fn httperror_display_test() {
+		use super::HttpError;
+
+		assert_eq!("404 Not Found", HttpError::not_found().to_string());
+		assert_eq!("409 Conflict: version 3 is stale",
+			HttpError::conflict().with_detail("version 3 is stale").to_string());
+	}
+
+	#[test]
+	fn httperror_source_test() {
+		use std::error::Error;
+		use std::io;
+		use super::HttpError;
+		use statuscodes::StatusCode;
+
+		let err = HttpError::from(io::Error::other("disk full"));
+		assert_eq!(StatusCode::InternalServerError, err.status());
+		assert_eq!("disk full", err.source().unwrap().to_string());
+
+		let err = HttpError::gateway_timeout().with_source("upstream took 30s");
+		assert_eq!("upstream took 30s", err.source().unwrap().to_string());
+
+		assert!(HttpError::not_found().source().is_none());
+	}
+
+	#[test]
+	fn httperror_question_mark_test() {
+		use super::HttpError;
+		use statuscodes::StatusCode;
+		use statusline::StatusLine;
+
+		fn handler(upstream: &str) -> Result<StatusCode, HttpError> {
+			let line: StatusLine = upstream.parse()?;
+			if line.status == StatusCode::NotFound {
+				return Err(HttpError::not_found().with_detail("no such widget"));
+			}
+			Ok(line.status)
+		}
+
+		assert_eq!(StatusCode::Ok, handler("HTTP/1.1 200 OK").unwrap());
+		assert_eq!(StatusCode::NotFound, handler("HTTP/1.1 404 Not Found").unwrap_err().status());
+		assert_eq!(StatusCode::BadGateway, handler("HTTP/1.1 042 Huh").unwrap_err().status());
+	}
+}
@@ -0,0 +1,272 @@
+//! The length of a received message body
+//! ([Section 3.3.3 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-3.3.3)).
+//!
+//! A message body is delimited by the first of these rules that applies:
+//!
+//! 1. A response to HEAD, a 1xx (Informational), 204 (No Content) or 304 (Not Modified)
+//!    response, and a 2xx (Successful) response to CONNECT have no body.
+//! 2. If Transfer-Encoding is present and chunked is the final coding, the body is chunked.
+//!    Transfer-Encoding overrides Content-Length.
+//! 3. If Transfer-Encoding is present without chunked as the final coding, a response body is
+//!    delimited by closing the connection, while a request is an error.
+//! 4. If Content-Length is present, it is the length of the body; it is an error if it is invalid
+//!    or has differing values.
+//! 5. Otherwise, a request has no body, and a response body is delimited by closing the
+//!    connection.
+//!
+//! Disagreement between recipients about where a message ends is what request smuggling and
+//! response splitting attacks exploit, so anything ambiguous is an error rather than a guess.
+
+use std::error::Error;
+use std::fmt;
+
+use header::{HeaderMap, HeaderName};
+use method::Method;
+use statuscodes::{StatusCode, StatusCodeClass};
+use typed_headers::{ContentLength, TransferEncoding};
+
+/// The Framing of a message body determines how the recipient finds where the body ends.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Framing {
+	/// The message has no body.
+	Empty,
+	/// The body is the given number of octets, as declared by Content-Length.
+	Length(u64),
+	/// The body is encoded with the chunked transfer coding.
+	Chunked,
+	/// The body ends when the server closes the connection, which only a response can use.
+	CloseDelimited,
+}
+
+/// Returns the framing of the body of a received request.
+///
+/// A request that has both Transfer-Encoding and Content-Length is rejected: it is how request
+/// smuggling hides one request inside another, and a server that receives one MUST close the
+/// connection after responding anyway.
+pub fn request_framing(headers: &HeaderMap) -> Result<Framing, FramingError> {
+	match transfer_encoding(headers)? {
+		Some(_) if headers.contains_key(HeaderName::CONTENT_LENGTH) => {
+			Err(FramingError::ContentLengthWithTransferEncoding)
+		}
+		Some(true) => Ok(Framing::Chunked),
+		// The length of the body cannot be determined reliably.
+		Some(false) => Err(FramingError::InvalidTransferEncoding),
+		None => match content_length(headers)? {
+			Some(len) => Ok(Framing::Length(len)),
+			None => Ok(Framing::Empty),
+		},
+	}
+}
+
+/// Returns the framing of the body of a received response to a request with the given method.
+pub fn response_framing(method: &Method, status: StatusCode, headers: &HeaderMap)
+	-> Result<Framing, FramingError>
+{
+	if *method == Method::Head || status.is_informational() || status == StatusCode::NoContent ||
+		status == StatusCode::NotModified {
+		return Ok(Framing::Empty);
+	}
+	if *method == Method::Connect && status.class() == Some(StatusCodeClass::Successful) {
+		// The connection becomes a tunnel immediately after the header section.
+		return Ok(Framing::Empty);
+	}
+
+	match transfer_encoding(headers)? {
+		Some(true) => Ok(Framing::Chunked),
+		Some(false) => Ok(Framing::CloseDelimited),
+		None => match content_length(headers)? {
+			Some(len) => Ok(Framing::Length(len)),
+			None => Ok(Framing::CloseDelimited),
+		},
+	}
+}
+
+/// Returns whether the final transfer coding is chunked, or `None` without Transfer-Encoding.
+fn transfer_encoding(headers: &HeaderMap) -> Result<Option<bool>, FramingError> {
+	let codings = match headers.get_typed::<TransferEncoding>() {
+		Ok(Some(codings)) => codings,
+		Ok(None) => return Ok(None),
+		Err(_) => return Err(FramingError::InvalidTransferEncoding),
+	};
+
+	// A sender MUST NOT apply chunked more than once to a message body.
+	let chunked = codings.codings().iter().filter(|coding| coding.eq_ignore_ascii_case("chunked")).count();
+	match chunked {
+		0 => Ok(Some(false)),
+		1 if codings.is_chunked() => Ok(Some(true)),
+		_ => Err(FramingError::InvalidTransferEncoding),
+	}
+}
+
+fn content_length(headers: &HeaderMap) -> Result<Option<u64>, FramingError> {
+	match headers.get_typed::<ContentLength>() {
+		Ok(length) => Ok(length.map(|ContentLength(len)| len)),
+		Err(_) => Err(FramingError::InvalidContentLength),
+	}
+}
+
+/// An error returned when the length of a received message body cannot be determined reliably.
+///
+/// The recipient MUST treat the message as unusable: a server responds with 400 (Bad Request),
+/// and either side closes the connection, since it cannot find the start of the next message.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FramingError {
+	/// Content-Length is not a decimal number, or has differing values.
+	InvalidContentLength,
+	/// Transfer-Encoding is malformed, applies chunked more than once or not as the final coding,
+	/// or, in a request, does not end with chunked.
+	InvalidTransferEncoding,
+	/// A request has both Transfer-Encoding and Content-Length.
+	ContentLengthWithTransferEncoding,
+}
+
+impl fmt::Display for FramingError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let description = match *self {
+			FramingError::InvalidContentLength => "invalid Content-Length",
+			FramingError::InvalidTransferEncoding => "invalid Transfer-Encoding",
+			FramingError::ContentLengthWithTransferEncoding => {
+				"both Content-Length and Transfer-Encoding in request"
+			}
+		};
+		f.write_str(description)
+	}
+}
+
+impl Error for FramingError {}
+
+#[cfg(test)]
+mod test {
+	use header::{HeaderMap, HeaderName, HeaderValue};
+
+	fn headers(fields: &[(&'static str, &'static str)]) -> HeaderMap {
+		fields.iter()
+			.map(|&(name, value)| (HeaderName::from_static(name), HeaderValue::from_static(value)))
+			.collect()
+	}
+
+	#[test]
+	fn request_framing_test() {
+		use super::{request_framing, Framing};
+
+		let test_cases = vec![
+			(vec![], Framing::Empty),
+			(vec![("Content-Length", "0")], Framing::Length(0)),
+			(vec![("Content-Length", "42")], Framing::Length(42)),
+			(vec![("Content-Length", "42"), ("content-length", "42")], Framing::Length(42)),
+			(vec![("Content-Length", "42, 42")], Framing::Length(42)),
+			(vec![("Content-Length", "007")], Framing::Length(7)),
+			(vec![("Transfer-Encoding", "chunked")], Framing::Chunked),
+			(vec![("Transfer-Encoding", "CHUNKED")], Framing::Chunked),
+			(vec![("Transfer-Encoding", "gzip, chunked")], Framing::Chunked),
+			(vec![("Transfer-Encoding", "gzip"), ("Transfer-Encoding", "chunked")], Framing::Chunked),
+		];
+
+		for (fields, expect) in test_cases {
+			assert_eq!(Ok(expect), request_framing(&headers(&fields)), "{:?}", fields);
+		}
+	}
+
+	#[test]
+	fn request_smuggling_test() {
+		use super::{request_framing, FramingError};
+
+		let test_cases = vec![
+			// CL.TE and TE.CL: the recipients disagree on which header delimits the body.
+			(vec![("Content-Length", "6"), ("Transfer-Encoding", "chunked")],
+				FramingError::ContentLengthWithTransferEncoding),
+			(vec![("Transfer-Encoding", "chunked"), ("Content-Length", "6")],
+				FramingError::ContentLengthWithTransferEncoding),
+			// Obfuscated Transfer-Encoding that some recipients do not recognize as chunked.
+			(vec![("Transfer-Encoding", "xchunked")], FramingError::InvalidTransferEncoding),
+			(vec![("Transfer-Encoding", "chunked, identity")], FramingError::InvalidTransferEncoding),
+			(vec![("Transfer-Encoding", "chunked"), ("Transfer-Encoding", "x")],
+				FramingError::InvalidTransferEncoding),
+			(vec![("Transfer-Encoding", "chunked, chunked")], FramingError::InvalidTransferEncoding),
+			(vec![("Transfer-Encoding", "\"chunked\"")], FramingError::InvalidTransferEncoding),
+			(vec![("Transfer-Encoding", "chunked;")], FramingError::InvalidTransferEncoding),
+			(vec![("Transfer-Encoding", "")], FramingError::InvalidTransferEncoding),
+			// Conflicting or malformed Content-Length.
+			(vec![("Content-Length", "6"), ("Content-Length", "5")], FramingError::InvalidContentLength),
+			(vec![("Content-Length", "6, 5")], FramingError::InvalidContentLength),
+			(vec![("Content-Length", "+6")], FramingError::InvalidContentLength),
+			(vec![("Content-Length", "-1")], FramingError::InvalidContentLength),
+			(vec![("Content-Length", "0x10")], FramingError::InvalidContentLength),
+			(vec![("Content-Length", "6 5")], FramingError::InvalidContentLength),
+			(vec![("Content-Length", "18446744073709551616")], FramingError::InvalidContentLength),
+			(vec![("Content-Length", "")], FramingError::InvalidContentLength),
+		];
+
+		for (fields, expect) in test_cases {
+			assert_eq!(Err(expect), request_framing(&headers(&fields)), "{:?}", fields);
+		}
+	}
+
+	#[test]
+	fn request_smuggling_head_test() {
+		use parser::{ParseError, Parser, Status};
+		use super::{request_framing, Framing};
+
+		// Tricks that hide a header field from some recipients are rejected when the head is
+		// parsed, before the framing is determined.
+		let test_cases = vec![
+			(&b"POST / HTTP/1.1\r\nTransfer-Encoding : chunked\r\n\r\n"[..], ParseError::InvalidToken),
+			(b"POST / HTTP/1.1\r\nX: y\r\n Transfer-Encoding: chunked\r\n\r\n", ParseError::ObsFold),
+			(b"POST / HTTP/1.1\r\nTransfer-Encoding:\r\n chunked\r\n\r\n", ParseError::ObsFold),
+			(b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\rContent-Length: 1\r\n\r\n",
+				ParseError::InvalidHeaderValue),
+			(b"POST / HTTP/1.1\r\nContent-Length: 1\nTransfer-Encoding: chunked\r\n\r\n",
+				ParseError::InvalidLineEnding),
+		];
+
+		for (buf, expect) in test_cases {
+			assert_eq!(Err(expect), Parser::new().parse_request(buf), "{:?}", buf);
+		}
+
+		let buf = b"POST / HTTP/1.1\r\nTransfer-Encoding:\tchunked \r\n\r\n0\r\n\r\n";
+		match Parser::new().parse_request(buf) {
+			Ok(Status::Complete(_, head)) => {
+				assert_eq!(Ok(Framing::Chunked), request_framing(&head.header_map()));
+			}
+			other => panic!("{:?}", other),
+		}
+	}
+
+	#[test]
+	fn response_framing_test() {
+		use method::Method;
+		use statuscodes::StatusCode;
+		use super::{response_framing, Framing, FramingError};
+
+		let chunked = vec![("Transfer-Encoding", "chunked")];
+		let test_cases = vec![
+			(Method::Get, StatusCode::Ok, vec![], Framing::CloseDelimited),
+			(Method::Get, StatusCode::Ok, vec![("Content-Length", "3")], Framing::Length(3)),
+			(Method::Get, StatusCode::Ok, chunked.clone(), Framing::Chunked),
+			(Method::Get, StatusCode::Ok, vec![("Transfer-Encoding", "gzip")], Framing::CloseDelimited),
+			// Transfer-Encoding overrides Content-Length.
+			(Method::Get, StatusCode::Ok, vec![("Content-Length", "3"), ("Transfer-Encoding", "chunked")],
+				Framing::Chunked),
+			(Method::Head, StatusCode::Ok, vec![("Content-Length", "3")], Framing::Empty),
+			(Method::Head, StatusCode::Ok, chunked.clone(), Framing::Empty),
+			(Method::Get, StatusCode::Continue, vec![], Framing::Empty),
+			(Method::Get, StatusCode::SwitchingProtocols, vec![], Framing::Empty),
+			(Method::Get, StatusCode::from_u16(199), vec![], Framing::Empty),
+			(Method::Delete, StatusCode::NoContent, vec![("Content-Length", "3")], Framing::Empty),
+			(Method::Get, StatusCode::NotModified, chunked.clone(), Framing::Empty),
+			(Method::Connect, StatusCode::Ok, vec![("Content-Length", "3")], Framing::Empty),
+			(Method::Connect, StatusCode::Forbidden, vec![("Content-Length", "3")], Framing::Length(3)),
+			(Method::Post, StatusCode::ResetContent, vec![("Content-Length", "0")], Framing::Length(0)),
+		];
+
+		for (method, status, fields, expect) in test_cases {
+			assert_eq!(Ok(expect), response_framing(&method, status, &headers(&fields)),
+				"{} {} {:?}", method, status, fields);
+		}
+
+		assert_eq!(Err(FramingError::InvalidContentLength),
+			response_framing(&Method::Get, StatusCode::Ok, &headers(&[("Content-Length", "1, 2")])));
+		assert_eq!(Err(FramingError::InvalidTransferEncoding),
+			response_framing(&Method::Get, StatusCode::Ok, &headers(&[("Transfer-Encoding", "chunked, gzip")])));
+	}
+}
@@ -0,0 +1,695 @@
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+use std::mem;
+use std::slice;
+use std::str::{self, FromStr};
+
+use token;
+use typed_headers::{Header, InvalidHeader};
+
+/// A HeaderName is the name of a header field.
+///
+/// Each header field consists of a case-insensitive field name followed by a colon (":"),
+/// optional leading whitespace, the field value, and optional trailing whitespace
+/// ([Section 3.2 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-3.2)).
+///
+/// ```c
+/// header-field = field-name ":" OWS field-value OWS
+/// field-name   = token
+/// ```
+///
+/// Names compare and hash case-insensitively. The names of the standard header fields are
+/// available as constants, e.g. `HeaderName::CONTENT_LENGTH`, and a name parsed with `from_bytes`
+/// that matches one of them is interned, so that it does not allocate and is spelled the
+/// canonical way.
+#[derive(Clone)]
+pub struct HeaderName {
+	name: Cow<'static, str>,
+}
+
+macro_rules! standard_headers {
+	($($(#[$attr:meta])* $constant:ident => $name:expr,)*) => {
+		impl HeaderName {
+			$(
+				$(#[$attr])*
+				pub const $constant: HeaderName = HeaderName { name: Cow::Borrowed($name) };
+			)*
+		}
+
+		const STANDARD_HEADERS: &[&str] = &[$($name,)*];
+	}
+}
+
+standard_headers! {
+	/// Accept (http://tools.ietf.org/html/rfc7231#section-5.3.2)
+	ACCEPT => "Accept",
+	/// Accept-Charset (http://tools.ietf.org/html/rfc7231#section-5.3.3)
+	ACCEPT_CHARSET => "Accept-Charset",
+	/// Accept-Encoding (http://tools.ietf.org/html/rfc7231#section-5.3.4)
+	ACCEPT_ENCODING => "Accept-Encoding",
+	/// Accept-Language (http://tools.ietf.org/html/rfc7231#section-5.3.5)
+	ACCEPT_LANGUAGE => "Accept-Language",
+	/// Accept-Ranges (http://tools.ietf.org/html/rfc7233#section-2.3)
+	ACCEPT_RANGES => "Accept-Ranges",
+	/// Age (http://tools.ietf.org/html/rfc7234#section-5.1)
+	AGE => "Age",
+	/// Allow (http://tools.ietf.org/html/rfc7231#section-7.4.1)
+	ALLOW => "Allow",
+	/// Authorization (http://tools.ietf.org/html/rfc7235#section-4.2)
+	AUTHORIZATION => "Authorization",
+	/// Cache-Control (http://tools.ietf.org/html/rfc7234#section-5.2)
+	CACHE_CONTROL => "Cache-Control",
+	/// Connection (http://tools.ietf.org/html/rfc7230#section-6.1)
+	CONNECTION => "Connection",
+	/// Content-Encoding (http://tools.ietf.org/html/rfc7231#section-3.1.2.2)
+	CONTENT_ENCODING => "Content-Encoding",
+	/// Content-Language (http://tools.ietf.org/html/rfc7231#section-3.1.3.2)
+	CONTENT_LANGUAGE => "Content-Language",
+	/// Content-Length (http://tools.ietf.org/html/rfc7230#section-3.3.2)
+	CONTENT_LENGTH => "Content-Length",
+	/// Content-Location (http://tools.ietf.org/html/rfc7231#section-3.1.4.2)
+	CONTENT_LOCATION => "Content-Location",
+	/// Content-Range (http://tools.ietf.org/html/rfc7233#section-4.2)
+	CONTENT_RANGE => "Content-Range",
+	/// Content-Type (http://tools.ietf.org/html/rfc7231#section-3.1.1.5)
+	CONTENT_TYPE => "Content-Type",
+	/// Cookie (http://tools.ietf.org/html/rfc6265#section-5.4)
+	COOKIE => "Cookie",
+	/// Date (http://tools.ietf.org/html/rfc7231#section-7.1.1.2)
+	DATE => "Date",
+	/// ETag (http://tools.ietf.org/html/rfc7232#section-2.3)
+	ETAG => "ETag",
+	/// Expect (http://tools.ietf.org/html/rfc7231#section-5.1.1)
+	EXPECT => "Expect",
+	/// Expires (http://tools.ietf.org/html/rfc7234#section-5.3)
+	EXPIRES => "Expires",
+	/// From (http://tools.ietf.org/html/rfc7231#section-5.5.1)
+	FROM => "From",
+	/// Host (http://tools.ietf.org/html/rfc7230#section-5.4)
+	HOST => "Host",
+	/// If-Match (http://tools.ietf.org/html/rfc7232#section-3.1)
+	IF_MATCH => "If-Match",
+	/// If-Modified-Since (http://tools.ietf.org/html/rfc7232#section-3.3)
+	IF_MODIFIED_SINCE => "If-Modified-Since",
+	/// If-None-Match (http://tools.ietf.org/html/rfc7232#section-3.2)
+	IF_NONE_MATCH => "If-None-Match",
+	/// If-Range (http://tools.ietf.org/html/rfc7233#section-3.2)
+	IF_RANGE => "If-Range",
+	/// If-Unmodified-Since (http://tools.ietf.org/html/rfc7232#section-3.4)
+	IF_UNMODIFIED_SINCE => "If-Unmodified-Since",
+	/// Last-Modified (http://tools.ietf.org/html/rfc7232#section-2.2)
+	LAST_MODIFIED => "Last-Modified",
+	/// Location (http://tools.ietf.org/html/rfc7231#section-7.1.2)
+	LOCATION => "Location",
+	/// Max-Forwards (http://tools.ietf.org/html/rfc7231#section-5.1.2)
+	MAX_FORWARDS => "Max-Forwards",
+	/// Pragma (http://tools.ietf.org/html/rfc7234#section-5.4)
+	PRAGMA => "Pragma",
+	/// Proxy-Authenticate (http://tools.ietf.org/html/rfc7235#section-4.3)
+	PROXY_AUTHENTICATE => "Proxy-Authenticate",
+	/// Proxy-Authorization (http://tools.ietf.org/html/rfc7235#section-4.4)
+	PROXY_AUTHORIZATION => "Proxy-Authorization",
+	/// Range (http://tools.ietf.org/html/rfc7233#section-3.1)
+	RANGE => "Range",
+	/// Referer (http://tools.ietf.org/html/rfc7231#section-5.5.2)
+	REFERER => "Referer",
+	/// Retry-After (http://tools.ietf.org/html/rfc7231#section-7.1.3)
+	RETRY_AFTER => "Retry-After",
+	/// Sec-WebSocket-Accept (http://tools.ietf.org/html/rfc6455#section-11.3.3)
+	SEC_WEBSOCKET_ACCEPT => "Sec-WebSocket-Accept",
+	/// Sec-WebSocket-Extensions (http://tools.ietf.org/html/rfc6455#section-11.3.2)
+	SEC_WEBSOCKET_EXTENSIONS => "Sec-WebSocket-Extensions",
+	/// Sec-WebSocket-Key (http://tools.ietf.org/html/rfc6455#section-11.3.1)
+	SEC_WEBSOCKET_KEY => "Sec-WebSocket-Key",
+	/// Sec-WebSocket-Protocol (http://tools.ietf.org/html/rfc6455#section-11.3.4)
+	SEC_WEBSOCKET_PROTOCOL => "Sec-WebSocket-Protocol",
+	/// Sec-WebSocket-Version (http://tools.ietf.org/html/rfc6455#section-11.3.5)
+	SEC_WEBSOCKET_VERSION => "Sec-WebSocket-Version",
+	/// Server (http://tools.ietf.org/html/rfc7231#section-7.4.2)
+	SERVER => "Server",
+	/// Set-Cookie (http://tools.ietf.org/html/rfc6265#section-4.1)
+	SET_COOKIE => "Set-Cookie",
+	/// TE (http://tools.ietf.org/html/rfc7230#section-4.3)
+	TE => "TE",
+	/// Trailer (http://tools.ietf.org/html/rfc7230#section-4.4)
+	TRAILER => "Trailer",
+	/// Transfer-Encoding (http://tools.ietf.org/html/rfc7230#section-3.3.1)
+	TRANSFER_ENCODING => "Transfer-Encoding",
+	/// Upgrade (http://tools.ietf.org/html/rfc7230#section-6.7)
+	UPGRADE => "Upgrade",
+	/// User-Agent (http://tools.ietf.org/html/rfc7231#section-5.5.3)
+	USER_AGENT => "User-Agent",
+	/// Vary (http://tools.ietf.org/html/rfc7231#section-7.1.4)
+	VARY => "Vary",
+	/// Via (http://tools.ietf.org/html/rfc7230#section-5.7.1)
+	VIA => "Via",
+	/// Warning (http://tools.ietf.org/html/rfc7234#section-5.5)
+	WARNING => "Warning",
+	/// WWW-Authenticate (http://tools.ietf.org/html/rfc7235#section-4.1)
+	WWW_AUTHENTICATE => "WWW-Authenticate",
+}
+
+impl HeaderName {
+	/// Parses a field-name from bytes.
+	///
+	/// Returns an error if the name is not a token.
+	pub fn from_bytes(s: &[u8]) -> Result<HeaderName, InvalidHeaderName> {
+		if !token::is_token(s) {
+			return Err(InvalidHeaderName);
+		}
+		// A token is always ASCII, and therefore valid UTF-8.
+		let s = str::from_utf8(s).unwrap();
+
+		let name = match STANDARD_HEADERS.iter().find(|name| name.eq_ignore_ascii_case(s)) {
+			Some(&name) => Cow::Borrowed(name),
+			None => Cow::Owned(s.to_string()),
+		};
+		Ok(HeaderName { name })
+	}
+
+	/// Creates a HeaderName from a string literal.
+	///
+	/// # Panics
+	///
+	/// Panics if the name is not a token.
+	pub fn from_static(name: &'static str) -> HeaderName {
+		match HeaderName::from_bytes(name.as_bytes()) {
+			Ok(HeaderName { name: Cow::Owned(_) }) => HeaderName { name: Cow::Borrowed(name) },
+			Ok(standard) => standard,
+			Err(_) => panic!("invalid header name {:?}", name),
+		}
+	}
+
+	/// Returns the name as it is sent on the wire.
+	pub fn as_str(&self) -> &str {
+		&self.name
+	}
+}
+
+impl PartialEq for HeaderName {
+	fn eq(&self, other: &HeaderName) -> bool {
+		self.name.eq_ignore_ascii_case(&other.name)
+	}
+}
+
+impl Eq for HeaderName {}
+
+impl PartialEq<str> for HeaderName {
+	fn eq(&self, other: &str) -> bool {
+		self.name.eq_ignore_ascii_case(other)
+	}
+}
+
+impl<'a> PartialEq<&'a str> for HeaderName {
+	fn eq(&self, other: &&'a str) -> bool {
+		self.name.eq_ignore_ascii_case(other)
+	}
+}
+
+impl Hash for HeaderName {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		for b in self.name.bytes() {
+			state.write_u8(b.to_ascii_lowercase());
+		}
+	}
+}
+
+impl AsRef<str> for HeaderName {
+	fn as_ref(&self) -> &str {
+		&self.name
+	}
+}
+
+impl FromStr for HeaderName {
+	type Err = InvalidHeaderName;
+
+	fn from_str(s: &str) -> Result<HeaderName, InvalidHeaderName> {
+		HeaderName::from_bytes(s.as_bytes())
+	}
+}
+
+impl fmt::Display for HeaderName {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(&self.name)
+	}
+}
+
+impl fmt::Debug for HeaderName {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		fmt::Debug::fmt(&*self.name, f)
+	}
+}
+
+/// An error returned when a field-name is not a token.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct InvalidHeaderName;
+
+impl fmt::Display for InvalidHeaderName {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("invalid header name: not a token")
+	}
+}
+
+impl Error for InvalidHeaderName {}
+
+/// A HeaderValue is the value of a single header field line.
+///
+/// ```c
+/// field-value   = *( field-content / obs-fold )
+/// field-content = field-vchar [ 1*( SP / HTAB ) field-vchar ]
+/// field-vchar   = VCHAR / obs-text
+/// ```
+///
+/// Historically, HTTP has allowed field content with text in the ISO-8859-1 charset, so a value
+/// is kept as bytes and may contain obs-text that is not valid UTF-8. A value never contains CR,
+/// LF or other control characters, so it cannot be used to inject additional header fields.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct HeaderValue {
+	bytes: Vec<u8>,
+}
+
+impl HeaderValue {
+	/// Creates a HeaderValue from bytes.
+	///
+	/// Returns an error if the value contains a control character other than horizontal tab.
+	pub fn from_bytes(bytes: &[u8]) -> Result<HeaderValue, InvalidHeaderValue> {
+		if !bytes.iter().all(|&b| token::is_field_value_byte(b)) {
+			return Err(InvalidHeaderValue);
+		}
+		Ok(HeaderValue { bytes: bytes.to_vec() })
+	}
+
+	/// Creates a HeaderValue from a string literal.
+	///
+	/// # Panics
+	///
+	/// Panics if the value contains a control character other than horizontal tab.
+	pub fn from_static(value: &'static str) -> HeaderValue {
+		HeaderValue::from_bytes(value.as_bytes())
+			.unwrap_or_else(|_| panic!("invalid header value {:?}", value))
+	}
+
+	/// Returns the value as bytes.
+	pub fn as_bytes(&self) -> &[u8] {
+		&self.bytes
+	}
+
+	/// Returns the value as a string, or `None` if it is not valid UTF-8.
+	pub fn to_str(&self) -> Option<&str> {
+		str::from_utf8(&self.bytes).ok()
+	}
+}
+
+impl FromStr for HeaderValue {
+	type Err = InvalidHeaderValue;
+
+	fn from_str(s: &str) -> Result<HeaderValue, InvalidHeaderValue> {
+		HeaderValue::from_bytes(s.as_bytes())
+	}
+}
+
+impl From<u64> for HeaderValue {
+	fn from(n: u64) -> HeaderValue {
+		HeaderValue { bytes: n.to_string().into_bytes() }
+	}
+}
+
+impl From<HeaderName> for HeaderValue {
+	/// Creates a value that lists a header field, as is done by Vary, Connection and Trailer.
+	fn from(name: HeaderName) -> HeaderValue {
+		HeaderValue { bytes: name.as_str().as_bytes().to_vec() }
+	}
+}
+
+impl PartialEq<str> for HeaderValue {
+	fn eq(&self, other: &str) -> bool {
+		self.bytes == other.as_bytes()
+	}
+}
+
+impl<'a> PartialEq<&'a str> for HeaderValue {
+	fn eq(&self, other: &&'a str) -> bool {
+		self.bytes == other.as_bytes()
+	}
+}
+
+impl PartialEq<HeaderValue> for str {
+	fn eq(&self, other: &HeaderValue) -> bool {
+		self.as_bytes() == &other.bytes[..]
+	}
+}
+
+impl PartialEq<HeaderValue> for &str {
+	fn eq(&self, other: &HeaderValue) -> bool {
+		self.as_bytes() == &other.bytes[..]
+	}
+}
+
+impl fmt::Debug for HeaderValue {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		fmt::Debug::fmt(&String::from_utf8_lossy(&self.bytes), f)
+	}
+}
+
+/// An error returned when a field-value contains a control character.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct InvalidHeaderValue;
+
+impl fmt::Display for InvalidHeaderValue {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("invalid header value: contains a control character")
+	}
+}
+
+impl Error for InvalidHeaderValue {}
+
+/// A HeaderMap holds the header fields of a message.
+///
+/// The map keeps every field line in the order it was added. A field name may appear more than
+/// once: `append` adds another line, while `insert` replaces all lines with that name.
+///
+/// A recipient MAY combine multiple header fields with the same field name into one
+/// "field-name: field-value" pair by appending each subsequent field value to the combined field
+/// value in order, separated by a comma, but only if the entire field value for that header field
+/// is defined as a comma-separated list. In practice, the Set-Cookie header field often appears
+/// multiple times in a response message and does not use the list syntax
+/// ([Section 3.2.2 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-3.2.2)). The map
+/// therefore never combines values; `iter` and `get_all` yield each line separately.
+///
+/// Lookups accept anything that can be viewed as a string, so both `HeaderName::HOST` and `"host"`
+/// can be used as a key.
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct HeaderMap {
+	fields: Vec<(HeaderName, HeaderValue)>,
+}
+
+impl HeaderMap {
+	/// Creates an empty map.
+	pub fn new() -> HeaderMap {
+		HeaderMap { fields: Vec::new() }
+	}
+
+	/// Returns the number of field lines in the map.
+	pub fn len(&self) -> usize {
+		self.fields.len()
+	}
+
+	/// Returns true if the map holds no field lines.
+	pub fn is_empty(&self) -> bool {
+		self.fields.is_empty()
+	}
+
+	/// Returns true if the map holds at least one field line with the given name.
+	pub fn contains_key<N: AsRef<str>>(&self, name: N) -> bool {
+		self.get(name).is_some()
+	}
+
+	/// Returns the value of the first field line with the given name.
+	pub fn get<N: AsRef<str>>(&self, name: N) -> Option<&HeaderValue> {
+		self.get_all(name).next()
+	}
+
+	/// Returns the values of all field lines with the given name, in order.
+	pub fn get_all<N: AsRef<str>>(&self, name: N) -> GetAll<'_, N> {
+		GetAll {
+			name,
+			fields: self.fields.iter(),
+		}
+	}
+
+	/// Sets the value of a field, replacing all field lines with the same name.
+	///
+	/// The field keeps the position of its first line. Returns the previous value of the first
+	/// line, if there was one.
+	pub fn insert(&mut self, name: HeaderName, value: HeaderValue) -> Option<HeaderValue> {
+		match self.fields.iter().position(|(n, _)| *n == name) {
+			Some(i) => {
+				let (_, previous) = mem::replace(&mut self.fields[i], (name, value));
+				let rest = self.fields.split_off(i + 1);
+				let name = &self.fields[i].0;
+				let rest: Vec<_> = rest.into_iter().filter(|(n, _)| n != name).collect();
+				self.fields.extend(rest);
+				Some(previous)
+			}
+			None => {
+				self.fields.push((name, value));
+				None
+			}
+		}
+	}
+
+	/// Adds a field line, keeping any existing lines with the same name.
+	pub fn append(&mut self, name: HeaderName, value: HeaderValue) {
+		self.fields.push((name, value));
+	}
+
+	/// Removes all field lines with the given name, returning the value of the first one.
+	pub fn remove<N: AsRef<str>>(&mut self, name: N) -> Option<HeaderValue> {
+		let name = name.as_ref();
+		let i = self.fields.iter().position(|(n, _)| *n == name)?;
+		let (_, first) = self.fields.remove(i);
+		self.fields.retain(|(n, _)| *n != name);
+		Some(first)
+	}
+
+	/// Returns the typed value of a header field, or `None` if the map holds no such field.
+	///
+	/// Returns an error if the field lines do not match the header's grammar.
+	pub fn get_typed<H: Header>(&self) -> Result<Option<H>, InvalidHeader> {
+		let values: Vec<&HeaderValue> = self.get_all(H::NAME).collect();
+		if values.is_empty() {
+			return Ok(None);
+		}
+		H::decode(&values).map(Some).ok_or_else(|| InvalidHeader::new(H::NAME))
+	}
+
+	/// Sets a header field from its typed value, replacing all field lines with the same name.
+	pub fn set_typed<H: Header>(&mut self, header: H) {
+		self.insert(H::NAME, header.encode());
+	}
+
+	/// Returns an iterator over the field lines, in order.
+	pub fn iter(&self) -> Iter<'_> {
+		Iter { fields: self.fields.iter() }
+	}
+}
+
+impl<'a> IntoIterator for &'a HeaderMap {
+	type Item = (&'a HeaderName, &'a HeaderValue);
+	type IntoIter = Iter<'a>;
+
+	fn into_iter(self) -> Iter<'a> {
+		self.iter()
+	}
+}
+
+impl Extend<(HeaderName, HeaderValue)> for HeaderMap {
+	/// Appends the field lines.
+	fn extend<I: IntoIterator<Item = (HeaderName, HeaderValue)>>(&mut self, iter: I) {
+		self.fields.extend(iter);
+	}
+}
+
+impl FromIterator<(HeaderName, HeaderValue)> for HeaderMap {
+	fn from_iter<I: IntoIterator<Item = (HeaderName, HeaderValue)>>(iter: I) -> HeaderMap {
+		HeaderMap { fields: iter.into_iter().collect() }
+	}
+}
+
+/// An iterator over the field lines of a HeaderMap.
+pub struct Iter<'a> {
+	fields: slice::Iter<'a, (HeaderName, HeaderValue)>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+	type Item = (&'a HeaderName, &'a HeaderValue);
+
+	fn next(&mut self) -> Option<(&'a HeaderName, &'a HeaderValue)> {
+		self.fields.next().map(|(name, value)| (name, value))
+	}
+}
+
+/// An iterator over the values of the field lines of a HeaderMap with a given name.
+pub struct GetAll<'a, N> {
+	name: N,
+	fields: slice::Iter<'a, (HeaderName, HeaderValue)>,
+}
+
+impl<'a, N: AsRef<str>> Iterator for GetAll<'a, N> {
+	type Item = &'a HeaderValue;
+
+	fn next(&mut self) -> Option<&'a HeaderValue> {
+		let name = self.name.as_ref();
+		self.fields.find(|(n, _)| *n == name).map(|(_, value)| value)
+	}
+}
+
+#[cfg(test)]
+mod test {
+
+	#[test]
+	fn headername_from_bytes_test() {
+		use super::HeaderName;
+
+		let test_cases = vec![
+			("Content-Length", "Content-Length"),
+			("content-length", "Content-Length"),
+			("CONTENT-LENGTH", "Content-Length"),
+			("etag", "ETag"),
+			("X-Request-Id", "X-Request-Id"),
+			("x-request-id", "x-request-id"),
+		];
+
+		for (s, expect) in test_cases {
+			assert_eq!(expect, s.parse::<HeaderName>().unwrap().as_str());
+		}
+	}
+
+	#[test]
+	fn headername_from_bytes_error_test() {
+		use super::{HeaderName, InvalidHeaderName};
+
+		for s in ["", "Content Length", "Host:", " Host", "Host\r\n", "Caf\u{e9}", "(x)"] {
+			assert_eq!(Err(InvalidHeaderName), HeaderName::from_bytes(s.as_bytes()), "{:?}", s);
+		}
+	}
+
+	#[test]
+	fn headername_eq_test() {
+		use std::collections::hash_map::DefaultHasher;
+		use std::hash::{Hash, Hasher};
+		use super::HeaderName;
+
+		fn hash(name: &HeaderName) -> u64 {
+			let mut hasher = DefaultHasher::new();
+			name.hash(&mut hasher);
+			hasher.finish()
+		}
+
+		let test_cases = vec![
+			(HeaderName::CONTENT_TYPE, HeaderName::from_static("content-type")),
+			(HeaderName::from_static("X-Custom"), HeaderName::from_static("x-CUSTOM")),
+		];
+
+		for (a, b) in test_cases {
+			assert_eq!(a, b);
+			assert_eq!(hash(&a), hash(&b));
+		}
+
+		assert!(HeaderName::HOST != HeaderName::CONTENT_TYPE);
+		assert!(HeaderName::HOST == "HOST");
+	}
+
+	#[test]
+	fn headervalue_from_bytes_test() {
+		use super::{HeaderValue, InvalidHeaderValue};
+
+		for s in ["", "text/html; charset=utf-8", "a\tb", "\"quoted, with comma\"", "caf\u{e9}"] {
+			assert_eq!(s, HeaderValue::from_bytes(s.as_bytes()).unwrap());
+		}
+		assert!(HeaderValue::from_bytes(b"\xe9t\xe9").unwrap().to_str().is_none());
+
+		for s in ["a\r\nSet-Cookie: x", "a\nb", "a\rb", "a\0b", "\x7f"] {
+			assert_eq!(Err(InvalidHeaderValue), HeaderValue::from_bytes(s.as_bytes()), "{:?}", s);
+		}
+	}
+
+	#[test]
+	fn headermap_insert_test() {
+		use super::{HeaderMap, HeaderName, HeaderValue};
+
+		let mut headers = HeaderMap::new();
+		assert_eq!(None, headers.insert(HeaderName::HOST, HeaderValue::from_static("a")));
+		headers.append(HeaderName::VARY, HeaderValue::from_static("Accept"));
+		headers.append(HeaderName::from_static("vary"), HeaderValue::from_static("Cookie"));
+		headers.append(HeaderName::DATE, HeaderValue::from_static("today"));
+
+		let previous = headers.insert(HeaderName::from_static("VARY"), HeaderValue::from_static("*"));
+		assert_eq!(Some(HeaderValue::from_static("Accept")), previous);
+
+		let fields: Vec<(&str, &[u8])> = headers.iter()
+			.map(|(name, value)| (name.as_str(), value.as_bytes()))
+			.collect();
+		let expect: Vec<(&str, &[u8])> = vec![("Host", b"a"), ("Vary", b"*"), ("Date", b"today")];
+		assert_eq!(expect, fields);
+	}
+
+	#[test]
+	fn headermap_get_test() {
+		use super::{HeaderMap, HeaderName, HeaderValue};
+
+		let mut headers = HeaderMap::new();
+		headers.append(HeaderName::ALLOW, HeaderValue::from_static("GET"));
+		headers.append(HeaderName::CONTENT_LENGTH, HeaderValue::from(42));
+		headers.append(HeaderName::ALLOW, HeaderValue::from_static("HEAD, POST"));
+
+		assert_eq!(Some(&HeaderValue::from_static("GET")), headers.get(HeaderName::ALLOW));
+		assert_eq!(Some(&HeaderValue::from(42)), headers.get("content-length"));
+		assert_eq!(None, headers.get("Location"));
+		assert!(headers.contains_key("allow"));
+		assert!(!headers.contains_key(HeaderName::LOCATION));
+
+		let allow: Vec<&HeaderValue> = headers.get_all("Allow").collect();
+		assert_eq!(vec!["GET", "HEAD, POST"], allow);
+		assert_eq!(0, headers.get_all("Upgrade").count());
+	}
+
+	#[test]
+	fn headermap_remove_test() {
+		use super::{HeaderMap, HeaderName, HeaderValue};
+
+		let mut headers: HeaderMap = vec![
+			(HeaderName::VIA, HeaderValue::from_static("1.0 fred")),
+			(HeaderName::HOST, HeaderValue::from_static("example.com")),
+			(HeaderName::VIA, HeaderValue::from_static("1.1 p.example.net")),
+		].into_iter().collect();
+
+		assert_eq!(Some(HeaderValue::from_static("1.0 fred")), headers.remove("via"));
+		assert_eq!(None, headers.remove(HeaderName::VIA));
+		assert_eq!(1, headers.len());
+		assert_eq!(Some(&HeaderValue::from_static("example.com")), headers.get(HeaderName::HOST));
+	}
+
+	#[test]
+	fn headermap_typed_test() {
+		use super::{HeaderMap, HeaderName, HeaderValue};
+		use typed_headers::{Allow, ContentLength, InvalidHeader, RetryAfter};
+		use method::Method;
+
+		let mut headers = HeaderMap::new();
+		assert_eq!(Ok(None), headers.get_typed::<ContentLength>());
+
+		headers.set_typed(Allow(vec![Method::Get, Method::Head]));
+		headers.set_typed(RetryAfter::Delay(120));
+		assert_eq!(Some(&HeaderValue::from_static("GET, HEAD")), headers.get(HeaderName::ALLOW));
+		assert_eq!(Ok(Some(RetryAfter::Delay(120))), headers.get_typed());
+
+		headers.append(HeaderName::CONTENT_LENGTH, HeaderValue::from(42));
+		headers.append(HeaderName::CONTENT_LENGTH, HeaderValue::from(43));
+		let err = InvalidHeader::new(HeaderName::CONTENT_LENGTH);
+		assert_eq!(Err(err), headers.get_typed::<ContentLength>());
+
+		headers.set_typed(ContentLength(7));
+		assert_eq!(1, headers.get_all(HeaderName::CONTENT_LENGTH).count());
+		assert_eq!(Ok(Some(ContentLength(7))), headers.get_typed());
+	}
+
+	#[test]
+	fn headermap_set_cookie_test() {
+		use super::{HeaderMap, HeaderName, HeaderValue};
+
+		let cookies = ["SID=31d4d96e407aad42; Expires=Wed, 09 Jun 2021 10:18:14 GMT", "lang=en-US"];
+		let mut headers = HeaderMap::new();
+		for &cookie in &cookies {
+			headers.append(HeaderName::SET_COOKIE, HeaderValue::from_static(cookie));
+		}
+
+		let lines: Vec<&HeaderValue> = headers.iter().map(|(_, value)| value).collect();
+		assert_eq!(cookies.to_vec(), lines);
+		let lines: Vec<&HeaderValue> = headers.get_all(HeaderName::SET_COOKIE).collect();
+		assert_eq!(cookies.to_vec(), lines);
+	}
+}
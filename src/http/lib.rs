@@ -1,5 +1,54 @@
 //! An implemention of the HTTP protocol for both clients and servers.
+//!
+//! # Features
+//!
+//! * `serde` - implements `Serialize` and `Deserialize` for `StatusCode` and `StatusCodeClass`.
+//!   At most 256 distinct extension reason phrases of up to 128 bytes each can be deserialized;
+//!   an extension code with any other phrase fails to deserialize.
 
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
+#[macro_use]
+extern crate serde_derive;
+
+mod chunked;
+mod client;
+mod date;
+mod error;
+mod framing;
+mod header;
+mod message;
+mod method;
+mod parser;
+mod serializer;
+mod server;
 mod statuscodes;
+mod statusline;
+mod token;
+mod typed_headers;
+mod upgrade;
+mod uri;
+mod version;
+mod websocket;
+#[cfg(feature = "serde")]
+mod serde_impls;
 
+pub use chunked::*;
+pub use client::*;
+pub use date::*;
+pub use error::*;
+pub use framing::*;
+pub use header::*;
+pub use message::*;
+pub use method::*;
+pub use parser::*;
+pub use serializer::*;
+pub use server::*;
 pub use statuscodes::*;
+pub use statusline::*;
+pub use typed_headers::*;
+pub use upgrade::*;
+pub use uri::*;
+pub use version::*;
+pub use websocket::*;
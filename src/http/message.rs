@@ -0,0 +1,303 @@
+use error::HttpError;
+use header::{HeaderMap, HeaderName, HeaderValue};
+use method::Method;
+use statuscodes::StatusCode;
+use typed_headers::{ContentLength, ContentType, Header};
+use uri::RequestTarget;
+use version::HttpVersion;
+
+/// A Request is a HTTP request message.
+///
+/// A client sends a request to a server in the form of a request method, request target and
+/// protocol version, followed by header fields and a possibly empty payload body
+/// ([Section 3 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-3)).
+///
+/// The body is usually the payload as bytes, but can be of any type, e.g. a reader that streams
+/// the payload from the connection.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Request<B = Vec<u8>> {
+	/// The request method.
+	pub method: Method,
+	/// The request-target.
+	pub target: RequestTarget,
+	/// The protocol version.
+	pub version: HttpVersion,
+	/// The header fields.
+	pub headers: HeaderMap,
+	/// The payload body.
+	pub body: B,
+}
+
+impl Request<()> {
+	/// Creates a builder for a GET request for "/" using HTTP/1.1.
+	pub fn builder() -> RequestBuilder {
+		RequestBuilder {
+			method: Method::Get,
+			target: RequestTarget::default(),
+			version: HttpVersion::Http11,
+			headers: HeaderMap::new(),
+		}
+	}
+}
+
+impl<B> Request<B> {
+	/// Replaces the body with the result of `f`, keeping the rest of the message.
+	pub fn map_body<C, F: FnOnce(B) -> C>(self, f: F) -> Request<C> {
+		Request {
+			method: self.method,
+			target: self.target,
+			version: self.version,
+			headers: self.headers,
+			body: f(self.body),
+		}
+	}
+}
+
+/// A RequestBuilder constructs a Request.
+///
+/// ```
+/// use http::{HeaderName, HeaderValue, Method, Request};
+///
+/// let request = Request::builder()
+///     .method(Method::Post)
+///     .target(http::RequestTarget::parse(&Method::Post, b"/widgets").unwrap())
+///     .header(HeaderName::HOST, HeaderValue::from_static("example.com"))
+///     .body(b"name=sprocket".to_vec());
+/// assert_eq!("/widgets", request.target.path());
+/// ```
+#[derive(Debug)]
+pub struct RequestBuilder {
+	method: Method,
+	target: RequestTarget,
+	version: HttpVersion,
+	headers: HeaderMap,
+}
+
+impl RequestBuilder {
+	/// Sets the request method.
+	pub fn method(mut self, method: Method) -> RequestBuilder {
+		self.method = method;
+		self
+	}
+
+	/// Sets the request-target.
+	pub fn target(mut self, target: RequestTarget) -> RequestBuilder {
+		self.target = target;
+		self
+	}
+
+	/// Sets the protocol version.
+	pub fn version(mut self, version: HttpVersion) -> RequestBuilder {
+		self.version = version;
+		self
+	}
+
+	/// Adds a header field line, keeping any existing lines with the same name.
+	pub fn header(mut self, name: HeaderName, value: HeaderValue) -> RequestBuilder {
+		self.headers.append(name, value);
+		self
+	}
+
+	/// Sets a header field from its typed value, replacing all lines with the same name.
+	pub fn typed_header<H: Header>(mut self, header: H) -> RequestBuilder {
+		self.headers.set_typed(header);
+		self
+	}
+
+	/// Completes the request with a body.
+	pub fn body<B>(self, body: B) -> Request<B> {
+		Request {
+			method: self.method,
+			target: self.target,
+			version: self.version,
+			headers: self.headers,
+			body,
+		}
+	}
+}
+
+/// A Response is a HTTP response message.
+///
+/// A server responds to a client's request by sending one or more HTTP response messages, each
+/// beginning with a status line that includes the protocol version, a success or error code, and
+/// textual reason phrase, followed by header fields and a possibly empty payload body
+/// ([Section 2.1 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-2.1)).
+#[derive(Clone, PartialEq, Debug)]
+pub struct Response<B = Vec<u8>> {
+	/// The protocol version.
+	pub version: HttpVersion,
+	/// The status code; the reason phrase is that of the status code.
+	pub status: StatusCode,
+	/// The header fields.
+	pub headers: HeaderMap,
+	/// The payload body.
+	pub body: B,
+}
+
+impl Response<()> {
+	/// Creates a builder for a 200 (OK) response using HTTP/1.1.
+	pub fn builder() -> ResponseBuilder {
+		ResponseBuilder {
+			version: HttpVersion::Http11,
+			status: StatusCode::Ok,
+			headers: HeaderMap::new(),
+		}
+	}
+}
+
+impl<B> Response<B> {
+	/// Replaces the body with the result of `f`, keeping the rest of the message.
+	pub fn map_body<C, F: FnOnce(B) -> C>(self, f: F) -> Response<C> {
+		Response {
+			version: self.version,
+			status: self.status,
+			headers: self.headers,
+			body: f(self.body),
+		}
+	}
+}
+
+impl From<HttpError> for Response {
+	/// Creates a response that reports the error to the peer, with the error's message as a plain
+	/// text body.
+	fn from(err: HttpError) -> Response {
+		let body = format!("{}\n", err).into_bytes();
+		Response::builder()
+			.status(err.status())
+			.typed_header("text/plain; charset=utf-8".parse::<ContentType>().unwrap())
+			.typed_header(ContentLength(body.len() as u64))
+			.body(body)
+	}
+}
+
+/// A ResponseBuilder constructs a Response.
+///
+/// ```
+/// use http::{Location, Response, StatusCode};
+///
+/// let response = Response::builder()
+///     .status(StatusCode::Created)
+///     .typed_header(Location("/widgets/7".parse().unwrap()))
+///     .body(());
+/// assert_eq!(StatusCode::Created, response.status);
+/// ```
+#[derive(Debug)]
+pub struct ResponseBuilder {
+	version: HttpVersion,
+	status: StatusCode,
+	headers: HeaderMap,
+}
+
+impl ResponseBuilder {
+	/// Sets the protocol version.
+	pub fn version(mut self, version: HttpVersion) -> ResponseBuilder {
+		self.version = version;
+		self
+	}
+
+	/// Sets the status code.
+	pub fn status(mut self, status: StatusCode) -> ResponseBuilder {
+		self.status = status;
+		self
+	}
+
+	/// Adds a header field line, keeping any existing lines with the same name.
+	pub fn header(mut self, name: HeaderName, value: HeaderValue) -> ResponseBuilder {
+		self.headers.append(name, value);
+		self
+	}
+
+	/// Sets a header field from its typed value, replacing all lines with the same name.
+	pub fn typed_header<H: Header>(mut self, header: H) -> ResponseBuilder {
+		self.headers.set_typed(header);
+		self
+	}
+
+	/// Completes the response with a body.
+	pub fn body<B>(self, body: B) -> Response<B> {
+		Response {
+			version: self.version,
+			status: self.status,
+			headers: self.headers,
+			body,
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+
+	#[test]
+	fn request_builder_test() {
+		use header::{HeaderName, HeaderValue};
+		use method::Method;
+		use super::Request;
+		use typed_headers::Host;
+		use uri::{RequestTarget, Uri};
+		use version::HttpVersion;
+
+		let request = Request::builder().body(());
+		assert_eq!(Method::Get, request.method);
+		assert_eq!("/", request.target.to_string());
+		assert_eq!(HttpVersion::Http11, request.version);
+		assert!(request.headers.is_empty());
+
+		let uri: Uri = "http://example.com/a?b".parse().unwrap();
+		let request = Request::builder()
+			.method(Method::Put)
+			.target(RequestTarget::Absolute(uri.clone()))
+			.version(HttpVersion::Http10)
+			.typed_header(Host::new("example.com", None).unwrap())
+			.header(HeaderName::ACCEPT, HeaderValue::from_static("text/html"))
+			.header(HeaderName::ACCEPT, HeaderValue::from_static("*/*"))
+			.body(b"data".to_vec());
+		assert_eq!(Method::Put, request.method);
+		assert_eq!(RequestTarget::Absolute(uri), request.target);
+		assert_eq!(HttpVersion::Http10, request.version);
+		assert_eq!(Some(&HeaderValue::from_static("example.com")), request.headers.get("Host"));
+		assert_eq!(2, request.headers.get_all(HeaderName::ACCEPT).count());
+		assert_eq!(b"data".to_vec(), request.body);
+
+		let request = request.map_body(|body| body.len());
+		assert_eq!(4, request.body);
+	}
+
+	#[test]
+	fn response_builder_test() {
+		use header::HeaderName;
+		use method::Method;
+		use statuscodes::StatusCode;
+		use super::Response;
+		use typed_headers::{Allow, RetryAfter};
+		use version::HttpVersion;
+
+		let response = Response::builder().body(());
+		assert_eq!(StatusCode::Ok, response.status);
+		assert_eq!(HttpVersion::Http11, response.version);
+
+		let response = Response::builder()
+			.status(StatusCode::MethodNotAllowed)
+			.typed_header(Allow(vec![Method::Get, Method::Head]))
+			.typed_header(RetryAfter::Delay(5))
+			.body("nope".to_string());
+		assert_eq!(StatusCode::MethodNotAllowed, response.status);
+		assert_eq!(Ok(Some(Allow(vec![Method::Get, Method::Head]))), response.headers.get_typed());
+		assert!(response.headers.contains_key(HeaderName::RETRY_AFTER));
+		assert_eq!("nope", response.body);
+	}
+
+	#[test]
+	fn response_from_httperror_test() {
+		use error::HttpError;
+		use statuscodes::StatusCode;
+		use super::Response;
+		use typed_headers::{ContentLength, ContentType};
+
+		let response = Response::from(HttpError::not_found().with_detail("no such widget"));
+		assert_eq!(StatusCode::NotFound, response.status);
+		assert_eq!(b"404 Not Found: no such widget\n".to_vec(), response.body);
+		assert_eq!(Ok(Some(ContentLength(30))), response.headers.get_typed());
+		let content_type: ContentType = response.headers.get_typed().unwrap().unwrap();
+		assert_eq!(Some("utf-8"), content_type.charset());
+	}
+}
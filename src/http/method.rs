@@ -0,0 +1,346 @@
+use std::error::Error;
+use std::fmt;
+use std::str::{self, FromStr};
+
+use token;
+
+/// A Method represents a HTTP request method.
+///
+/// The request method token is the primary source of request semantics; it indicates the purpose
+/// for which the client has made this request and what is expected by the client as a successful
+/// result ([Section 4 of \[RFC7231\]](http://tools.ietf.org/html/rfc7231#section-4)).
+///
+/// The method token is case-sensitive because it might be used as a gateway to object-based
+/// systems with case-sensitive method names. By convention, standardized methods are defined in
+/// all-uppercase US-ASCII letters, so "get" is an extension method and not `Get`.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Method {
+	/// GET (http://tools.ietf.org/html/rfc7231#section-4.3.1)
+	///
+	/// The GET method requests transfer of a current selected representation for the target
+	/// resource. GET is the primary mechanism of information retrieval and the focus of almost all
+	/// performance optimizations. Hence, when people speak of retrieving some identifiable
+	/// information via HTTP, they are generally referring to making a GET request.
+	///
+	/// A payload within a GET request message has no defined semantics; sending a payload body on
+	/// a GET request might cause some existing implementations to reject the request.
+	///
+	/// The response to a GET request is cacheable; a cache MAY use it to satisfy subsequent GET and
+	/// HEAD requests unless otherwise indicated by the Cache-Control header field
+	/// ([Section 5.2 of \[RFC7234\]](http://tools.ietf.org/html/rfc7234#section-5.2)).
+	Get,
+	/// HEAD (http://tools.ietf.org/html/rfc7231#section-4.3.2)
+	///
+	/// The HEAD method is identical to GET except that the server MUST NOT send a message body in
+	/// the response (i.e., the response terminates at the end of the header section). The server
+	/// SHOULD send the same header fields in response to a HEAD request as it would have sent if
+	/// the request had been a GET, except that the payload header fields
+	/// ([Section 3.3 of \[RFC7231\]](http://tools.ietf.org/html/rfc7231#section-3.3)) MAY be
+	/// omitted. This method can be used for obtaining metadata about the selected representation
+	/// without transferring the representation data and is often used for testing hypertext links
+	/// for validity, accessibility, and recent modification.
+	///
+	/// The response to a HEAD request is cacheable; a cache MAY use it to satisfy subsequent HEAD
+	/// requests unless otherwise indicated by the Cache-Control header field.
+	Head,
+	/// POST (http://tools.ietf.org/html/rfc7231#section-4.3.3)
+	///
+	/// The POST method requests that the target resource process the representation enclosed in
+	/// the request according to the resource's own specific semantics. For example, POST is used
+	/// for providing a block of data to a data-handling process, posting a message to a bulletin
+	/// board, or creating a new resource.
+	///
+	/// If one or more resources has been created on the origin server as a result of successfully
+	/// processing a POST request, the origin server SHOULD send a 201 (Created) response containing
+	/// a Location header field that provides an identifier for the primary resource created.
+	///
+	/// Responses to POST requests are only cacheable when they include explicit freshness
+	/// information
+	/// ([Section 4.2.1 of \[RFC7234\]](http://tools.ietf.org/html/rfc7234#section-4.2.1)).
+	Post,
+	/// PUT (http://tools.ietf.org/html/rfc7231#section-4.3.4)
+	///
+	/// The PUT method requests that the state of the target resource be created or replaced with
+	/// the state defined by the representation enclosed in the request message payload. A
+	/// successful PUT of a given representation would suggest that a subsequent GET on that same
+	/// target resource will result in an equivalent representation being sent in a 200 (OK)
+	/// response.
+	///
+	/// If the target resource does not have a current representation and the PUT successfully
+	/// creates one, then the origin server MUST inform the user agent by sending a 201 (Created)
+	/// response. If the target resource does have a current representation and that
+	/// representation is successfully modified in accordance with the state of the enclosed
+	/// representation, then the origin server MUST send either a 200 (OK) or a 204 (No Content)
+	/// response to indicate successful completion of the request.
+	///
+	/// Responses to the PUT method are not cacheable.
+	Put,
+	/// DELETE (http://tools.ietf.org/html/rfc7231#section-4.3.5)
+	///
+	/// The DELETE method requests that the origin server remove the association between the target
+	/// resource and its current functionality. In effect, this method is similar to the rm command
+	/// in UNIX: it expresses a deletion operation on the URI mapping of the origin server rather
+	/// than an expectation that the previously associated information be deleted.
+	///
+	/// If a DELETE method is successfully applied, the origin server SHOULD send a 202 (Accepted)
+	/// status code if the action will likely succeed but has not yet been enacted, a 204 (No
+	/// Content) status code if the action has been enacted and no further information is to be
+	/// supplied, or a 200 (OK) status code if the action has been enacted and the response message
+	/// includes a representation describing the status.
+	///
+	/// Responses to the DELETE method are not cacheable.
+	Delete,
+	/// CONNECT (http://tools.ietf.org/html/rfc7231#section-4.3.6)
+	///
+	/// The CONNECT method requests that the recipient establish a tunnel to the destination origin
+	/// server identified by the request-target and, if successful, thereafter restrict its behavior
+	/// to blind forwarding of packets, in both directions, until the tunnel is closed. Tunnels are
+	/// commonly used to create an end-to-end virtual connection, through one or more proxies, which
+	/// can then be secured using TLS (Transport Layer Security).
+	///
+	/// CONNECT is intended only for use in requests to a proxy. The request-target of a CONNECT
+	/// request is in authority-form, i.e. only the host name and port number of the tunnel
+	/// destination, separated by a colon.
+	///
+	/// Any 2xx (Successful) response indicates that the sender (and all inbound proxies) will
+	/// switch to tunnel mode immediately after the blank line that concludes the successful
+	/// response's header section.
+	///
+	/// Responses to the CONNECT method are not cacheable.
+	Connect,
+	/// OPTIONS (http://tools.ietf.org/html/rfc7231#section-4.3.7)
+	///
+	/// The OPTIONS method requests information about the communication options available for the
+	/// target resource, at either the origin server or an intervening intermediary. This method
+	/// allows a client to determine the options and/or requirements associated with a resource, or
+	/// the capabilities of a server, without implying a resource action.
+	///
+	/// An OPTIONS request with an asterisk ("*") as the request-target applies to the server in
+	/// general rather than to a specific resource.
+	///
+	/// Responses to the OPTIONS method are not cacheable.
+	Options,
+	/// TRACE (http://tools.ietf.org/html/rfc7231#section-4.3.8)
+	///
+	/// The TRACE method requests a remote, application-level loop-back of the request message. The
+	/// final recipient of the request SHOULD reflect the message received, excluding some fields
+	/// described below, back to the client as the message body of a 200 (OK) response with a
+	/// Content-Type of "message/http".
+	///
+	/// A client MUST NOT generate header fields in a TRACE request containing sensitive data that
+	/// might be disclosed by the response. A client MUST NOT send a message body in a TRACE
+	/// request.
+	///
+	/// Responses to the TRACE method are not cacheable.
+	Trace,
+	/// PATCH (http://tools.ietf.org/html/rfc5789#section-2)
+	///
+	/// The PATCH method requests that a set of changes described in the request entity be applied
+	/// to the resource identified by the Request-URI. The set of changes is represented in a format
+	/// called a "patch document" identified by a media type.
+	///
+	/// The difference between the PUT and PATCH requests is reflected in the way the server
+	/// processes the enclosed entity to modify the resource identified by the Request-URI. In a PUT
+	/// request, the enclosed entity is considered to be a modified version of the resource stored
+	/// on the origin server. With PATCH, however, the enclosed entity contains a set of
+	/// instructions describing how a resource currently residing on the origin server should be
+	/// modified to produce a new version.
+	///
+	/// PATCH is neither safe nor idempotent.
+	Patch,
+	/// The set of methods is extensible; additional methods are registered in the "Hypertext
+	/// Transfer Protocol (HTTP) Method Registry"
+	/// ([Section 8.1 of \[RFC7231\]](http://tools.ietf.org/html/rfc7231#section-8.1)).
+	///
+	/// An origin server that receives a request method that is unrecognized or not implemented
+	/// SHOULD respond with the 501 (Not Implemented) status code. An origin server that receives a
+	/// request method that is recognized and implemented, but not allowed for the target resource,
+	/// SHOULD respond with the 405 (Method Not Allowed) status code.
+	///
+	/// Extension methods are treated as neither safe, idempotent nor cacheable.
+	Extension(ExtensionMethod),
+}
+
+/// The token of an extension method.
+///
+/// It is only created by `Method::from_bytes`, so it is always a token and never the token of
+/// one of the standard methods, which would make a method unequal to itself.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ExtensionMethod(String);
+
+impl ExtensionMethod {
+	/// Returns the method token, e.g. "PROPFIND".
+	pub fn as_str(&self) -> &str {
+		&self.0
+	}
+}
+
+impl Method {
+	/// Parses a method from the method token of a request-line.
+	///
+	/// The token is matched case-sensitively against the standard methods; any other token is an
+	/// `Extension` method.
+	pub fn from_bytes(s: &[u8]) -> Result<Method, InvalidMethod> {
+		match s {
+			b"GET" => Ok(Method::Get),
+			b"HEAD" => Ok(Method::Head),
+			b"POST" => Ok(Method::Post),
+			b"PUT" => Ok(Method::Put),
+			b"DELETE" => Ok(Method::Delete),
+			b"CONNECT" => Ok(Method::Connect),
+			b"OPTIONS" => Ok(Method::Options),
+			b"TRACE" => Ok(Method::Trace),
+			b"PATCH" => Ok(Method::Patch),
+			_ if token::is_token(s) => {
+				// A token is always ASCII, and therefore valid UTF-8.
+				Ok(Method::Extension(ExtensionMethod(str::from_utf8(s).unwrap().to_string())))
+			}
+			_ => Err(InvalidMethod),
+		}
+	}
+
+	/// Returns the method token, e.g. "GET".
+	pub fn as_str(&self) -> &str {
+		match *self {
+			Method::Get => "GET",
+			Method::Head => "HEAD",
+			Method::Post => "POST",
+			Method::Put => "PUT",
+			Method::Delete => "DELETE",
+			Method::Connect => "CONNECT",
+			Method::Options => "OPTIONS",
+			Method::Trace => "TRACE",
+			Method::Patch => "PATCH",
+			Method::Extension(ref method) => method.as_str(),
+		}
+	}
+
+	/// Returns true if the semantics of the method are essentially read-only; i.e., the client
+	/// does not request, and does not expect, any state change on the origin server as a result of
+	/// applying a safe method to a target resource
+	/// ([Section 4.2.1 of \[RFC7231\]](http://tools.ietf.org/html/rfc7231#section-4.2.1)).
+	///
+	/// Of the methods defined by RFC 7231, GET, HEAD, OPTIONS, and TRACE are safe.
+	pub fn is_safe(&self) -> bool {
+		matches!(*self, Method::Get | Method::Head | Method::Options | Method::Trace)
+	}
+
+	/// Returns true if the intended effect on the server of multiple identical requests with the
+	/// method is the same as the effect for a single such request
+	/// ([Section 4.2.2 of \[RFC7231\]](http://tools.ietf.org/html/rfc7231#section-4.2.2)).
+	///
+	/// PUT, DELETE, and the safe methods are idempotent. Idempotent requests can be repeated
+	/// automatically if a communication failure occurs before the client is able to read the
+	/// server's response.
+	pub fn is_idempotent(&self) -> bool {
+		self.is_safe() || matches!(*self, Method::Put | Method::Delete)
+	}
+
+	/// Returns true if responses to the method are allowed to be stored for future reuse
+	/// ([Section 4.2.3 of \[RFC7231\]](http://tools.ietf.org/html/rfc7231#section-4.2.3)).
+	///
+	/// GET, HEAD, and POST are cacheable, although the overwhelming majority of cache
+	/// implementations only support GET and HEAD.
+	pub fn is_cacheable(&self) -> bool {
+		matches!(*self, Method::Get | Method::Head | Method::Post)
+	}
+}
+
+impl FromStr for Method {
+	type Err = InvalidMethod;
+
+	fn from_str(s: &str) -> Result<Method, InvalidMethod> {
+		Method::from_bytes(s.as_bytes())
+	}
+}
+
+impl fmt::Display for Method {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+/// An error returned when a method is not a valid token.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct InvalidMethod;
+
+impl fmt::Display for InvalidMethod {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("invalid method: not a token")
+	}
+}
+
+impl Error for InvalidMethod {}
+
+#[cfg(test)]
+mod test {
+
+	#[test]
+	fn method_from_bytes_test() {
+		use super::Method;
+
+		let test_cases = vec![
+			("GET", Method::Get),
+			("HEAD", Method::Head),
+			("POST", Method::Post),
+			("PUT", Method::Put),
+			("DELETE", Method::Delete),
+			("CONNECT", Method::Connect),
+			("OPTIONS", Method::Options),
+			("TRACE", Method::Trace),
+			("PATCH", Method::Patch),
+		];
+
+		for (s, expect) in test_cases {
+			assert_eq!(Ok(expect.clone()), Method::from_bytes(s.as_bytes()));
+			assert_eq!(Ok(expect.clone()), s.parse());
+			assert_eq!(s, expect.as_str());
+			assert_eq!(s, expect.to_string());
+		}
+
+		for s in ["PROPFIND", "get", "M-SEARCH"] {
+			let method: Method = s.parse().unwrap();
+			match method {
+				Method::Extension(ref extension) => assert_eq!(s, extension.as_str()),
+				_ => panic!("{} is not an extension method", s),
+			}
+			assert_eq!(s, method.as_str());
+			assert_eq!(s, method.to_string());
+		}
+	}
+
+	#[test]
+	fn method_from_bytes_rejects_non_tokens() {
+		use super::{InvalidMethod, Method};
+
+		for s in [&b""[..], b"GET ", b" GET", b"GE T", b"GET\r\n", b"G:ET", b"\xc3\xa9"] {
+			assert_eq!(Err(InvalidMethod), Method::from_bytes(s), "{:?}", s);
+		}
+	}
+
+	#[test]
+	fn method_properties_test() {
+		use super::Method;
+
+		// (method, safe, idempotent, cacheable), per RFC 7231, Section 4.2 and RFC 5789,
+		// Section 2.
+		let test_cases = vec![
+			(Method::Get, true, true, true),
+			(Method::Head, true, true, true),
+			(Method::Post, false, false, true),
+			(Method::Put, false, true, false),
+			(Method::Delete, false, true, false),
+			(Method::Connect, false, false, false),
+			(Method::Options, true, true, false),
+			(Method::Trace, true, true, false),
+			(Method::Patch, false, false, false),
+			("PROPFIND".parse().unwrap(), false, false, false),
+		];
+
+		for (method, safe, idempotent, cacheable) in test_cases {
+			assert_eq!(safe, method.is_safe(), "{}", method);
+			assert_eq!(idempotent, method.is_idempotent(), "{}", method);
+			assert_eq!(cacheable, method.is_cacheable(), "{}", method);
+		}
+	}
+}
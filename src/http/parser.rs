@@ -0,0 +1,723 @@
+//! An incremental parser for the head of HTTP/1.1 messages
+//! ([Section 3 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-3)).
+//!
+//! ```c
+//! HTTP-message = start-line
+//!                *( header-field CRLF )
+//!                CRLF
+//!                [ message-body ]
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::str;
+
+use header::{HeaderMap, HeaderName, HeaderValue};
+use method::Method;
+use statuscodes::StatusCode;
+use token;
+use version::HttpVersion;
+
+/// The default limit on the length of a single line of a message head, in bytes.
+pub const DEFAULT_MAX_LINE_LENGTH: usize = 8192;
+
+/// The default limit on the number of header fields in a message head.
+pub const DEFAULT_MAX_HEADERS: usize = 100;
+
+/// The Status of parsing a message head from the bytes received so far.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Status<T> {
+	/// More bytes are needed to complete the message head.
+	Partial,
+	/// The message head is complete: it was parsed into a `T` from the first `usize` bytes.
+	///
+	/// The remaining bytes, if any, belong to the message body or the next message.
+	Complete(usize, T),
+}
+
+impl<T> Status<T> {
+	/// Returns true if the message head is complete.
+	pub fn is_complete(&self) -> bool {
+		match *self {
+			Status::Partial => false,
+			Status::Complete(..) => true,
+		}
+	}
+}
+
+/// A HeaderField is a header field line of a message head, borrowed from the received bytes.
+///
+/// ```c
+/// header-field = field-name ":" OWS field-value OWS
+/// ```
+///
+/// The field-value has its leading and trailing whitespace removed.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct HeaderField<'b> {
+	/// The field-name, as it was received.
+	pub name: &'b str,
+	/// The field-value, which may contain obs-text that is not valid UTF-8.
+	pub value: &'b [u8],
+}
+
+/// A RequestHead is the request-line and header fields of a request message, borrowed from the
+/// received bytes.
+#[derive(Clone, PartialEq, Debug)]
+pub struct RequestHead<'b> {
+	/// The request method; only an extension method is copied.
+	pub method: Method,
+	/// The request-target, which can be parsed by `RequestTarget::parse`.
+	pub target: &'b str,
+	/// The protocol version.
+	pub version: HttpVersion,
+	/// The header fields, in the order they were received.
+	pub headers: Vec<HeaderField<'b>>,
+}
+
+impl<'b> RequestHead<'b> {
+	/// Copies the header fields into a HeaderMap.
+	pub fn header_map(&self) -> HeaderMap {
+		header_map(&self.headers)
+	}
+}
+
+/// A ResponseHead is the status-line and header fields of a response message, borrowed from the
+/// received bytes.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ResponseHead<'b> {
+	/// The protocol version.
+	pub version: HttpVersion,
+	/// The status code.
+	pub status: StatusCode,
+	/// The reason phrase, which may contain obs-text that is not valid UTF-8.
+	pub reason: &'b [u8],
+	/// The header fields, in the order they were received.
+	pub headers: Vec<HeaderField<'b>>,
+}
+
+impl<'b> ResponseHead<'b> {
+	/// Copies the header fields into a HeaderMap.
+	pub fn header_map(&self) -> HeaderMap {
+		header_map(&self.headers)
+	}
+}
+
+/// A Parser parses the head of request and response messages as their bytes arrive.
+///
+/// The caller appends the bytes it receives to a buffer and passes the whole buffer to
+/// `parse_request` or `parse_response` until the head is `Complete`. The parser remembers how
+/// much of the buffer it has already scanned for the end of the head, so each call only looks at
+/// the new bytes, and the parsed head borrows from the buffer instead of copying it.
+///
+/// Once a head is complete the parser is ready for the next message, which starts after the
+/// bytes that were consumed.
+///
+/// Parsing never panics and depends only on the bytes passed in, so arbitrary input can be fed to
+/// it by a fuzzer.
+///
+/// ```
+/// use http::{Method, Parser, Status};
+///
+/// let mut parser = Parser::new();
+/// let mut buf = b"GET /index.html HTTP/1.1\r\nHost: exa".to_vec();
+/// assert_eq!(Ok(Status::Partial), parser.parse_request(&buf));
+///
+/// buf.extend_from_slice(b"mple.com\r\n\r\n");
+/// match parser.parse_request(&buf) {
+///     Ok(Status::Complete(n, head)) => {
+///         assert_eq!(buf.len(), n);
+///         assert_eq!(Method::Get, head.method);
+///         assert_eq!("/index.html", head.target);
+///         assert_eq!(&b"example.com"[..], head.headers[0].value);
+///     }
+///     other => panic!("{:?}", other),
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Parser {
+	max_line_length: usize,
+	max_headers: usize,
+	allow_bare_lf: bool,
+	/// The number of bytes of the buffer that have been scanned.
+	scanned: usize,
+	/// The start of the line being scanned.
+	line_start: usize,
+	/// The number of lines scanned, including empty lines ahead of the start-line.
+	lines: usize,
+	/// Whether a non-empty line, i.e. the start-line, has been scanned.
+	started: bool,
+}
+
+impl Default for Parser {
+	fn default() -> Parser {
+		Parser::new()
+	}
+}
+
+impl Parser {
+	/// Creates a Parser with the default limits, which requires every line to end with CRLF.
+	pub fn new() -> Parser {
+		Parser {
+			max_line_length: DEFAULT_MAX_LINE_LENGTH,
+			max_headers: DEFAULT_MAX_HEADERS,
+			allow_bare_lf: false,
+			scanned: 0,
+			line_start: 0,
+			lines: 0,
+			started: false,
+		}
+	}
+
+	/// Sets the limit on the length of a single line, not counting its line ending.
+	pub fn max_line_length(mut self, max: usize) -> Parser {
+		self.max_line_length = max;
+		self
+	}
+
+	/// Sets the limit on the number of header fields.
+	pub fn max_headers(mut self, max: usize) -> Parser {
+		self.max_headers = max;
+		self
+	}
+
+	/// Sets whether a line may end with a bare LF instead of CRLF.
+	///
+	/// A recipient MAY recognize a single LF as a line terminator and ignore any preceding CR
+	/// ([Section 3.5 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-3.5)).
+	pub fn allow_bare_lf(mut self, allow: bool) -> Parser {
+		self.allow_bare_lf = allow;
+		self
+	}
+
+	/// Forgets the bytes scanned so far, so that parsing starts over with a new buffer.
+	pub fn reset(&mut self) {
+		self.scanned = 0;
+		self.line_start = 0;
+		self.lines = 0;
+		self.started = false;
+	}
+
+	/// Parses the head of a request message from the bytes received so far.
+	///
+	/// Empty lines ahead of the request-line are ignored
+	/// ([Section 3.5 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-3.5)).
+	pub fn parse_request<'b>(&mut self, buf: &'b [u8])
+		-> Result<Status<RequestHead<'b>>, ParseError>
+	{
+		let n = match self.scan(buf)? {
+			Some(n) => n,
+			None => return Ok(Status::Partial),
+		};
+		let mut lines = Lines::new(&buf[..n]);
+		let (method, target, version) = parse_request_line(lines.start_line())?;
+		let headers = parse_header_fields(lines)?;
+		Ok(Status::Complete(n, RequestHead {
+			method,
+			target,
+			version,
+			headers,
+		}))
+	}
+
+	/// Parses the head of a response message from the bytes received so far.
+	pub fn parse_response<'b>(&mut self, buf: &'b [u8])
+		-> Result<Status<ResponseHead<'b>>, ParseError>
+	{
+		let n = match self.scan(buf)? {
+			Some(n) => n,
+			None => return Ok(Status::Partial),
+		};
+		let mut lines = Lines::new(&buf[..n]);
+		let (version, status, reason) = parse_status_line(lines.start_line())?;
+		let headers = parse_header_fields(lines)?;
+		Ok(Status::Complete(n, ResponseHead {
+			version,
+			status,
+			reason,
+			headers,
+		}))
+	}
+
+	/// Scans the new bytes of `buf` for the empty line that ends the head, enforcing the limits
+	/// and line endings on the way. Returns the length of the head once it is complete.
+	fn scan(&mut self, buf: &[u8]) -> Result<Option<usize>, ParseError> {
+		if buf.len() < self.scanned {
+			// Not the buffer that was scanned before.
+			self.reset();
+		}
+
+		let result = self.scan_lines(buf);
+		if result != Ok(None) {
+			self.reset();
+		}
+		result
+	}
+
+	fn scan_lines(&mut self, buf: &[u8]) -> Result<Option<usize>, ParseError> {
+		while let Some(i) = buf[self.scanned..].iter().position(|&b| b == b'\n') {
+			let lf = self.scanned + i;
+			let line = &buf[self.line_start..lf];
+			let line = match line.strip_suffix(b"\r") {
+				Some(line) => line,
+				None if self.allow_bare_lf => line,
+				None => return Err(ParseError::InvalidLineEnding),
+			};
+			self.check_line_length(line.len())?;
+
+			self.scanned = lf + 1;
+			self.line_start = lf + 1;
+			if line.is_empty() && self.started {
+				return Ok(Some(lf + 1));
+			}
+			self.started |= !line.is_empty();
+
+			self.lines += 1;
+			if self.lines > self.max_headers + 1 {
+				return Err(ParseError::TooManyHeaders);
+			}
+		}
+
+		self.scanned = buf.len();
+		let partial = &buf[self.line_start..];
+		let partial = partial.strip_suffix(b"\r").unwrap_or(partial);
+		self.check_line_length(partial.len())?;
+		Ok(None)
+	}
+
+	fn check_line_length(&self, len: usize) -> Result<(), ParseError> {
+		if len <= self.max_line_length {
+			Ok(())
+		} else if self.started {
+			Err(ParseError::HeaderLineTooLong)
+		} else {
+			Err(ParseError::StartLineTooLong)
+		}
+	}
+}
+
+/// An error returned when the head of a message cannot be parsed.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ParseError {
+	/// The method or a field-name is not a token.
+	InvalidToken,
+	/// The request-target is empty or contains a character other than a VCHAR.
+	InvalidTarget,
+	/// The protocol version is not of the form "HTTP/x.y", or has an unknown major version.
+	InvalidVersion,
+	/// The status code is not three digits, or is not in the range 100-999.
+	InvalidStatusCode,
+	/// The reason phrase contains a control character.
+	InvalidReasonPhrase,
+	/// A field-value contains a control character.
+	InvalidHeaderValue,
+	/// A line ends with a bare CR, or with a bare LF when that is not allowed.
+	InvalidLineEnding,
+	/// A header field line is folded onto the next line (obs-fold), which a recipient MUST reject
+	/// or replace with spaces
+	/// ([Section 3.2.4 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-3.2.4)).
+	ObsFold,
+	/// The start-line or a header field line does not have the required structure, e.g. a missing
+	/// space or colon, or whitespace ahead of the first header field.
+	Malformed,
+	/// The request-line or status-line is longer than the limit.
+	StartLineTooLong,
+	/// A header field line is longer than the limit.
+	HeaderLineTooLong,
+	/// There are more header fields than the limit.
+	TooManyHeaders,
+}
+
+impl ParseError {
+	/// Returns the status code a server responds with when it fails to parse a request head.
+	pub fn status(self) -> StatusCode {
+		match self {
+			ParseError::StartLineTooLong => StatusCode::UriTooLong,
+			ParseError::HeaderLineTooLong | ParseError::TooManyHeaders => {
+				StatusCode::RequestHeaderFieldsTooLarge
+			}
+			_ => StatusCode::BadRequest,
+		}
+	}
+}
+
+impl fmt::Display for ParseError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let description = match *self {
+			ParseError::InvalidToken => "invalid token in message head",
+			ParseError::InvalidTarget => "invalid request-target",
+			ParseError::InvalidVersion => "invalid HTTP-version",
+			ParseError::InvalidStatusCode => "invalid status-code",
+			ParseError::InvalidReasonPhrase => "invalid reason-phrase",
+			ParseError::InvalidHeaderValue => "invalid field-value",
+			ParseError::InvalidLineEnding => "invalid line ending in message head",
+			ParseError::ObsFold => "obsolete line folding in header field",
+			ParseError::Malformed => "malformed message head",
+			ParseError::StartLineTooLong => "start-line too long",
+			ParseError::HeaderLineTooLong => "header field line too long",
+			ParseError::TooManyHeaders => "too many header fields",
+		};
+		f.write_str(description)
+	}
+}
+
+impl Error for ParseError {}
+
+/// Lines splits a complete message head into its lines, without their line endings.
+struct Lines<'b> {
+	head: &'b [u8],
+}
+
+impl<'b> Lines<'b> {
+	fn new(head: &'b [u8]) -> Lines<'b> {
+		Lines { head }
+	}
+
+	/// Returns the first non-empty line.
+	fn start_line(&mut self) -> &'b [u8] {
+		self.find(|line| !line.is_empty()).unwrap_or(b"")
+	}
+}
+
+impl<'b> Iterator for Lines<'b> {
+	type Item = &'b [u8];
+
+	fn next(&mut self) -> Option<&'b [u8]> {
+		let lf = self.head.iter().position(|&b| b == b'\n')?;
+		let line = &self.head[..lf];
+		self.head = &self.head[lf + 1..];
+		Some(line.strip_suffix(b"\r").unwrap_or(line))
+	}
+}
+
+/// ```c
+/// request-line = method SP request-target SP HTTP-version CRLF
+/// ```
+fn parse_request_line(line: &[u8]) -> Result<(Method, &str, HttpVersion), ParseError> {
+	let mut parts = line.splitn(3, |&b| b == b' ');
+	let (method, target, version) = match (parts.next(), parts.next(), parts.next()) {
+		(Some(method), Some(target), Some(version)) => (method, target, version),
+		_ => return Err(ParseError::Malformed),
+	};
+
+	if !token::is_token(method) {
+		return Err(ParseError::InvalidToken);
+	}
+	let method = Method::from_bytes(method).map_err(|_| ParseError::InvalidToken)?;
+
+	if target.is_empty() || !target.iter().all(|&b| (0x21..=0x7e).contains(&b)) {
+		return Err(ParseError::InvalidTarget);
+	}
+	// The target is visible ASCII, and therefore valid UTF-8.
+	let target = str::from_utf8(target).map_err(|_| ParseError::InvalidTarget)?;
+
+	let version = HttpVersion::parse(version).map_err(|_| ParseError::InvalidVersion)?;
+	Ok((method, target, version))
+}
+
+/// ```c
+/// status-line = HTTP-version SP status-code SP reason-phrase CRLF
+/// ```
+fn parse_status_line(line: &[u8]) -> Result<(HttpVersion, StatusCode, &[u8]), ParseError> {
+	let sp = line.iter().position(|&b| b == b' ').ok_or(ParseError::Malformed)?;
+	let version = HttpVersion::parse(&line[..sp]).map_err(|_| ParseError::InvalidVersion)?;
+
+	let rest = &line[sp + 1..];
+	let (digits, reason) = match rest.get(3) {
+		None => (rest, &rest[rest.len()..]),
+		Some(&b' ') => (&rest[..3], &rest[4..]),
+		Some(_) => return Err(ParseError::InvalidStatusCode),
+	};
+	if digits.len() != 3 || !digits.iter().all(u8::is_ascii_digit) {
+		return Err(ParseError::InvalidStatusCode);
+	}
+	let code = digits.iter().fold(0, |n, &d| n * 10 + (d - b'0') as u16);
+	if code < 100 {
+		return Err(ParseError::InvalidStatusCode);
+	}
+
+	if !reason.iter().all(|&b| token::is_field_value_byte(b)) {
+		return Err(ParseError::InvalidReasonPhrase);
+	}
+	Ok((version, StatusCode::from_u16(code), reason))
+}
+
+fn parse_header_fields<'b, I>(lines: I) -> Result<Vec<HeaderField<'b>>, ParseError>
+	where I: Iterator<Item = &'b [u8]>
+{
+	let mut fields = Vec::new();
+	for line in lines.take_while(|line| !line.is_empty()) {
+		if line[0] == b' ' || line[0] == b'\t' {
+			return Err(if fields.is_empty() { ParseError::Malformed } else { ParseError::ObsFold });
+		}
+		fields.push(parse_header_field(line)?);
+	}
+	Ok(fields)
+}
+
+/// Parses a single header field line, without its line ending, e.g. a field of the trailer of a
+/// chunked body.
+///
+/// ```c
+/// header-field = field-name ":" OWS field-value OWS
+/// ```
+pub fn parse_header_field(line: &[u8]) -> Result<HeaderField<'_>, ParseError> {
+	let colon = line.iter().position(|&b| b == b':').ok_or(ParseError::Malformed)?;
+
+	// No whitespace is allowed between the field-name and colon, so it fails the token check.
+	let name = &line[..colon];
+	if !token::is_token(name) {
+		return Err(ParseError::InvalidToken);
+	}
+	// A token is always ASCII, and therefore valid UTF-8.
+	let name = str::from_utf8(name).map_err(|_| ParseError::InvalidToken)?;
+
+	let value = trim_ows(&line[colon + 1..]);
+	if !value.iter().all(|&b| token::is_field_value_byte(b)) {
+		return Err(ParseError::InvalidHeaderValue);
+	}
+	Ok(HeaderField { name, value })
+}
+
+fn trim_ows(mut value: &[u8]) -> &[u8] {
+	while let [b' ' | b'\t', rest @ ..] = value {
+		value = rest;
+	}
+	while let [rest @ .., b' ' | b'\t'] = value {
+		value = rest;
+	}
+	value
+}
+
+fn header_map(fields: &[HeaderField]) -> HeaderMap {
+	fields.iter()
+		.filter_map(|field| {
+			// The parser has already validated the name and value.
+			let name = HeaderName::from_bytes(field.name.as_bytes()).ok()?;
+			let value = HeaderValue::from_bytes(field.value).ok()?;
+			Some((name, value))
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod test {
+
+	#[test]
+	fn parse_request_test() {
+		use method::Method;
+		use super::{HeaderField, Parser, RequestHead, Status};
+		use version::HttpVersion;
+
+		let test_cases = vec![
+			(&b"GET / HTTP/1.1\r\n\r\n"[..], Method::Get, "/", HttpVersion::Http11, vec![]),
+			(b"\r\nPOST /a?b HTTP/1.0\r\nHost: x\r\n\r\n", Method::Post, "/a?b",
+				HttpVersion::Http10, vec![("Host", &b"x"[..])]),
+			(b"OPTIONS * HTTP/1.1\r\nA: \t1 2 \t\r\nB:\r\nb: \xe9\r\n\r\n", Method::Options, "*",
+				HttpVersion::Http11, vec![("A", &b"1 2"[..]), ("B", b""), ("b", b"\xe9")]),
+			(b"PROPFIND http://x/ HTTP/1.1\r\n\r\n", "PROPFIND".parse().unwrap(),
+				"http://x/", HttpVersion::Http11, vec![]),
+		];
+
+		for (buf, method, target, version, headers) in test_cases {
+			let expect = RequestHead {
+				method,
+				target,
+				version,
+				headers: headers.into_iter().map(|(name, value)| HeaderField { name, value }).collect(),
+			};
+			assert_eq!(Ok(Status::Complete(buf.len(), expect)), Parser::new().parse_request(buf));
+		}
+	}
+
+	#[test]
+	fn parse_response_test() {
+		use statuscodes::StatusCode;
+		use super::{Parser, Status};
+		use version::HttpVersion;
+
+		let test_cases = vec![
+			(&b"HTTP/1.1 200 OK\r\n\r\n"[..], HttpVersion::Http11, StatusCode::Ok, &b"OK"[..], 0),
+			(b"HTTP/1.0 404 Not Found\r\nA: b\r\n\r\n", HttpVersion::Http10, StatusCode::NotFound,
+				b"Not Found", 1),
+			(b"HTTP/1.1 204\r\n\r\n", HttpVersion::Http11, StatusCode::NoContent, b"", 0),
+			(b"HTTP/1.1 471 \r\n\r\n", HttpVersion::Http11, StatusCode::from_u16(471), b"", 0),
+			(b"HTTP/1.1 200 \xe9t\xe9\r\n\r\n", HttpVersion::Http11, StatusCode::Ok, b"\xe9t\xe9", 0),
+		];
+
+		for (buf, version, status, reason, headers) in test_cases {
+			match Parser::new().parse_response(buf) {
+				Ok(Status::Complete(n, head)) => {
+					assert_eq!(buf.len(), n);
+					assert_eq!(version, head.version);
+					assert_eq!(status, head.status);
+					assert_eq!(reason, head.reason);
+					assert_eq!(headers, head.headers.len());
+				}
+				other => panic!("{:?}: {:?}", buf, other),
+			}
+		}
+	}
+
+	#[test]
+	fn parse_incremental_test() {
+		use super::{Parser, Status};
+
+		let buf = b"GET /a HTTP/1.1\r\nHost: example.com\r\nAccept: */*\r\n\r\nbody";
+		let head_len = buf.len() - 4;
+		let expect = Parser::new().parse_request(buf);
+		assert!(matches!(expect, Ok(Status::Complete(n, _)) if n == head_len));
+
+		// Feeding the bytes one at a time gives the same result once the head is complete.
+		let mut parser = Parser::new();
+		for end in 1..head_len {
+			assert_eq!(Ok(Status::Partial), parser.parse_request(&buf[..end]), "{}", end);
+		}
+		assert_eq!(expect, parser.parse_request(&buf[..head_len]));
+
+		// The parser is ready for the next message once a head is complete.
+		let pipelined = b"GET /a HTTP/1.1\r\n\r\nGET /b HTTP/1.1\r\n\r\n";
+		let mut parser = Parser::new();
+		let n = match parser.parse_request(pipelined) {
+			Ok(Status::Complete(n, head)) => {
+				assert_eq!("/a", head.target);
+				n
+			}
+			other => panic!("{:?}", other),
+		};
+		match parser.parse_request(&pipelined[n..]) {
+			Ok(Status::Complete(_, head)) => assert_eq!("/b", head.target),
+			other => panic!("{:?}", other),
+		}
+	}
+
+	#[test]
+	fn parse_error_test() {
+		use super::{ParseError, Parser};
+
+		let test_cases = vec![
+			(&b"GET / HTTP/1.1\n\n"[..], ParseError::InvalidLineEnding),
+			(b"GET / HTTP/1.1\r\nA: b\n\r\n", ParseError::InvalidLineEnding),
+			(b"GET / HTTP/1.1\r\nA: b\rc\r\n\r\n", ParseError::InvalidHeaderValue),
+			(b"GET / HTTP/1.1\r\nA: b\r\n c\r\n\r\n", ParseError::ObsFold),
+			(b"GET / HTTP/1.1\r\nA: b\r\n\tc\r\n\r\n", ParseError::ObsFold),
+			(b"GET / HTTP/1.1\r\n A: b\r\n\r\n", ParseError::Malformed),
+			(b"GET / HTTP/1.1\r\nA b\r\n\r\n", ParseError::Malformed),
+			(b"GET / HTTP/1.1\r\nA : b\r\n\r\n", ParseError::InvalidToken),
+			(b"GET / HTTP/1.1\r\n: b\r\n\r\n", ParseError::InvalidToken),
+			(b"GET / HTTP/1.1\r\nA: b\x00\r\n\r\n", ParseError::InvalidHeaderValue),
+			(b"GET /\r\n\r\n", ParseError::Malformed),
+			(b"G(T / HTTP/1.1\r\n\r\n", ParseError::InvalidToken),
+			(b" GET / HTTP/1.1\r\n\r\n", ParseError::InvalidToken),
+			(b"GET  / HTTP/1.1\r\n\r\n", ParseError::InvalidTarget),
+			(b"GET /\xe9 HTTP/1.1\r\n\r\n", ParseError::InvalidTarget),
+			(b"GET / HTTP/1.1 \r\n\r\n", ParseError::InvalidVersion),
+			(b"GET / http/1.1\r\n\r\n", ParseError::InvalidVersion),
+		];
+
+		for (buf, expect) in test_cases {
+			assert_eq!(Err(expect), Parser::new().parse_request(buf), "{:?}", buf);
+		}
+
+		let test_cases = vec![
+			(&b"HTTP/1.1\r\n\r\n"[..], ParseError::Malformed),
+			(b"HTTP/4.0 200 OK\r\n\r\n", ParseError::InvalidVersion),
+			(b"HTTP/1.1 2000 OK\r\n\r\n", ParseError::InvalidStatusCode),
+			(b"HTTP/1.1 20 OK\r\n\r\n", ParseError::InvalidStatusCode),
+			(b"HTTP/1.1 042 OK\r\n\r\n", ParseError::InvalidStatusCode),
+			(b"HTTP/1.1 200 O\x7fK\r\n\r\n", ParseError::InvalidReasonPhrase),
+		];
+
+		for (buf, expect) in test_cases {
+			assert_eq!(Err(expect), Parser::new().parse_response(buf), "{:?}", buf);
+		}
+	}
+
+	#[test]
+	fn parse_bare_lf_test() {
+		use super::{Parser, Status};
+
+		let mut parser = Parser::new().allow_bare_lf(true);
+		match parser.parse_request(b"GET / HTTP/1.1\nA: b\r\nC: d\n\n") {
+			Ok(Status::Complete(n, head)) => {
+				assert_eq!(27, n);
+				assert_eq!(&b"b"[..], head.headers[0].value);
+				assert_eq!(&b"d"[..], head.headers[1].value);
+			}
+			other => panic!("{:?}", other),
+		}
+	}
+
+	#[test]
+	fn parse_limits_test() {
+		use statuscodes::StatusCode;
+		use super::{ParseError, Parser, Status};
+
+		let mut parser = Parser::new().max_line_length(16).max_headers(2);
+		assert_eq!(Err(ParseError::StartLineTooLong), parser.parse_request(b"GET /0123456789ab"));
+		assert_eq!(StatusCode::UriTooLong, ParseError::StartLineTooLong.status());
+
+		// The limits are enforced before the head is complete.
+		let mut parser = Parser::new().max_line_length(16).max_headers(2);
+		assert_eq!(Ok(Status::Partial), parser.parse_request(b"GET / HTTP/1.1\r\n"));
+		assert_eq!(Err(ParseError::HeaderLineTooLong),
+			parser.parse_request(b"GET / HTTP/1.1\r\nA: 0123456789abcd"));
+
+		let mut parser = Parser::new().max_line_length(16).max_headers(2);
+		assert!(parser.parse_request(b"GET / HTTP/1.1\r\nA: b\r\nC: d\r\n\r\n").unwrap().is_complete());
+		assert_eq!(Err(ParseError::TooManyHeaders),
+			parser.parse_request(b"GET / HTTP/1.1\r\nA: b\r\nC: d\r\nE: f\r\n"));
+		assert_eq!(StatusCode::RequestHeaderFieldsTooLarge, ParseError::TooManyHeaders.status());
+	}
+
+	#[test]
+	fn parse_header_map_test() {
+		use header::{HeaderName, HeaderValue};
+		use super::{Parser, Status};
+
+		match Parser::new().parse_request(b"GET / HTTP/1.1\r\nset-cookie: a\r\nSet-Cookie: b\r\n\r\n") {
+			Ok(Status::Complete(_, head)) => {
+				let headers = head.header_map();
+				let values: Vec<_> = headers.get_all(HeaderName::SET_COOKIE).collect();
+				assert_eq!(vec![&HeaderValue::from_static("a"), &HeaderValue::from_static("b")], values);
+			}
+			other => panic!("{:?}", other),
+		}
+	}
+
+	#[test]
+	fn parse_mutation_test() {
+		use super::{Parser, Status};
+
+		let messages: Vec<&[u8]> = vec![
+			b"POST /a?b HTTP/1.1\r\nHost: x\r\nContent-Length: 3\r\n\r\nabc",
+			b"HTTP/1.1 200 OK\r\nA: b\r\n c\r\n\r\n",
+		];
+
+		// Every prefix and single byte mutation of the messages must parse without panicking,
+		// and give the same result incrementally as in one go.
+		let mut seed = 0x2545_f491_u32;
+		for message in messages {
+			for i in 0..message.len() {
+				for _ in 0..8 {
+					seed ^= seed << 13;
+					seed ^= seed >> 17;
+					seed ^= seed << 5;
+
+					let mut buf = message.to_vec();
+					buf[i] = seed as u8;
+
+					let config = Parser::new().allow_bare_lf(seed & 0x100 != 0).max_line_length(24);
+					let mut parser = config.clone();
+					for end in 0..buf.len() + 1 {
+						let result = parser.parse_request(&buf[..end]);
+						assert_eq!(config.clone().parse_request(&buf[..end]), result);
+						if result != Ok(Status::Partial) {
+							break;
+						}
+					}
+					let _ = config.clone().parse_response(&buf);
+				}
+			}
+		}
+	}
+}
@@ -0,0 +1,286 @@
+//! Serialize and Deserialize implementations, enabled by the `serde` feature.
+//!
+//! A `StatusCode` is serialized as its integer value, e.g. `404`. An `Extension` code that carries
+//! a reason phrase is serialized as a map, e.g. `{"code": 471, "phrase": "Too Many Widgets"}`, so
+//! that the phrase survives a round trip. A `StatusCodeClass` is serialized as a string, e.g.
+//! `"4xx"`.
+//!
+//! Deserialized phrases are kept for the life of the program, so only a bounded number of them
+//! can be: deserializing a phrase longer than 128 bytes, or a new one after 256 distinct phrases
+//! have been kept, fails rather than losing the phrase. So does a phrase that is not a valid
+//! reason-phrase, e.g. one containing CR or LF.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Mutex;
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, Unexpected, Visitor};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+use statuscodes::{StatusCode, StatusCodeClass};
+
+const STATUS_CODE_FIELDS: &[&str] = &["code", "phrase"];
+
+/// The number of distinct extension reason phrases that are kept by deserialization.
+const MAX_PHRASES: usize = 256;
+
+/// The length of the longest extension reason phrase that is kept by deserialization.
+const MAX_PHRASE_LENGTH: usize = 128;
+
+impl Serialize for StatusCode {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		match *self {
+			StatusCode::Extension(code, phrase) if !phrase.is_empty() => {
+				let mut state = serializer.serialize_struct("StatusCode", 2)?;
+				state.serialize_field("code", &code)?;
+				state.serialize_field("phrase", phrase)?;
+				state.end()
+			}
+			status => serializer.serialize_u16(status.to_u16()),
+		}
+	}
+}
+
+impl<'de> Deserialize<'de> for StatusCode {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<StatusCode, D::Error> {
+		deserializer.deserialize_any(StatusCodeVisitor)
+	}
+}
+
+struct StatusCodeVisitor;
+
+impl<'de> Visitor<'de> for StatusCodeVisitor {
+	type Value = StatusCode;
+
+	fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("a three-digit status code, or a map with a code and a phrase")
+	}
+
+	fn visit_u64<E: de::Error>(self, n: u64) -> Result<StatusCode, E> {
+		status_code_from_u64(n, "")
+	}
+
+	fn visit_i64<E: de::Error>(self, n: i64) -> Result<StatusCode, E> {
+		if n < 0 {
+			return Err(E::invalid_value(Unexpected::Signed(n), &self));
+		}
+		status_code_from_u64(n as u64, "")
+	}
+
+	fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<StatusCode, A::Error> {
+		let mut code = None;
+		let mut phrase = None;
+
+		while let Some(key) = map.next_key::<String>()? {
+			match key.as_str() {
+				"code" if code.is_none() => code = Some(map.next_value::<u64>()?),
+				"phrase" if phrase.is_none() => phrase = Some(map.next_value::<String>()?),
+				"code" => return Err(de::Error::duplicate_field("code")),
+				"phrase" => return Err(de::Error::duplicate_field("phrase")),
+				_ => return Err(de::Error::unknown_field(&key, STATUS_CODE_FIELDS)),
+			}
+		}
+
+		let code = code.ok_or_else(|| de::Error::missing_field("code"))?;
+		let phrase = phrase.unwrap_or_default();
+		status_code_from_u64(code, &phrase)
+	}
+}
+
+fn status_code_from_u64<E: de::Error>(n: u64, phrase: &str) -> Result<StatusCode, E> {
+	if n > u16::MAX as u64 {
+		return Err(E::invalid_value(Unexpected::Unsigned(n), &StatusCodeVisitor));
+	}
+
+	match StatusCode::try_from_u16(n as u16) {
+		Ok(StatusCode::Extension(code, _)) => {
+			Ok(StatusCode::Extension(code, extension_phrase(phrase)?))
+		}
+		Ok(status) => Ok(status),
+		Err(_) => Err(E::invalid_value(Unexpected::Unsigned(n), &StatusCodeVisitor)),
+	}
+}
+
+/// Returns a `'static` copy of the reason phrase of a deserialized extension code.
+fn extension_phrase<E: de::Error>(phrase: &str) -> Result<&'static str, E> {
+	if phrase.is_empty() {
+		return Ok("");
+	}
+	if phrase.len() > MAX_PHRASE_LENGTH || !is_reason_phrase(phrase) {
+		let expected = "a reason-phrase of at most 128 bytes";
+		return Err(E::invalid_value(Unexpected::Str(phrase), &expected));
+	}
+	intern(phrase).ok_or_else(|| {
+		E::custom(format_args!("more than {} distinct extension reason phrases", MAX_PHRASES))
+	})
+}
+
+/// Returns a `'static` copy of an extension reason phrase, or `None` if no more phrases are kept.
+///
+/// `StatusCode` only holds `'static` phrases so that it can be `Copy`. Each distinct phrase that
+/// is deserialized is leaked once and shared by every later occurrence, and the number and length
+/// of the phrases are limited, so the memory used stays bounded whatever the input.
+fn intern(phrase: &str) -> Option<&'static str> {
+	static PHRASES: Mutex<Option<HashSet<&'static str>>> = Mutex::new(None);
+
+	let mut phrases = PHRASES.lock().unwrap_or_else(|e| e.into_inner());
+	intern_in(phrases.get_or_insert_with(HashSet::new), phrase)
+}
+
+fn intern_in(phrases: &mut HashSet<&'static str>, phrase: &str) -> Option<&'static str> {
+	if let Some(interned) = phrases.get(phrase) {
+		return Some(interned);
+	}
+	if phrases.len() >= MAX_PHRASES {
+		return None;
+	}
+
+	let interned: &'static str = Box::leak(phrase.to_string().into_boxed_str());
+	phrases.insert(interned);
+	Some(interned)
+}
+
+/// reason-phrase = *( HTAB / SP / VCHAR / obs-text )
+fn is_reason_phrase(phrase: &str) -> bool {
+	phrase.bytes().all(|b| b == b'\t' || b == b' ' || (0x21..=0x7e).contains(&b) || b >= 0x80)
+}
+
+impl Serialize for StatusCodeClass {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_str(self)
+	}
+}
+
+impl<'de> Deserialize<'de> for StatusCodeClass {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<StatusCodeClass, D::Error> {
+		deserializer.deserialize_str(StatusCodeClassVisitor)
+	}
+}
+
+struct StatusCodeClassVisitor;
+
+impl<'de> Visitor<'de> for StatusCodeClassVisitor {
+	type Value = StatusCodeClass;
+
+	fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("one of \"1xx\", \"2xx\", \"3xx\", \"4xx\" or \"5xx\"")
+	}
+
+	fn visit_str<E: de::Error>(self, s: &str) -> Result<StatusCodeClass, E> {
+		s.parse().map_err(|_| E::invalid_value(Unexpected::Str(s), &self))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	extern crate serde_json;
+	extern crate toml;
+
+	use statuscodes::{StatusCode, StatusCodeClass};
+
+	#[derive(Serialize, Deserialize, PartialEq, Debug)]
+	struct Outcome {
+		status: StatusCode,
+		class: StatusCodeClass,
+	}
+
+	#[test]
+	fn statuscode_json_test() {
+		let test_cases = vec![
+			(StatusCode::Ok, "200"),
+			(StatusCode::TooManyRequests, "429"),
+			(StatusCode::from_u16(471), "471"),
+			(StatusCode::from_u16_with_phrase(471, "Too Many Widgets"),
+				r#"{"code":471,"phrase":"Too Many Widgets"}"#),
+		];
+
+		for (status, json) in test_cases {
+			assert_eq!(json, serde_json::to_string(&status).unwrap());
+			assert_eq!(status, serde_json::from_str::<StatusCode>(json).unwrap());
+		}
+	}
+
+	#[test]
+	fn statuscode_json_round_trip_test() {
+		for n in 100..1000 {
+			let status = StatusCode::from_u16(n);
+			let json = serde_json::to_string(&status).unwrap();
+			assert_eq!(status, serde_json::from_str::<StatusCode>(&json).unwrap());
+		}
+	}
+
+	#[test]
+	fn statuscode_json_error_test() {
+		for json in ["0", "42", "1000", "65536", "-1", "\"200\"", "{\"phrase\":\"OK\"}",
+			"{\"code\":471,\"reason\":\"x\"}", "{\"code\":471,\"phrase\":\"x\\r\\nInjected: 1\"}",
+			"{\"code\":471,\"phrase\":\"\\u0000\"}"] {
+			assert!(serde_json::from_str::<StatusCode>(json).is_err(), "{}", json);
+		}
+	}
+
+	#[test]
+	fn statuscode_json_registered_code_ignores_phrase() {
+		let status = serde_json::from_str::<StatusCode>(r#"{"code":404,"phrase":"Nope"}"#);
+		assert_eq!(StatusCode::NotFound, status.unwrap());
+	}
+
+	#[test]
+	fn intern_limits_test() {
+		use std::collections::HashSet;
+
+		use super::{intern_in, MAX_PHRASES, MAX_PHRASE_LENGTH};
+
+		let mut phrases = HashSet::new();
+		let first = intern_in(&mut phrases, "Too Many Widgets").unwrap();
+		assert_eq!("Too Many Widgets", first);
+		assert!(::std::ptr::eq(first, intern_in(&mut phrases, "Too Many Widgets").unwrap()));
+
+		for n in 0..MAX_PHRASES * 2 {
+			intern_in(&mut phrases, &n.to_string());
+		}
+		assert_eq!(MAX_PHRASES, phrases.len());
+		assert_eq!(None, intern_in(&mut phrases, "Too Few Widgets"));
+		assert_eq!(Some("Too Many Widgets"), intern_in(&mut phrases, "Too Many Widgets"));
+
+		let json = format!(r#"{{"code":471,"phrase":"{}"}}"#, "x".repeat(MAX_PHRASE_LENGTH + 1));
+		assert!(serde_json::from_str::<StatusCode>(&json).is_err());
+	}
+
+	#[test]
+	fn statuscodeclass_json_test() {
+		let test_cases = vec![
+			(StatusCodeClass::Informational, "\"1xx\""),
+			(StatusCodeClass::Successful, "\"2xx\""),
+			(StatusCodeClass::Redirection, "\"3xx\""),
+			(StatusCodeClass::ClientError, "\"4xx\""),
+			(StatusCodeClass::ServerError, "\"5xx\""),
+		];
+
+		for (class, json) in test_cases {
+			assert_eq!(json, serde_json::to_string(&class).unwrap());
+			assert_eq!(class, serde_json::from_str::<StatusCodeClass>(json).unwrap());
+		}
+
+		for json in ["\"6xx\"", "\"4XX\"", "4"] {
+			assert!(serde_json::from_str::<StatusCodeClass>(json).is_err(), "{}", json);
+		}
+	}
+
+	#[test]
+	fn toml_test() {
+		let outcome = Outcome {
+			status: StatusCode::PermanentRedirect,
+			class: StatusCodeClass::Redirection,
+		};
+		let text = toml::to_string(&outcome).unwrap();
+		assert_eq!("status = 308\nclass = \"3xx\"\n", text);
+		assert_eq!(outcome, toml::from_str(&text).unwrap());
+
+		let outcome = Outcome {
+			status: StatusCode::from_u16_with_phrase(471, "Too Many Widgets"),
+			class: StatusCodeClass::ClientError,
+		};
+		let text = toml::to_string(&outcome).unwrap();
+		assert_eq!(outcome, toml::from_str(&text).unwrap());
+	}
+}
@@ -0,0 +1,514 @@
+//! Serialization of HTTP/1.1 messages
+//! ([Section 3 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-3)).
+//!
+//! The message head is written as it is given, except for the header fields that delimit the
+//! message body: unless the message already has a Content-Length or Transfer-Encoding header
+//! field, the serializer adds the one that matches the framing it chooses for the body.
+
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Write};
+
+use chunked::ChunkedEncoder;
+use framing::Framing;
+use header::{HeaderMap, HeaderName};
+use message::{Request, Response};
+use method::Method;
+use statuscodes::{InvalidStatusCode, StatusCode};
+use token;
+use typed_headers::{ContentLength, TransferEncoding};
+
+/// Writes a request to `w`, with framing chosen for its body, and returns that framing.
+///
+/// A body is sent with Content-Length unless the request has a Transfer-Encoding header field
+/// with chunked as the final coding, in which case it is sent as a single chunk.
+pub fn write_request<W: Write, B: AsRef<[u8]>>(w: &mut W, request: &Request<B>)
+	-> Result<Framing, SerializeError>
+{
+	let body = request.body.as_ref();
+	let framing = write_request_head(w, request, Some(body.len() as u64))?;
+	write_body(w, framing, body)?;
+	Ok(framing)
+}
+
+/// Writes the request-line and header fields of a request to `w`, and returns the framing of the
+/// body that follows them.
+///
+/// `body_len` is the length of the body, or `None` if it is not known in advance, in which case
+/// the body is chunked.
+pub fn write_request_head<W: Write, B>(w: &mut W, request: &Request<B>, body_len: Option<u64>)
+	-> Result<Framing, SerializeError>
+{
+	let framing = request_framing(request, body_len)?;
+	write!(w, "{} {} {}\r\n", request.method, request.target, request.version)?;
+	write_header_fields(w, &request.headers, framing)?;
+	Ok(framing)
+}
+
+/// Writes a response to `w`, with framing chosen for its body, and returns that framing.
+///
+/// `method` is the method of the request being responded to. The body is omitted for a response
+/// to HEAD, while the header fields still describe it, and for 1xx (Informational), 204 (No
+/// Content) and 304 (Not Modified) responses, which never have a body
+/// ([Section 3.3 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-3.3)).
+///
+/// The version of the response is the protocol its framing is chosen for; a server responding to
+/// an HTTP/1.0 request should set it to HTTP/1.0, so that a body is never chunked.
+///
+/// A server closes the connection after a response whose framing is `CloseDelimited`.
+pub fn write_response<W: Write, B: AsRef<[u8]>>(w: &mut W, response: &Response<B>, method: &Method)
+	-> Result<Framing, SerializeError>
+{
+	let body = response.body.as_ref();
+	let framing = write_response_head(w, response, method, Some(body.len() as u64))?;
+	write_body(w, framing, body)?;
+	Ok(framing)
+}
+
+/// Writes the status-line and header fields of a response to `w`, and returns the framing of the
+/// body that follows them.
+///
+/// `body_len` is the length of the body, or `None` if it is not known in advance, in which case
+/// the body is chunked, or delimited by closing the connection for HTTP/1.0.
+pub fn write_response_head<W: Write, B>(w: &mut W, response: &Response<B>, method: &Method,
+	body_len: Option<u64>) -> Result<Framing, SerializeError>
+{
+	let status = response.status;
+	// The status-code element is a three-digit integer code.
+	StatusCode::try_from_u16(status.to_u16())?;
+	// reason-phrase = *( HTAB / SP / VCHAR / obs-text ), as an extension code can be created
+	// with any phrase.
+	if !status.reason_phrase().bytes().all(token::is_field_value_byte) {
+		return Err(SerializeError::InvalidReasonPhrase);
+	}
+	let framing = response_framing(response, method, body_len)?;
+	write!(w, "{} {:03} {}\r\n", response.version, status.to_u16(), status.reason_phrase())?;
+
+	// The header fields of a response to HEAD describe the body that GET would have sent.
+	let described = match (framing, method, body_len) {
+		(Framing::Empty, &Method::Head, Some(len)) if status.permits_body() => Framing::Length(len),
+		_ => framing,
+	};
+	write_header_fields(w, &response.headers, described)?;
+	Ok(framing)
+}
+
+fn request_framing<B>(request: &Request<B>, body_len: Option<u64>)
+	-> Result<Framing, SerializeError>
+{
+	if let Some(framing) = declared_framing(&request.headers, body_len)? {
+		if framing == Framing::CloseDelimited {
+			// A request cannot be delimited by closing the connection, as the response could not
+			// be received.
+			return Err(SerializeError::InvalidFraming);
+		}
+		if framing == Framing::Chunked && !request.version.supports_chunked() {
+			return Err(SerializeError::ChunkedNotSupported);
+		}
+		return Ok(framing);
+	}
+
+	match body_len {
+		// A user agent SHOULD NOT send a Content-Length header field when the request message
+		// does not contain a payload body and the method semantics do not anticipate such a body.
+		Some(0) => match request.method {
+			Method::Post | Method::Put | Method::Patch | Method::Extension(_) => Ok(Framing::Length(0)),
+			_ => Ok(Framing::Empty),
+		},
+		Some(len) => Ok(Framing::Length(len)),
+		None if request.version.supports_chunked() => Ok(Framing::Chunked),
+		None => Err(SerializeError::ChunkedNotSupported),
+	}
+}
+
+fn response_framing<B>(response: &Response<B>, method: &Method, body_len: Option<u64>)
+	-> Result<Framing, SerializeError>
+{
+	let status = response.status;
+	if status.is_informational() || status == StatusCode::NoContent {
+		// A server MUST NOT send a Content-Length or Transfer-Encoding header field in any
+		// response with a status code of 1xx (Informational) or 204 (No Content).
+		if response.headers.contains_key(HeaderName::CONTENT_LENGTH) ||
+			response.headers.contains_key(HeaderName::TRANSFER_ENCODING) {
+			return Err(SerializeError::InvalidFraming);
+		}
+		return Ok(Framing::Empty);
+	}
+
+	if status == StatusCode::ResetContent {
+		// A server MUST NOT generate a payload in a 205 (Reset Content) response. It indicates a
+		// zero-length body with Content-Length: 0, or with only the last chunk if the response
+		// is chunked.
+		if body_len != Some(0) {
+			return Err(SerializeError::BodyNotAllowed(status));
+		}
+		return match declared_framing(&response.headers, body_len)? {
+			Some(Framing::Chunked) if response.version.supports_chunked() => Ok(Framing::Chunked),
+			Some(Framing::Length(_)) | None => Ok(Framing::Length(0)),
+			Some(_) => Err(SerializeError::InvalidFraming),
+		};
+	}
+
+	if *method == Method::Head || status == StatusCode::NotModified {
+		// The header fields describe the body that a GET would have sent, not the empty one that
+		// is sent, so only their consistency is checked.
+		declared_framing(&response.headers, None)?;
+		return Ok(Framing::Empty);
+	}
+	let declared = declared_framing(&response.headers, body_len)?;
+
+	let chunked = response.version.supports_chunked();
+	match (declared, body_len) {
+		(Some(Framing::Chunked), _) if !chunked => Err(SerializeError::ChunkedNotSupported),
+		(Some(framing), _) => Ok(framing),
+		(None, Some(len)) => Ok(Framing::Length(len)),
+		(None, None) if chunked => Ok(Framing::Chunked),
+		(None, None) => Ok(Framing::CloseDelimited),
+	}
+}
+
+/// Returns the framing declared by the Content-Length and Transfer-Encoding header fields of a
+/// message, checking that it agrees with the length of the body.
+fn declared_framing(headers: &HeaderMap, body_len: Option<u64>)
+	-> Result<Option<Framing>, SerializeError>
+{
+	let content_length = headers.get_typed::<ContentLength>()
+		.map_err(|_| SerializeError::InvalidFraming)?;
+	let transfer_encoding = headers.get_typed::<TransferEncoding>()
+		.map_err(|_| SerializeError::InvalidFraming)?;
+
+	match (content_length, transfer_encoding) {
+		// A sender MUST NOT send a Content-Length header field in any message that contains a
+		// Transfer-Encoding header field.
+		(Some(_), Some(_)) => Err(SerializeError::InvalidFraming),
+		(Some(ContentLength(len)), None) => match body_len {
+			Some(actual) if actual != len => Err(SerializeError::ContentLengthMismatch),
+			_ => Ok(Some(Framing::Length(len))),
+		},
+		(None, Some(ref codings)) if codings.is_chunked() => Ok(Some(Framing::Chunked)),
+		(None, Some(_)) => Ok(Some(Framing::CloseDelimited)),
+		(None, None) => Ok(None),
+	}
+}
+
+fn write_header_fields<W: Write>(w: &mut W, headers: &HeaderMap, framing: Framing)
+	-> io::Result<()>
+{
+	for (name, value) in headers {
+		write_header_field(w, name, value.as_bytes())?;
+	}
+
+	if !headers.contains_key(HeaderName::CONTENT_LENGTH) &&
+		!headers.contains_key(HeaderName::TRANSFER_ENCODING) {
+		match framing {
+			Framing::Length(len) => {
+				write_header_field(w, &HeaderName::CONTENT_LENGTH, len.to_string().as_bytes())?
+			}
+			Framing::Chunked => write_header_field(w, &HeaderName::TRANSFER_ENCODING, b"chunked")?,
+			Framing::Empty | Framing::CloseDelimited => {}
+		}
+	}
+
+	w.write_all(b"\r\n")
+}
+
+fn write_header_field<W: Write>(w: &mut W, name: &HeaderName, value: &[u8]) -> io::Result<()> {
+	w.write_all(name.as_str().as_bytes())?;
+	w.write_all(b": ")?;
+	w.write_all(value)?;
+	w.write_all(b"\r\n")
+}
+
+/// Writes a message body to `w` with the framing returned by `write_request_head` or
+/// `write_response_head`.
+pub fn write_body<W: Write>(w: &mut W, framing: Framing, body: &[u8]) -> Result<(), SerializeError> {
+	match framing {
+		Framing::Empty => {}
+		Framing::Length(_) | Framing::CloseDelimited => w.write_all(body)?,
+		Framing::Chunked => {
+			let mut encoder = ChunkedEncoder::new(w);
+			encoder.write_all(body)?;
+			encoder.finish()?;
+		}
+	}
+	Ok(())
+}
+
+/// An error returned when a message cannot be serialized.
+#[derive(Debug)]
+pub enum SerializeError {
+	/// The message has a body, but a server MUST NOT generate a payload with its status code.
+	BodyNotAllowed(StatusCode),
+	/// The Content-Length header field does not match the length of the body.
+	ContentLengthMismatch,
+	/// The Content-Length and Transfer-Encoding header fields do not describe a valid framing,
+	/// e.g. both are present, or one is present in a response that must not have them.
+	InvalidFraming,
+	/// The body would be chunked, but the protocol version does not support chunked.
+	ChunkedNotSupported,
+	/// The status code is not a three-digit integer, so it cannot be sent in a status-line.
+	InvalidStatusCode(InvalidStatusCode),
+	/// The reason phrase contains a control character, such as CR or LF.
+	InvalidReasonPhrase,
+	/// Writing the message failed.
+	Io(io::Error),
+}
+
+impl fmt::Display for SerializeError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			SerializeError::BodyNotAllowed(status) => write!(f, "a {} response must not have a payload", status),
+			SerializeError::ContentLengthMismatch => f.write_str("Content-Length does not match body"),
+			SerializeError::InvalidFraming => f.write_str("invalid message body framing"),
+			SerializeError::ChunkedNotSupported => f.write_str("chunked not supported by version"),
+			SerializeError::InvalidStatusCode(ref err) => err.fmt(f),
+			SerializeError::InvalidReasonPhrase => f.write_str("invalid reason-phrase"),
+			SerializeError::Io(ref err) => err.fmt(f),
+		}
+	}
+}
+
+impl Error for SerializeError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match *self {
+			SerializeError::InvalidStatusCode(ref err) => Some(err),
+			SerializeError::Io(ref err) => Some(err),
+			_ => None,
+		}
+	}
+}
+
+impl From<InvalidStatusCode> for SerializeError {
+	fn from(err: InvalidStatusCode) -> SerializeError {
+		SerializeError::InvalidStatusCode(err)
+	}
+}
+
+impl From<io::Error> for SerializeError {
+	fn from(err: io::Error) -> SerializeError {
+		SerializeError::Io(err)
+	}
+}
+
+#[cfg(test)]
+mod test {
+
+	#[test]
+	fn write_response_test() {
+		use header::{HeaderName, HeaderValue};
+		use message::Response;
+		use method::Method;
+		use statuscodes::StatusCode;
+		use super::write_response;
+		use version::HttpVersion;
+
+		let test_cases = vec![
+			(Response::builder().body(b"hi".to_vec()), Method::Get,
+				"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nhi"),
+			(Response::builder().body(b"hi".to_vec()), Method::Head,
+				"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n"),
+			(Response::builder().status(StatusCode::NoContent).body(b"x".to_vec()), Method::Delete,
+				"HTTP/1.1 204 No Content\r\n\r\n"),
+			(Response::builder().status(StatusCode::NotModified).body(b"x".to_vec()), Method::Get,
+				"HTTP/1.1 304 Not Modified\r\n\r\n"),
+			(Response::builder()
+				.header(HeaderName::CONTENT_LENGTH, HeaderValue::from(40))
+				.body(vec![]), Method::Head,
+				"HTTP/1.1 200 OK\r\nContent-Length: 40\r\n\r\n"),
+			(Response::builder()
+				.status(StatusCode::NotModified)
+				.header(HeaderName::CONTENT_LENGTH, HeaderValue::from(40))
+				.body(vec![]), Method::Get,
+				"HTTP/1.1 304 Not Modified\r\nContent-Length: 40\r\n\r\n"),
+			(Response::builder().status(StatusCode::Continue).body(vec![]), Method::Put,
+				"HTTP/1.1 100 Continue\r\n\r\n"),
+			(Response::builder().status(StatusCode::ResetContent).body(vec![]), Method::Post,
+				"HTTP/1.1 205 Reset Content\r\nContent-Length: 0\r\n\r\n"),
+			(Response::builder()
+				.status(StatusCode::ResetContent)
+				.header(HeaderName::TRANSFER_ENCODING, HeaderValue::from_static("chunked"))
+				.body(vec![]), Method::Post,
+				"HTTP/1.1 205 Reset Content\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\n"),
+			(Response::builder().status(StatusCode::from_u16(471)).body(vec![]), Method::Get,
+				"HTTP/1.1 471 \r\nContent-Length: 0\r\n\r\n"),
+			(Response::builder()
+				.version(HttpVersion::Http10)
+				.header(HeaderName::SERVER, HeaderValue::from_static("fanoracle"))
+				.header(HeaderName::CONTENT_LENGTH, HeaderValue::from(3))
+				.body(b"abc".to_vec()), Method::Get,
+				"HTTP/1.0 200 OK\r\nServer: fanoracle\r\nContent-Length: 3\r\n\r\nabc"),
+			(Response::builder()
+				.header(HeaderName::TRANSFER_ENCODING, HeaderValue::from_static("chunked"))
+				.body(b"abc".to_vec()), Method::Get,
+				"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n0\r\n\r\n"),
+		];
+
+		for (response, method, expect) in test_cases {
+			let mut buf = Vec::new();
+			write_response(&mut buf, &response, &method).unwrap();
+			assert_eq!(expect, String::from_utf8(buf).unwrap());
+		}
+	}
+
+	#[test]
+	fn write_response_head_test() {
+		use message::Response;
+		use method::Method;
+		use framing::Framing;
+		use super::write_response_head;
+		use version::HttpVersion;
+
+		let test_cases = vec![
+			(HttpVersion::Http11, Method::Get, Some(5), Framing::Length(5),
+				"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\n"),
+			(HttpVersion::Http11, Method::Get, None, Framing::Chunked,
+				"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n"),
+			(HttpVersion::Http10, Method::Get, None, Framing::CloseDelimited, "HTTP/1.0 200 OK\r\n\r\n"),
+			(HttpVersion::Http11, Method::Head, None, Framing::Empty, "HTTP/1.1 200 OK\r\n\r\n"),
+		];
+
+		for (version, method, body_len, framing, expect) in test_cases {
+			let response = Response::builder().version(version).body(());
+			let mut buf = Vec::new();
+			assert_eq!(framing, write_response_head(&mut buf, &response, &method, body_len).unwrap());
+			assert_eq!(expect, String::from_utf8(buf).unwrap());
+		}
+	}
+
+	#[test]
+	fn write_response_error_test() {
+		use header::{HeaderName, HeaderValue};
+		use message::Response;
+		use method::Method;
+		use statuscodes::StatusCode;
+		use super::{write_response, SerializeError};
+		use version::HttpVersion;
+
+		let response = Response::builder().status(StatusCode::ResetContent).body(b"x".to_vec());
+		match write_response(&mut Vec::new(), &response, &Method::Post) {
+			Err(SerializeError::BodyNotAllowed(StatusCode::ResetContent)) => {}
+			other => panic!("{:?}", other),
+		}
+
+		for n in [0, 99, 1000, 1234] {
+			let response = Response::builder().status(StatusCode::from_u16(n)).body(vec![]);
+			let mut buf = Vec::new();
+			match write_response(&mut buf, &response, &Method::Get) {
+				Err(SerializeError::InvalidStatusCode(ref err)) if err.code() == n => {}
+				other => panic!("{}: {:?}", n, other),
+			}
+			assert!(buf.is_empty(), "{}", n);
+		}
+
+		for phrase in ["Widgets\r\nInjected: 1", "Widgets\n", "\0"] {
+			let status = StatusCode::Extension(471, phrase);
+			let response = Response::builder().status(status).body(vec![]);
+			let mut buf = Vec::new();
+			match write_response(&mut buf, &response, &Method::Get) {
+				Err(SerializeError::InvalidReasonPhrase) => {}
+				other => panic!("{:?}: {:?}", phrase, other),
+			}
+			assert!(buf.is_empty(), "{:?}", phrase);
+		}
+
+		let response = Response::builder()
+			.header(HeaderName::CONTENT_LENGTH, HeaderValue::from(40))
+			.header(HeaderName::TRANSFER_ENCODING, HeaderValue::from_static("chunked"))
+			.body(vec![]);
+		let err = write_response(&mut Vec::new(), &response, &Method::Head).unwrap_err();
+		assert_eq!("invalid message body framing", err.to_string());
+
+		let test_cases = vec![
+			(Response::builder()
+				.header(HeaderName::CONTENT_LENGTH, HeaderValue::from(4))
+				.body(b"abc".to_vec()), "Content-Length does not match body"),
+			(Response::builder()
+				.header(HeaderName::CONTENT_LENGTH, HeaderValue::from(3))
+				.header(HeaderName::TRANSFER_ENCODING, HeaderValue::from_static("chunked"))
+				.body(b"abc".to_vec()), "invalid message body framing"),
+			(Response::builder()
+				.status(StatusCode::NoContent)
+				.header(HeaderName::CONTENT_LENGTH, HeaderValue::from(0))
+				.body(vec![]), "invalid message body framing"),
+			(Response::builder()
+				.version(HttpVersion::Http10)
+				.header(HeaderName::TRANSFER_ENCODING, HeaderValue::from_static("chunked"))
+				.body(vec![]), "chunked not supported by version"),
+		];
+
+		for (response, expect) in test_cases {
+			let err = write_response(&mut Vec::new(), &response, &Method::Get).unwrap_err();
+			assert_eq!(expect, err.to_string());
+		}
+	}
+
+	#[test]
+	fn write_request_test() {
+		use header::{HeaderName, HeaderValue};
+		use message::Request;
+		use method::Method;
+		use framing::Framing;
+		use super::{write_request, write_request_head, SerializeError};
+		use uri::RequestTarget;
+		use version::HttpVersion;
+
+		let test_cases = vec![
+			(Request::builder()
+				.target(RequestTarget::parse(&Method::Get, b"/a?b").unwrap())
+				.header(HeaderName::HOST, HeaderValue::from_static("example.com"))
+				.body(vec![]),
+				"GET /a?b HTTP/1.1\r\nHost: example.com\r\n\r\n"),
+			(Request::builder().method(Method::Post).body(vec![]),
+				"POST / HTTP/1.1\r\nContent-Length: 0\r\n\r\n"),
+			(Request::builder().method(Method::Put).body(b"abc".to_vec()),
+				"PUT / HTTP/1.1\r\nContent-Length: 3\r\n\r\nabc"),
+			(Request::builder().method("PROPFIND".parse().unwrap()).body(vec![]),
+				"PROPFIND / HTTP/1.1\r\nContent-Length: 0\r\n\r\n"),
+		];
+		// A method is always a token, so it cannot end the request-line early.
+		assert!("GET / HTTP/1.1\r\nInjected:".parse::<Method>().is_err());
+
+		for (request, expect) in test_cases {
+			let mut buf = Vec::new();
+			write_request(&mut buf, &request).unwrap();
+			assert_eq!(expect, String::from_utf8(buf).unwrap());
+		}
+
+		let request = Request::builder().method(Method::Post).body(());
+		let mut buf = Vec::new();
+		assert_eq!(Framing::Chunked, write_request_head(&mut buf, &request, None).unwrap());
+		assert_eq!("POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n", String::from_utf8(buf).unwrap());
+
+		let request = Request::builder().version(HttpVersion::Http10).body(());
+		match write_request_head(&mut Vec::new(), &request, None) {
+			Err(SerializeError::ChunkedNotSupported) => {}
+			other => panic!("{:?}", other),
+		}
+	}
+
+	#[test]
+	fn write_parse_round_trip_test() {
+		use header::{HeaderName, HeaderValue};
+		use message::Response;
+		use method::Method;
+		use parser::{Parser, Status};
+		use statuscodes::StatusCode;
+		use super::write_response;
+
+		let response = Response::builder()
+			.status(StatusCode::Created)
+			.header(HeaderName::LOCATION, HeaderValue::from_static("/widgets/7"))
+			.body(b"{}".to_vec());
+		let mut buf = Vec::new();
+		write_response(&mut buf, &response, &Method::Post).unwrap();
+
+		match Parser::new().parse_response(&buf) {
+			Ok(Status::Complete(n, head)) => {
+				assert_eq!(response.status, head.status);
+				assert_eq!(response.headers.get(HeaderName::LOCATION), head.header_map().get("location"));
+				assert_eq!(b"{}", &buf[n..]);
+			}
+			other => panic!("{:?}", other),
+		}
+	}
+}
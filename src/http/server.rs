@@ -0,0 +1,979 @@
+//! A blocking HTTP/1.1 server.
+
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chunked::{ChunkedDecoder, ChunkedError};
+use date::HttpDate;
+use error::HttpError;
+use framing::{self, Framing};
+use header::HeaderName;
+use message::{Request, Response};
+use method::Method;
+use parser::{Parser, Status, DEFAULT_MAX_HEADERS, DEFAULT_MAX_LINE_LENGTH};
+use serializer::{self, SerializeError};
+use statuscodes::StatusCode;
+use typed_headers::{Connection, Date, Expect, Host, UpgradeProtocol};
+use upgrade::{self, Upgraded};
+use uri::RequestTarget;
+use version::HttpVersion;
+
+/// The default limit on the size of a request body, in bytes.
+pub const DEFAULT_MAX_BODY_SIZE: u64 = 8 * 1024 * 1024;
+
+/// The default number of worker threads.
+pub const DEFAULT_THREADS: usize = 4;
+
+/// The default limit on how long writing to a connection may block.
+pub const DEFAULT_WRITE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The default time to wait for the next request on a persistent connection.
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The default time allowed for receiving a request, once its first byte has arrived.
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The default number of requests answered on a connection before it is closed.
+pub const DEFAULT_MAX_REQUESTS: usize = 100;
+
+/// How long, and for how many bytes, to keep reading from a connection that is being closed.
+const LINGER_TIMEOUT: Duration = Duration::from_secs(2);
+const LINGER_LIMIT: u64 = 64 * 1024;
+
+/// A Handler produces the response to a request.
+///
+/// The handler is shared by the worker threads of a server, so it is called concurrently. Any
+/// closure taking a `Request` and returning a `Response` is a handler.
+pub trait Handler: Send + Sync + 'static {
+	/// Handles a request, with its body read into memory.
+	///
+	/// A handler that fails can convert an `HttpError` into the response. If the handler panics,
+	/// the server responds with 500 (Internal Server Error).
+	fn handle(&self, request: Request) -> Response;
+
+	/// Decides, from the request-line and header fields alone, whether to receive the body of a
+	/// request whose client waits for 100 (Continue) before sending it
+	/// ([Section 5.1.1 of \[RFC7231\]](http://tools.ietf.org/html/rfc7231#section-5.1.1)).
+	///
+	/// Returning `Ok` sends 100 (Continue), then reads the body and calls `handle`. Returning a
+	/// final response, e.g. 413 (Payload Too Large) or 417 (Expectation Failed), sends it instead
+	/// and closes the connection without reading the body. The body of `request` is empty. The
+	/// default, which closures use, accepts every request.
+	fn expect_continue(&self, request: &Request) -> Result<(), Response> {
+		let _ = request;
+		Ok(())
+	}
+
+	/// Returns the protocols a client must ask to upgrade to, in order of preference; requests
+	/// that ask for none of them are answered with 426 (Upgrade Required) without calling
+	/// `handle`. The default requires none.
+	fn required_protocols(&self) -> &[UpgradeProtocol] {
+		&[]
+	}
+
+	/// Takes over a connection after `handle` answered `request` with 101 (Switching Protocols),
+	/// which the server sends only if the response switches to protocols the request asked for
+	/// (see `upgrade::switching_protocols`); otherwise the client gets 500 (Internal Server Error).
+	///
+	/// The connection is closed when this returns. The default closes it right away.
+	fn upgrade(&self, request: Request, stream: Upgraded) {
+		let _ = (request, stream);
+	}
+}
+
+impl<F> Handler for F where F: Fn(Request) -> Response + Send + Sync + 'static {
+	fn handle(&self, request: Request) -> Response {
+		self(request)
+	}
+}
+
+/// A Server accepts connections on a `TcpListener` and answers the requests it receives on them
+/// with a `Handler`.
+///
+/// Connections are served by a fixed pool of worker threads; while all of them are busy, new
+/// connections wait to be accepted. Requests that are malformed or exceed the limits are answered
+/// with the appropriate 4xx (Client Error) status without calling the handler.
+///
+/// Connections are persistent unless the client asks otherwise: an HTTP/1.1 connection stays open
+/// until a request carries the "close" connection option, and an HTTP/1.0 connection only when a
+/// request carries "keep-alive"
+/// ([Section 6.3 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-6.3)). Pipelined
+/// requests are answered in the order they were received. A connection is closed after it has
+/// been idle for the idle timeout, after the maximum number of requests, and after a request that
+/// is not received within the request timeout, which is answered with 408 (Request Timeout).
+///
+/// ```no_run
+/// use http::{ContentLength, Response, Server, StatusCode};
+///
+/// let server = Server::bind("127.0.0.1:8080").unwrap().threads(8);
+/// server.serve(|request: http::Request| {
+///     let body = format!("you asked for {}\n", request.target).into_bytes();
+///     Response::builder()
+///         .status(StatusCode::Ok)
+///         .typed_header(ContentLength(body.len() as u64))
+///         .body(body)
+/// }).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Server {
+	listener: TcpListener,
+	threads: usize,
+	limits: Limits,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Limits {
+	max_line_length: usize,
+	max_headers: usize,
+	max_body_size: u64,
+	write_timeout: Duration,
+	idle_timeout: Duration,
+	request_timeout: Duration,
+	max_requests: usize,
+}
+
+impl Server {
+	/// Creates a server listening on the given address.
+	pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Server> {
+		TcpListener::bind(addr).map(Server::from_listener)
+	}
+
+	/// Creates a server accepting connections from a listener, with the default limits.
+	pub fn from_listener(listener: TcpListener) -> Server {
+		Server {
+			listener,
+			threads: DEFAULT_THREADS,
+			limits: Limits {
+				max_line_length: DEFAULT_MAX_LINE_LENGTH,
+				max_headers: DEFAULT_MAX_HEADERS,
+				max_body_size: DEFAULT_MAX_BODY_SIZE,
+				write_timeout: DEFAULT_WRITE_TIMEOUT,
+				idle_timeout: DEFAULT_IDLE_TIMEOUT,
+				request_timeout: DEFAULT_REQUEST_TIMEOUT,
+				max_requests: DEFAULT_MAX_REQUESTS,
+			},
+		}
+	}
+
+	/// Returns the address the server is listening on.
+	pub fn local_addr(&self) -> io::Result<SocketAddr> {
+		self.listener.local_addr()
+	}
+
+	/// Sets the number of worker threads, which is the number of connections served at once.
+	pub fn threads(mut self, threads: usize) -> Server {
+		self.threads = threads.max(1);
+		self
+	}
+
+	/// Sets the limit on the length of the request-line and of each header field line.
+	pub fn max_line_length(mut self, max: usize) -> Server {
+		self.limits.max_line_length = max;
+		self
+	}
+
+	/// Sets the limit on the number of header fields in a request.
+	pub fn max_headers(mut self, max: usize) -> Server {
+		self.limits.max_headers = max;
+		self
+	}
+
+	/// Sets the limit on the size of a request body; larger requests are answered with 413
+	/// (Payload Too Large).
+	pub fn max_body_size(mut self, max: u64) -> Server {
+		self.limits.max_body_size = max;
+		self
+	}
+
+	/// Sets how long a connection may wait for its next request before it is closed.
+	pub fn idle_timeout(mut self, timeout: Duration) -> Server {
+		self.limits.idle_timeout = timeout;
+		self
+	}
+
+	/// Sets how long receiving a request may take, from its first byte to the end of its body;
+	/// slower requests are answered with 408 (Request Timeout).
+	pub fn request_timeout(mut self, timeout: Duration) -> Server {
+		self.limits.request_timeout = timeout;
+		self
+	}
+
+	/// Sets the number of requests answered on a connection before it is closed.
+	pub fn max_requests(mut self, max: usize) -> Server {
+		self.limits.max_requests = max.max(1);
+		self
+	}
+
+	/// Sets the limit on how long writing a response may block, e.g. because the client does not
+	/// read it; the connection is closed when it is exceeded, so that it does not hold a worker.
+	pub fn write_timeout(mut self, timeout: Duration) -> Server {
+		// A zero timeout is rejected by the socket.
+		self.limits.write_timeout = timeout.max(Duration::from_millis(1));
+		self
+	}
+
+	/// Serves connections with `handler` until accepting a connection fails, or forever.
+	pub fn serve<H: Handler>(self, handler: H) -> io::Result<()> {
+		let handler = Arc::new(handler);
+		let (sender, receiver) = mpsc::sync_channel::<TcpStream>(self.threads);
+		let receiver = Arc::new(Mutex::new(receiver));
+
+		for i in 0..self.threads {
+			let receiver = receiver.clone();
+			let handler = handler.clone();
+			let limits = self.limits;
+			thread::Builder::new().name(format!("http-worker-{}", i)).spawn(move || loop {
+				let stream = match receiver.lock().unwrap().recv() {
+					Ok(stream) => stream,
+					Err(_) => return,
+				};
+				serve_connection(stream, &*handler, limits);
+			})?;
+		}
+
+		// A failure to accept one connection, e.g. because it was reset by the peer or the process
+		// is out of file descriptors, does not stop the server.
+		for stream in self.listener.incoming().flatten() {
+			if sender.send(stream).is_err() {
+				break;
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Answers the requests received on a connection, in order, until either side closes it.
+fn serve_connection<H: Handler>(stream: TcpStream, handler: &H, limits: Limits) {
+	let mut reader = match stream.try_clone() {
+		Ok(reader) => BufReader::new(DeadlineStream { stream: reader, deadline: None }),
+		Err(_) => return,
+	};
+	if stream.set_write_timeout(Some(limits.write_timeout)).is_err() {
+		return;
+	}
+	let mut writer = BufWriter::new(stream);
+
+	for served in 1.. {
+		// Wait for the first byte of the next request; a pipelined request is already buffered.
+		reader.get_mut().expire_in(limits.idle_timeout);
+		match reader.fill_buf() {
+			Ok(buf) if !buf.is_empty() => {}
+			_ => return,
+		}
+
+		reader.get_mut().expire_in(limits.request_timeout);
+		let (method, mut response, mut keep_alive, mut upgrade) = match read_request(&mut reader,
+			&mut writer, handler, limits)
+		{
+			Ok(Ok(request)) => {
+				let method = request.method.clone();
+				let version = request.version;
+				let keep_alive = is_persistent(&request) && served < limits.max_requests;
+				let (mut response, upgrade) = respond(handler, request);
+				response.version = version;
+				(method, response, keep_alive, upgrade)
+			}
+			// The rest of an invalid or rejected request cannot be told apart from the next one.
+			Ok(Err(response)) => (Method::Get, response, false, None),
+			Err(ref err) if is_timeout(err) => {
+				(Method::Get, Response::from(HttpError::request_timeout()), false, None)
+			}
+			Err(_) => return,
+		};
+
+		if let Ok(Some(connection)) = response.headers.get_typed::<Connection>() {
+			keep_alive &= !connection.contains("close");
+		}
+		finish_response(&mut response, keep_alive);
+
+		let framing = match serializer::write_response(&mut writer, &response, &method) {
+			Ok(framing) => framing,
+			Err(SerializeError::Io(_)) => return,
+			// Nothing has been written yet when the handler's response cannot be framed.
+			Err(_) => {
+				let version = response.version;
+				response = Response::from(HttpError::internal_server_error());
+				response.version = version;
+				keep_alive = false;
+				upgrade = None;
+				finish_response(&mut response, keep_alive);
+				match serializer::write_response(&mut writer, &response, &method) {
+					Ok(framing) => framing,
+					Err(_) => return,
+				}
+			}
+		};
+		if writer.flush().is_err() {
+			return;
+		}
+		if let Some(request) = upgrade {
+			return switch_protocols(reader, writer, handler, request);
+		}
+		if !keep_alive || framing == Framing::CloseDelimited {
+			break;
+		}
+	}
+
+	linger_close(reader);
+}
+
+/// Closes a connection once the client has read everything sent on it.
+fn linger_close(mut reader: BufReader<DeadlineStream>) {
+	// Closing a socket with unread bytes resets the connection, which can discard what was sent
+	// before the client reads it, so read what the client still sends for a while.
+	let _ = reader.get_ref().stream.shutdown(Shutdown::Write);
+	reader.get_mut().expire_in(LINGER_TIMEOUT);
+	let _ = io::copy(&mut reader.take(LINGER_LIMIT), &mut io::sink());
+}
+
+/// Returns whether the client of a request wants the connection to persist after the response.
+fn is_persistent(request: &Request) -> bool {
+	match request.headers.get_typed::<Connection>() {
+		Ok(Some(ref connection)) if connection.contains("close") => false,
+		Ok(Some(ref connection)) if connection.contains("keep-alive") => true,
+		_ => request.version == HttpVersion::Http11,
+	}
+}
+
+/// Adds the Date header field, and the connection option telling the client whether the
+/// connection persists, or that it is upgraded.
+fn finish_response(response: &mut Response, keep_alive: bool) {
+	// An origin server with a clock MUST send a Date header field in all 2xx, 3xx and 4xx
+	// responses, and MAY send it in the others.
+	if !response.headers.contains_key(HeaderName::DATE) {
+		response.headers.set_typed(Date(HttpDate::now()));
+	}
+	if response.status == StatusCode::SwitchingProtocols {
+		response.headers.set_typed(Connection::upgrade());
+	} else if !keep_alive {
+		response.headers.set_typed(Connection::close());
+	} else if response.version == HttpVersion::Http10 {
+		response.headers.set_typed(Connection::keep_alive());
+	}
+}
+
+/// Returns whether a read failed because its timeout elapsed.
+fn is_timeout(err: &io::Error) -> bool {
+	err.kind() == io::ErrorKind::WouldBlock || err.kind() == io::ErrorKind::TimedOut
+}
+
+/// A DeadlineStream fails reads with `TimedOut` once its deadline has passed, however many reads
+/// it takes to get there.
+#[derive(Debug)]
+struct DeadlineStream {
+	stream: TcpStream,
+	deadline: Option<Instant>,
+}
+
+impl DeadlineStream {
+	fn expire_in(&mut self, timeout: Duration) {
+		self.deadline = Some(Instant::now() + timeout);
+	}
+}
+
+impl Read for DeadlineStream {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		if let Some(deadline) = self.deadline {
+			let now = Instant::now();
+			if now >= deadline {
+				return Err(io::ErrorKind::TimedOut.into());
+			}
+			self.stream.set_read_timeout(Some(deadline - now))?;
+		}
+		self.stream.read(buf)
+	}
+}
+
+/// Calls the handler, turning a panic into 500 (Internal Server Error), unless the request does
+/// not ask for a protocol the handler requires.
+///
+/// Returns the head of the request along with a 101 (Switching Protocols) response, for handing
+/// the connection over to the handler.
+fn respond<H: Handler>(handler: &H, request: Request) -> (Response, Option<Request>) {
+	let required = handler.required_protocols();
+	if !required.is_empty() && upgrade::offered_protocol(&request, required).is_none() {
+		return (upgrade::upgrade_required(required), None);
+	}
+
+	let head = if request.headers.contains_key(HeaderName::UPGRADE) {
+		Some(Request {
+			method: request.method.clone(),
+			target: request.target.clone(),
+			version: request.version,
+			headers: request.headers.clone(),
+			body: Vec::new(),
+		})
+	} else {
+		None
+	};
+	let response = panic::catch_unwind(AssertUnwindSafe(|| handler.handle(request)))
+		.unwrap_or_else(|_| Response::from(HttpError::internal_server_error()));
+	if response.status != StatusCode::SwitchingProtocols {
+		return (response, None);
+	}
+
+	match head {
+		Some(head) if upgrade::is_valid_switch(&head, &response) => (response, Some(head)),
+		_ => (Response::from(HttpError::internal_server_error()), None),
+	}
+}
+
+/// Hands a connection over to the handler after a 101 (Switching Protocols) response, with the
+/// bytes that the client sent after its request.
+fn switch_protocols<H: Handler>(reader: BufReader<DeadlineStream>, writer: BufWriter<TcpStream>,
+	handler: &H, request: Request)
+{
+	let buffered = reader.buffer().to_vec();
+	let stream = match writer.into_inner() {
+		Ok(stream) => stream,
+		Err(_) => return,
+	};
+	// The read timeout of the stream is shared with the reader's copy of it.
+	if stream.set_read_timeout(None).is_err() {
+		return;
+	}
+	let upgraded = Upgraded::new(stream, buffered);
+	let _ = panic::catch_unwind(AssertUnwindSafe(|| handler.upgrade(request, upgraded)));
+	linger_close(reader);
+}
+
+/// Reads a request from the connection, asking the handler whether to receive its body if the
+/// client expects 100 (Continue).
+///
+/// Returns an `io::Error` if the connection fails or is closed, and the response to send instead
+/// of calling the handler if the request is invalid or rejected.
+fn read_request<R: BufRead, W: Write, H: Handler>(reader: &mut R, writer: &mut W, handler: &H,
+	limits: Limits) -> io::Result<Result<Request, Response>>
+{
+	let mut request = match read_head(reader, limits)? {
+		Ok(request) => request,
+		Err(err) => return Ok(Err(err.into())),
+	};
+
+	let framing = match check_request(&request, limits) {
+		Ok(framing) => framing,
+		Err(err) => return Ok(Err(err.into())),
+	};
+	if let Err(response) = send_continue(writer, handler, &request, framing)? {
+		return Ok(Err(response));
+	}
+
+	request.body = match read_body(reader, framing, &mut request, limits)? {
+		Ok(body) => body,
+		Err(err) => return Ok(Err(err.into())),
+	};
+	Ok(Ok(request))
+}
+
+/// Reads the request-line and header fields, leaving the body in the reader.
+fn read_head<R: BufRead>(reader: &mut R, limits: Limits) -> io::Result<Result<Request, HttpError>> {
+	let mut parser = Parser::new()
+		.max_line_length(limits.max_line_length)
+		.max_headers(limits.max_headers);
+	let mut buf = Vec::new();
+
+	loop {
+		let received = buf.len();
+		{
+			let available = reader.fill_buf()?;
+			if available.is_empty() {
+				return Err(io::ErrorKind::UnexpectedEof.into());
+			}
+			buf.extend_from_slice(available);
+		}
+
+		match parser.parse_request(&buf) {
+			Ok(Status::Partial) => reader.consume(buf.len() - received),
+			Ok(Status::Complete(n, head)) => {
+				reader.consume(n - received);
+				let target = match RequestTarget::parse(&head.method, head.target.as_bytes()) {
+					Ok(target) => target,
+					Err(err) => return Ok(Err(err.into())),
+				};
+				return Ok(Ok(Request {
+					method: head.method.clone(),
+					target,
+					version: head.version,
+					headers: head.header_map(),
+					body: Vec::new(),
+				}));
+			}
+			Err(err) => return Ok(Err(err.into())),
+		}
+	}
+}
+
+/// Checks the parts of a request that the server relies on, and returns the framing of its body.
+fn check_request(request: &Request, limits: Limits) -> Result<Framing, HttpError> {
+	match request.version {
+		HttpVersion::Http10 | HttpVersion::Http11 => {}
+		_ => return Err(HttpError::http_version_not_supported()),
+	}
+
+	// A server MUST respond with a 400 (Bad Request) status code to any HTTP/1.1 request message
+	// that lacks a Host header field and to any request message that contains more than one Host
+	// header field or a Host header field with an invalid field-value.
+	if request.headers.get_typed::<Host>()?.is_none() && request.version == HttpVersion::Http11 {
+		return Err(HttpError::bad_request().with_detail("missing Host header"));
+	}
+
+	match framing::request_framing(&request.headers)? {
+		Framing::Length(len) if len > limits.max_body_size => Err(HttpError::payload_too_large()),
+		// A body delimited by closing the connection leaves no way to send the response.
+		Framing::CloseDelimited => Err(HttpError::bad_request()),
+		framing => Ok(framing),
+	}
+}
+
+/// Meets the expectation of a request, sending 100 (Continue) if the handler accepts its body.
+///
+/// A server that receives a 100-continue expectation in an HTTP/1.0 request MUST ignore that
+/// expectation, and one that receives a request without a body need not respond to it.
+fn send_continue<W: Write, H: Handler>(writer: &mut W, handler: &H, request: &Request,
+	framing: Framing) -> io::Result<Result<(), Response>>
+{
+	if request.version != HttpVersion::Http11 {
+		return Ok(Ok(()));
+	}
+	match request.headers.get_typed::<Expect>() {
+		Ok(None) => return Ok(Ok(())),
+		Ok(Some(ref expect)) if expect.is_continue() => {}
+		_ => return Ok(Err(HttpError::expectation_failed().into())),
+	}
+	if framing == Framing::Empty {
+		return Ok(Ok(()));
+	}
+
+	let accepted = panic::catch_unwind(AssertUnwindSafe(|| handler.expect_continue(request)))
+		.unwrap_or_else(|_| Err(Response::from(HttpError::internal_server_error())));
+	if let Err(mut response) = accepted {
+		response.version = request.version;
+		return Ok(Err(response));
+	}
+
+	let interim = Response::builder().status(StatusCode::Continue).body(());
+	match serializer::write_response_head(writer, &interim, &request.method, None) {
+		Ok(_) => writer.flush().map(Ok),
+		Err(SerializeError::Io(err)) => Err(err),
+		Err(err) => Err(io::Error::other(err)),
+	}
+}
+
+/// Reads the body of a request, adding the fields of a chunked trailer to its header fields.
+fn read_body<R: BufRead>(reader: &mut R, framing: Framing, request: &mut Request, limits: Limits)
+	-> io::Result<Result<Vec<u8>, HttpError>>
+{
+	let mut body = Vec::new();
+	match framing {
+		Framing::Empty | Framing::CloseDelimited => {}
+		Framing::Length(len) => {
+			reader.take(len).read_to_end(&mut body)?;
+			if (body.len() as u64) < len {
+				return Err(io::ErrorKind::UnexpectedEof.into());
+			}
+		}
+		Framing::Chunked => {
+			let mut decoder = ChunkedDecoder::new(reader)
+				.max_line_length(limits.max_line_length)
+				.max_trailers(limits.max_headers)
+				.max_body_size(limits.max_body_size);
+			match decoder.read_to_end(&mut body) {
+				Ok(_) => {}
+				Err(err) => {
+					return match err.get_ref().and_then(|err| err.downcast_ref::<ChunkedError>()) {
+						Some(&ChunkedError::BodyTooLarge) => Ok(Err(HttpError::payload_too_large())),
+						Some(&err) => Ok(Err(HttpError::bad_request().with_source(err))),
+						None => Err(err),
+					};
+				}
+			}
+			for (name, value) in decoder.trailers() {
+				request.headers.append(name.clone(), value.clone());
+			}
+		}
+	}
+	Ok(Ok(body))
+}
+
+#[cfg(test)]
+mod test {
+	use std::io::{BufRead, BufReader, Read, Write};
+	use std::net::{SocketAddr, TcpStream};
+	use std::thread;
+	use std::time::Duration;
+
+	use message::{Request, Response};
+	use statuscodes::StatusCode;
+	use super::{Handler, Server};
+
+	fn start<H: Handler>(server: Server, handler: H) -> SocketAddr {
+		let addr = server.local_addr().unwrap();
+		thread::spawn(move || server.serve(handler));
+		addr
+	}
+
+	fn exchange(addr: SocketAddr, request: &[u8]) -> String {
+		let mut stream = TcpStream::connect(addr).unwrap();
+		stream.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+		stream.write_all(request).unwrap();
+		let mut response = Vec::new();
+		stream.read_to_end(&mut response).unwrap();
+		String::from_utf8_lossy(&response).into_owned()
+	}
+
+	/// Reads one response with a Content-Length from a persistent connection.
+	fn read_response<R: BufRead>(reader: &mut R) -> String {
+		let mut response = String::new();
+		let mut len = 0;
+		loop {
+			let mut line = String::new();
+			reader.read_line(&mut line).unwrap();
+			if let Some(value) = line.strip_prefix("Content-Length: ") {
+				len = value.trim().parse().unwrap();
+			}
+			response.push_str(&line);
+			if line == "\r\n" || line.is_empty() {
+				break;
+			}
+		}
+		let mut body = vec![0; len];
+		reader.read_exact(&mut body).unwrap();
+		response + &String::from_utf8_lossy(&body)
+	}
+
+	fn connect(addr: SocketAddr) -> (BufReader<TcpStream>, TcpStream) {
+		let stream = TcpStream::connect(addr).unwrap();
+		stream.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+		(BufReader::new(stream.try_clone().unwrap()), stream)
+	}
+
+	fn is_closed<R: Read>(reader: &mut R) -> bool {
+		let mut rest = Vec::new();
+		reader.read_to_end(&mut rest).is_ok() && rest.is_empty()
+	}
+
+	fn echo(request: Request) -> Response {
+		use typed_headers::ContentLength;
+
+		let mut body = format!("{} {} {}\n", request.method, request.target, request.version).into_bytes();
+		for (name, value) in &request.headers {
+			body.extend_from_slice(format!("{}={}\n", name, value.to_str().unwrap_or("?")).as_bytes());
+		}
+		body.extend_from_slice(&request.body);
+		Response::builder()
+			.typed_header(ContentLength(body.len() as u64))
+			.body(body)
+	}
+
+	#[test]
+	fn server_test() {
+		let addr = start(Server::bind("127.0.0.1:0").unwrap(), echo);
+
+		let response = exchange(addr, b"GET /a?b HTTP/1.1\r\nHost: x\r\nConnection: close\r\n\r\n");
+		assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+		assert!(response.contains("\r\nConnection: close\r\n"), "{}", response);
+		assert!(response.contains("\r\nDate: "), "{}", response);
+		assert!(response.ends_with("\r\n\r\nGET /a?b HTTP/1.1\nHost=x\nConnection=close\n"), "{}", response);
+
+		let response = exchange(addr, b"GET / HTTP/1.0\r\n\r\n");
+		assert!(response.starts_with("HTTP/1.0 200 OK\r\n"), "{}", response);
+
+		let response = exchange(addr, b"HEAD / HTTP/1.1\r\nHost: x\r\nConnection: close\r\n\r\n");
+		assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+		assert!(response.contains("\r\nContent-Length: 40\r\n"), "{}", response);
+		assert!(response.ends_with("\r\n\r\n"), "{}", response);
+	}
+
+	#[test]
+	fn server_head_test() {
+		use method::Method;
+		use typed_headers::ContentLength;
+
+		// A response to HEAD describes the body that GET would have sent, without sending it.
+		let handler = |request: Request| {
+			let body = if request.method == Method::Head { Vec::new() } else { vec![b'x'; 40] };
+			Response::builder().typed_header(ContentLength(40)).body(body)
+		};
+		let addr = start(Server::bind("127.0.0.1:0").unwrap(), handler);
+
+		let response = exchange(addr, b"HEAD / HTTP/1.1\r\nHost: x\r\nConnection: close\r\n\r\n");
+		assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+		assert!(response.contains("\r\nContent-Length: 40\r\n"), "{}", response);
+		assert!(response.ends_with("\r\n\r\n"), "{}", response);
+	}
+
+	#[test]
+	fn server_write_timeout_test() {
+		use typed_headers::ContentLength;
+
+		// More than the socket buffers hold, so that writing it blocks until the client reads.
+		let handler = |request: Request| {
+			let size = if request.target.to_string() == "/large" { 32 << 20 } else { 0 };
+			let body = vec![b'x'; size];
+			Response::builder().typed_header(ContentLength(body.len() as u64)).body(body)
+		};
+		let server = Server::bind("127.0.0.1:0").unwrap()
+			.threads(1)
+			.write_timeout(Duration::from_millis(200));
+		let addr = start(server, handler);
+
+		// A client that never reads its response only holds the only worker until the timeout.
+		let mut stalled = TcpStream::connect(addr).unwrap();
+		stalled.write_all(b"GET /large HTTP/1.1\r\nHost: x\r\n\r\n").unwrap();
+		thread::sleep(Duration::from_millis(100));
+
+		let response = exchange(addr, b"GET / HTTP/1.1\r\nHost: x\r\nConnection: close\r\n\r\n");
+		assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+		drop(stalled);
+	}
+
+	#[test]
+	fn server_body_test() {
+		let addr = start(Server::bind("127.0.0.1:0").unwrap(), echo);
+
+		let response = exchange(addr, b"POST / HTTP/1.1\r\nHost: x\r\nConnection: close\r\nContent-Length: 5\r\n\r\nhello");
+		assert!(response.ends_with("\nhello"), "{}", response);
+
+		let request = b"POST / HTTP/1.1\r\nHost: x\r\nConnection: close\r\nTransfer-Encoding: chunked\r\n\r\n\
+			3\r\nhel\r\n2\r\nlo\r\n0\r\nX-Sum: 9\r\n\r\n";
+		let response = exchange(addr, request);
+		assert!(response.ends_with("\nX-Sum=9\nhello"), "{}", response);
+	}
+
+	#[test]
+	fn server_keep_alive_test() {
+		let addr = start(Server::bind("127.0.0.1:0").unwrap(), echo);
+
+		// HTTP/1.1 connections persist until the client closes them.
+		let (mut reader, mut stream) = connect(addr);
+		for path in &["/a", "/b"] {
+			stream.write_all(format!("GET {} HTTP/1.1\r\nHost: x\r\n\r\n", path).as_bytes()).unwrap();
+			let response = read_response(&mut reader);
+			assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+			assert!(!response.contains("\r\nConnection: "), "{}", response);
+			assert!(response.contains(&format!("GET {} HTTP/1.1\n", path)), "{}", response);
+		}
+		stream.write_all(b"GET /c HTTP/1.1\r\nHost: x\r\nConnection: close\r\n\r\n").unwrap();
+		let response = read_response(&mut reader);
+		assert!(response.contains("\r\nConnection: close\r\n"), "{}", response);
+		assert!(is_closed(&mut reader));
+
+		// HTTP/1.0 connections persist only when the client asks.
+		let (mut reader, mut stream) = connect(addr);
+		stream.write_all(b"GET / HTTP/1.0\r\nConnection: keep-alive\r\n\r\n").unwrap();
+		let response = read_response(&mut reader);
+		assert!(response.starts_with("HTTP/1.0 200 OK\r\n"), "{}", response);
+		assert!(response.contains("\r\nConnection: keep-alive\r\n"), "{}", response);
+		stream.write_all(b"GET / HTTP/1.0\r\n\r\n").unwrap();
+		let response = read_response(&mut reader);
+		assert!(response.contains("\r\nConnection: close\r\n"), "{}", response);
+		assert!(is_closed(&mut reader));
+	}
+
+	#[test]
+	fn server_pipelining_test() {
+		let addr = start(Server::bind("127.0.0.1:0").unwrap(), echo);
+
+		let (mut reader, mut stream) = connect(addr);
+		stream.write_all(b"GET /1 HTTP/1.1\r\nHost: x\r\n\r\n\
+			POST /2 HTTP/1.1\r\nHost: x\r\nContent-Length: 3\r\n\r\nabc\
+			GET /3 HTTP/1.1\r\nHost: x\r\nConnection: close\r\n\r\n").unwrap();
+		for path in &["/1", "/2", "/3"] {
+			let response = read_response(&mut reader);
+			assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+			assert!(response.contains(&format!(" {} HTTP/1.1\n", path)), "{}", response);
+		}
+		assert!(is_closed(&mut reader));
+
+		// A request after an invalid one is not answered.
+		let response = exchange(addr, b"GET / HTTP/1.1\r\n\r\nGET / HTTP/1.1\r\nHost: x\r\n\r\n");
+		assert!(response.starts_with("HTTP/1.1 400 Bad Request\r\n"), "{}", response);
+		assert_eq!(response.matches("HTTP/1.1 ").count(), 1, "{}", response);
+	}
+
+	#[test]
+	fn server_connection_limits_test() {
+		let server = Server::bind("127.0.0.1:0").unwrap()
+			.max_requests(2)
+			.idle_timeout(Duration::from_millis(100))
+			.request_timeout(Duration::from_millis(200));
+		let addr = start(server, echo);
+
+		let (mut reader, mut stream) = connect(addr);
+		stream.write_all(b"GET / HTTP/1.1\r\nHost: x\r\n\r\nGET / HTTP/1.1\r\nHost: x\r\n\r\n").unwrap();
+		let response = read_response(&mut reader);
+		assert!(!response.contains("\r\nConnection: "), "{}", response);
+		let response = read_response(&mut reader);
+		assert!(response.contains("\r\nConnection: close\r\n"), "{}", response);
+		assert!(is_closed(&mut reader));
+
+		// An idle connection is closed without a response.
+		let (mut reader, _stream) = connect(addr);
+		assert!(is_closed(&mut reader));
+
+		// A request that is not received in time is answered with 408.
+		let (mut reader, mut stream) = connect(addr);
+		stream.write_all(b"GET / HTTP/1.1\r\nHost: x\r\n").unwrap();
+		let response = read_response(&mut reader);
+		assert!(response.starts_with("HTTP/1.1 408 Request Timeout\r\n"), "{}", response);
+		assert!(response.contains("\r\nConnection: close\r\n"), "{}", response);
+		assert!(is_closed(&mut reader));
+	}
+
+	#[test]
+	fn server_expect_continue_test() {
+		struct Uploads;
+
+		impl Handler for Uploads {
+			fn handle(&self, request: Request) -> Response {
+				echo(request)
+			}
+
+			fn expect_continue(&self, request: &Request) -> Result<(), Response> {
+				match request.target.path() {
+					"/full" => Err(Response::builder().status(StatusCode::InsufficientStorage).body(Vec::new())),
+					_ => Ok(()),
+				}
+			}
+		}
+
+		let addr = start(Server::bind("127.0.0.1:0").unwrap().max_body_size(8), Uploads);
+
+		// The body is sent only after 100 (Continue).
+		let (mut reader, mut stream) = connect(addr);
+		stream.write_all(b"PUT / HTTP/1.1\r\nHost: x\r\nExpect: 100-continue\r\nContent-Length: 2\r\n\r\n").unwrap();
+		assert_eq!("HTTP/1.1 100 Continue\r\n\r\n", read_response(&mut reader));
+		stream.write_all(b"ok").unwrap();
+		let response = read_response(&mut reader);
+		assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+		assert!(response.ends_with("\nok"), "{}", response);
+
+		let test_cases = vec![
+			("PUT /full HTTP/1.1\r\nHost: x\r\nExpect: 100-continue\r\nContent-Length: 2\r\n\r\n",
+				"HTTP/1.1 507 Insufficient Storage\r\n"),
+			("PUT / HTTP/1.1\r\nHost: x\r\nExpect: 100-continue\r\nContent-Length: 9\r\n\r\n",
+				"HTTP/1.1 413 Payload Too Large\r\n"),
+			("PUT / HTTP/1.1\r\nHost: x\r\nExpect: x-telepathy\r\nContent-Length: 2\r\n\r\n",
+				"HTTP/1.1 417 Expectation Failed\r\n"),
+			// The expectation is ignored in an HTTP/1.0 request, and for a request without a body.
+			("PUT / HTTP/1.0\r\nExpect: 100-continue\r\nContent-Length: 2\r\n\r\nok", "HTTP/1.0 200 OK\r\n"),
+			("GET /full HTTP/1.1\r\nHost: x\r\nExpect: 100-continue\r\nConnection: close\r\n\r\n",
+				"HTTP/1.1 200 OK\r\n"),
+		];
+
+		for (request, status) in test_cases {
+			let response = exchange(addr, request.as_bytes());
+			assert!(response.starts_with(status), "{:?}: {}", request, response);
+			assert!(!response.contains("100 Continue"), "{:?}: {}", request, response);
+		}
+	}
+
+	#[test]
+	fn server_upgrade_test() {
+		use typed_headers::UpgradeProtocol;
+		use upgrade::switching_protocols;
+
+		// The handler switches to whatever it is asked, which the server only allows when asked.
+		let server = Server::bind("127.0.0.1:0").unwrap().idle_timeout(Duration::from_millis(50));
+		let addr = start(server, |_: Request| {
+			switching_protocols(UpgradeProtocol::new("shout", None).unwrap())
+		});
+
+		let test_cases = vec![
+			("GET / HTTP/1.1\r\nHost: x\r\nUpgrade: shout\r\nConnection: upgrade\r\n\r\n",
+				"HTTP/1.1 101 Switching Protocols\r\n"),
+			("GET / HTTP/1.1\r\nHost: x\r\nUpgrade: whisper\r\nConnection: upgrade\r\n\r\n",
+				"HTTP/1.1 500 Internal Server Error\r\n"),
+			("GET / HTTP/1.1\r\nHost: x\r\nUpgrade: shout\r\nConnection: close\r\n\r\n",
+				"HTTP/1.1 500 Internal Server Error\r\n"),
+			("GET / HTTP/1.0\r\nUpgrade: shout\r\nConnection: upgrade\r\n\r\n",
+				"HTTP/1.0 500 Internal Server Error\r\n"),
+		];
+
+		for (request, status) in test_cases {
+			let response = exchange(addr, request.as_bytes());
+			assert!(response.starts_with(status), "{:?}: {}", request, response);
+		}
+	}
+
+	#[test]
+	fn server_error_test() {
+		let server = Server::bind("127.0.0.1:0").unwrap()
+			.max_line_length(64)
+			.max_headers(4)
+			.max_body_size(8);
+		let addr = start(server, echo);
+
+		let long_target = format!("GET /{} HTTP/1.1\r\nHost: x\r\n\r\n", "a".repeat(64));
+		let long_header = format!("GET / HTTP/1.1\r\nHost: x\r\nX: {}\r\n\r\n", "a".repeat(64));
+		let test_cases = vec![
+			("GET / HTTP/1.1\r\n\r\n".to_string(), "400 Bad Request"),
+			("GET / HTTP/1.1\r\nHost: x\r\nHost: y\r\n\r\n".to_string(), "400 Bad Request"),
+			("GET / HTTP/1.1\r\nHost: x\r\n bad\r\n\r\n".to_string(), "400 Bad Request"),
+			("G@T / HTTP/1.1\r\nHost: x\r\n\r\n".to_string(), "400 Bad Request"),
+			("GET /\r\n\r\n".to_string(), "400 Bad Request"),
+			("GET * HTTP/1.1\r\nHost: x\r\n\r\n".to_string(), "400 Bad Request"),
+			("GET / HTTP/2.0\r\nHost: x\r\n\r\n".to_string(), "505 HTTP Version Not Supported"),
+			(long_target, "414 URI Too Long"),
+			(long_header, "431 Request Header Fields Too Large"),
+			("GET / HTTP/1.1\r\nHost: x\r\nA: 1\r\nB: 2\r\nC: 3\r\nD: 4\r\n\r\n".to_string(),
+				"431 Request Header Fields Too Large"),
+			("POST / HTTP/1.1\r\nHost: x\r\nContent-Length: 9\r\n\r\n123456789".to_string(),
+				"413 Payload Too Large"),
+			("POST / HTTP/1.1\r\nHost: x\r\nTransfer-Encoding: chunked\r\n\r\n9\r\n123456789\r\n0\r\n\r\n"
+				.to_string(), "413 Payload Too Large"),
+			("POST / HTTP/1.1\r\nHost: x\r\nContent-Length: 3\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\n"
+				.to_string(), "400 Bad Request"),
+			("POST / HTTP/1.1\r\nHost: x\r\nTransfer-Encoding: chunked\r\n\r\nzz\r\n".to_string(),
+				"400 Bad Request"),
+		];
+
+		for (request, status) in test_cases {
+			let response = exchange(addr, request.as_bytes());
+			assert!(response.starts_with(&format!("HTTP/1.1 {}\r\n", status)), "{:?}: {}", request, response);
+			assert!(response.contains("\r\nConnection: close\r\n"), "{}", response);
+		}
+	}
+
+	#[test]
+	fn server_handler_panic_test() {
+		let addr = start(Server::bind("127.0.0.1:0").unwrap().threads(1), |request: Request| {
+			if request.target.path() == "/panic" {
+				panic!("handler failed");
+			}
+			Response::builder().body(Vec::new())
+		});
+
+		let response = exchange(addr, b"GET /panic HTTP/1.1\r\nHost: x\r\nConnection: close\r\n\r\n");
+		assert!(response.starts_with("HTTP/1.1 500 Internal Server Error\r\n"), "{}", response);
+
+		// The worker thread survives the panic.
+		let response = exchange(addr, b"GET / HTTP/1.1\r\nHost: x\r\nConnection: close\r\n\r\n");
+		assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+	}
+
+	#[test]
+	fn server_threads_test() {
+		use std::sync::{Arc, Barrier};
+
+		// Both requests are in the handler at the same time, or neither completes.
+		let barrier = Arc::new(Barrier::new(2));
+		let addr = start(Server::bind("127.0.0.1:0").unwrap().threads(2), move |_: Request| {
+			barrier.wait();
+			Response::builder().body(Vec::new())
+		});
+
+		let clients: Vec<_> = (0..2)
+			.map(|_| thread::spawn(move || exchange(addr, b"GET / HTTP/1.1\r\nHost: x\r\nConnection: close\r\n\r\n")))
+			.collect();
+		for client in clients {
+			let response = client.join().unwrap();
+			assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+		}
+	}
+}
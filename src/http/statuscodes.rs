@@ -1,526 +1,160 @@
-/// A StatusCode represents a HTTP response status code.
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Defines the StatusCode enum, and the methods that depend on the registry of status codes.
 ///
-/// The status-code element is a three-digit integer code giving the result of the attempt to
-/// understand and satisfy the request.
-#[derive(PartialEq, Debug)]
-pub enum StatusCode {
-	/// 100 - Continue (http://tools.ietf.org/html/rfc7231#section-6.2.1)
-	///
-	/// The 100 (Continue) status code indicates that the initial part of a request has been
-	/// received and has not yet been rejected by the server. The server intends to send a final
-	/// response after the request has been fully received and acted upon.
-	///
-	/// When the request contains an Expect header field that includes a 100-continue expectation,
-	/// the 100 response indicates that the server wishes to receive the request payload body, as
-	/// described in http://tools.ietf.org/html/rfc7231#section-5.1.1. The client ought to continue
-	/// sending the request and discard the 100 response.
-	///
-	/// If the request did not contain an Expect header field containg the 100-continue
-	/// expectation, the client can simply discard this interim response.
-	Continue,
-	/// 101 - Switching Protocols (http://tools.ietf.org/html/rfc7231#section-6.2.2)
-	///
-	/// The 101 (Switching Protocols) status code indicates that the server understands and is
-	/// willing to comply with the client's request, via, the Upgrade header field
-	/// [Section 6.7 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-6.7), for a change 
-    /// in the application protocol being used on this connection. The server MUST generate an 
-    /// Upgrade header field in the response that indicates which protocol(s) will be switched to
-	/// immediatly after the empty line that terminates the 101 response.
-	///
-	/// It is assumed that the server will only agree to switch protocols when it is adventageous
-	/// to do so. For example, switching to a newer version of HTTP might be adventageous over
-	/// older versions, and switching to a real-time, synchronous protocol might be adventageous
-	/// when delivering resources that use such features.
-	SwitchingProtocols,
-	/// 200 - OK (http://tools.ietf.org/html/rfc7231#section-6.3.1)
-	///
-	/// The 200 (OK) status code indicates that the request has succeeded. The payload sent in a
-	/// 200 response depends on the request method. For the methods defined by this specification,
-	/// the intended meaning of the payload can be summarized as:
-	///
-	/// * GET - a representation of the target resource;
-	/// * HEAD - the same representation as GET, but without the representation data;
-	/// * POST - a representation of the status of, or results obtained from, the action;
-	/// * PUT, DELETE - a representation of the status of the action;
-	/// * OPTIONS - a representation of the communications options;
-	/// * TRACE - a representation of the request message as received by the end server.
-	///
-	/// Aside from responses to CONNECT, a 200 response always has a payload, though an origin
-	/// server MAY generate a payload body of zero length. If no payload is desired, an origin
-	/// server ought to send 204 (No Content) instead. For CONNECT, no payload is allowed because
-	/// the successful result is a tunnelm, which begins immediatly after the 200 response header
-	/// section.
-	///
-	/// A 200 response is cacheable by default; i.e., unless otherwise indicated by the method
-	/// definition or explicit cache controls
-	/// (see [Section 3.2.2 of \[RFC7234\]](http://tools.ietf.org/html/rfc7234#section-4.2.2)).
-	Ok,
-	/// 201 - Created (http://tools.ietf.org/html/rfc7231#section-6.3.2)
-	///
-	/// The 201 (Created) status code indicates that the request has been fufilled and has resulted
-	/// in one or more new resources being created. The primary resource created by the request is
-	/// identified by either a Location header field in the response or, if no Location field is
-	/// received, by the effective request URI.
-	///
-	/// The 201 response payload typically describes and links to the resource(s) created.
-	/// See [Section 7.2 of \[RFC7231\]](http://tools.ietf.org/html/rfc7231#section-7.2) for a
-	/// discussion of the meaning and purpose of validator header fields, such as ETag and
-	/// Last-Modified, in a 201 response.
-	Created,
-	/// 202 - Accepted (http://tools.ietf.org/html/rfc7231#section-6.3.3)
-	///
-	/// The 202 (Accepted) status code indicates that the request has been accepted for processing,
-	/// but the processing has not been completed. The request might or might not eventually be
-	/// acted upon, as it might be disallowed when processing actually takes place. There is no
-	/// facility in HTTP for re-sending a status code from an asynchronous operation.
-	///
-	/// The 202 response is intentionally noncommittal. Its purpose is to allow a server to accept
-	/// a request for some other process (perhaps a batch-oriented process that is only run once
-	/// per day) without requiring that the user agent's connection to the server persist until the
-	/// process is completed. The representation sent with this response ought to describe the
-	/// request's current status and point to (or embed) a status monitor that can provide the user
-	/// with an estimate of when the request will be fufilled.
-	Accepted,
-	/// 203 - Non-Authoritative Information (http://tools.ietf.org/html/rfc7231#section-6.3.4)
-	///
-	/// The 203 (Non-Authoritative Information) status code indicates that the request was
-	/// successful but the enclosed payload has been modified from that of the origin server's
-	/// 200 (OK) response by a transforming proxy
-	/// ([Section 5.7.2 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-5.7.2)).
-	/// This status code allows the proxy to notify the recipients when a transformation has been
-	/// applied, since that knowledge might impact later decisions regarding the content. For
-	/// example, future cache validation requests for the conent might only be applicable along the
-	/// same request path (through the same proxies).
-	///
-	/// The 203 response is similiar to the Warning code of 214 Transformation Applied
-	/// ([Section 5.5 of \[RFC7234\]](http://tools.ietf.org/html/rfc7234#section-5.5)), which has
-	/// the advantage of being appliable to responses with any status code.
-	///
-	/// A 203 resposne is cacheable by default; i.e., unless otherwise indicated by the method
-	/// definition or explicit cache controls (see
-	/// [Section 4.2.2 of \[RFC7234\]](http://tools.ietf.org/html/rfc7234#section-4.2.2)).
-	NonAuthoritativeInformation,
-	/// 204 - No Content (http://tools.ietf.org/html/rfc7231#section-6.3.5)
-	///
-	/// The 204 (No Content) status code indicates that the server has successfully fufilled the
-	/// request and that there is no additional content to send in the response payload body.
-	/// Metadata in the response header fields refer to the target resource and its selected
-	/// representation after the requested action was applied.
-	///
-	/// For example, if a 204 status code is received in response to a PUT request and the response
-	/// contains the ETag header field, then the PUT was successful and the ETag field-value
-	/// contains the entity-tag for the new representation of that target resource.
-	///
-	/// The 204 response allows a server to indicate that the action has been successfully applied
-	/// to the target resource, which implying that the user agent does not need to traverse away
-	/// from its current "document view" (if any). The server assumes that the user agent will
-	/// provide some indication of the success to its user, in accord with its own interface, and
-	/// apply any new or updated metadata in the response to its active representation.
-	///
-	/// For example, a 204 status code is commonly used with document editing interfaces
-	/// corresponding to a "save" action, such that the doucment being saved remains available to
-	/// the user for editing. It is also frequently used with interfaces that expect automated data
-	/// transfers to be prevalent, such as withing distributed version control systems.
-	///
-	/// A 204 response is terminated by the first empty line after the header fiels because it
-	/// cannot contain a message body.
-	///
-	/// A 204 response is cacheable by default; i.e., unless otherwise indicated by the method
-	/// definition or explicit cache controls (see
-	/// [Section 4.2.2 of \[RFC7234\]](http://tools.ietf.org/html/rfc7234#section-4.2.2)).
-	NoContent,
-	/// 205 - Reset Content (http://tools.ietf.org/html/rfc7231#section-6.3.6)
-	///
-	/// The 205 (Reset Content) status code indicates that the server has fufilled the request and
-	/// desires that the user agent reset the "document view", which caused the request to be sent,
-	/// to its original state as received from the origin server.
-	///
-	/// This response is intended to support a common data entry use case where the user receives
-	/// content that supports data entry (a form, notepad, canvas, etc.), enters or manipulates
-	/// data in that space, causes the entered data to be submitted in a request, and then the data
-	/// entry mechanism is reset for the next entry so that the user can easily initiate another
-	/// input aciton.
-	///
-	/// Since the 205 status code implies that no addtional content will be provided, a server MUST
-	/// NOT generate a payload in a 205 response. In other wors, a server MUST do one of the
-	/// following for a 205 response: a) indicate a zero-length body for the response by including
-	/// a Content-Length header field with a value of 0; b) indicate a zero-length payload for the
-	/// response by including a Transfer-Encoding header field with a value of chunked and a message
-	/// body consiting of a single chunk of zero-length; or, c) close the connection immediatly
-	/// after sending the blank line terminating the header section.
-	ResetContent,
-	/// 206 - Partial Content (http://tools.ietf.org/html/rfc7233#section-4.1)
-	PartialContent,
-	/// 300 - Multiple Choices (http://tools.ietf.org/html/rfc7231#section-6.4.1)
-	///
-	/// The 300 (Multiple Choices) status code indicates that the target resource has more than one
-	/// representation, each with its own more specific identifier, and information about
-	/// alternatives is being provided so that the user (or user agent) can select a preferred
-	/// representation by redirecting its request to one or more of those identifiers. In other
-	/// words, the server desires that the user agent engage in reactive negotiation to select the
-	/// most appropriate representation(s) for its needs
-	/// ([Section 3.4 of \[RFC7231\]](http://tools.ietf.org/html/rfc7231#section-3.4)).
-	///
-	/// If the server has a preferred choice, the server SHOULD generate a Location header field
-	/// containing a preferred choice's URI reference. The user agent MAY use the Location field
-	/// value for automatic redirection.
-	///
-	/// For request methods other than HEAD, the server SHOULD generate a payload in the 300
-	/// response containing a list of representation metadata and URI reference(s) from which the
-	/// user or user agent can choose the one most preferred. The user agent MAY make a selection
-	/// from that list automatically if it understands the provided media type. A specific format
-	/// for automatic selection is not defined by this specification because HTTP tries to remain
-	/// orthogonal to the definition of its payloads. In practice, the representation is provided
-	/// in some easily parsed format believed to be acceptable to the user agent, as determined by
-	/// shared design or content negotiation, or in some commonly accepted hypertext format.
-	///
-	/// A 300 response is cacheable by default; i.e., unless otherwise indicated by the method
-	/// definition or explicit cache controls
-	/// (see [Section 4..2 of \[RFC7234\]](http://tools.ietf.org/html/rfc7234#section-4.2.2)).
-	///
-	/// > Note: The original proposal for the 300 status code defined the URI header field as
-	/// > providing a list of alternative representations, such that it would be usable for 200,
-	/// > 300, and 406 responses and be transferred in response to the HEAD method. However, a
-	/// > lack of deployment and disagreement over syntax led to both URI and Alternates (a
-	/// > subsequent proposal) being dropped from this specification. It is possible to
-	/// > communicate the list using a set of Link header fields
-	/// > [ \[RFC5988\] ](http://tools.ietf.org/html/rfc5988), each with a relationship of
-	/// > "alternate", though deployment is a chicken-and-egg problem.
-	MultipleChoices,
-	/// 301 - Moved Permanently (http://tools.ietf.org/html/rfc7231#section-6.4.2)
-	///
-	/// The 301 (Moved Permanently) status code indicates that the target resource has been
-	/// assigned a new permanent URI and any future references to this resource ought to use one of
-	/// the enclosed URIs. Clients with link-editing capabilities ought to automatically re-link
-	/// references to the effective request URI to one or more of the new references sent by the
-	/// server, where possible.
-	///
-	/// The server SHOULD generate a Location header field in the response containing a preferred
-	/// URI reference for the new permanent URI. The user agent MAY use the location field value
-	/// for automatic redirection. The server's response payload usually contains a short hypertext
-	/// note with a hyperlink to the new URI(s).
-	///
-	/// > Note: For historical reasons, a user agent MAY change the request method from POST to GET
-	/// > for the subsequent request. If this behavior is undesired the 307 (Temporary Redirect)
-	/// > status code can be used instead.
-	///
-	/// A 301 response is cacheable by default; i.e., unless otherwise indicated by the method
-	/// definition or explicit cache controls (see
-	/// [Section 4.2.2 of \[RFC7234\]](http://tools.ietf.org/html/rfc7234#section-4.2.2)).
-	MovedPermanently,
-	/// 302 - Found (http://tools.ietf.org/html/rfc7231#section-6.4.3)
-	///
-	/// The 302 (Found) status code indicates that the target resource resides temporarily under a
-	/// different URI. Since the redirection might be altered on occasion, the client ought to
-	/// continue to use the effective request URI for future requests.
-	///
-	/// The server SHOULD generate a Location header field in the response containing a URI
-	/// reference for the different URI. The user agent MAY use the Location field value for
-	/// automatic redirection. The server's response payload usually contains a short hypertext
-	/// note with a hyperlink to the different URI(s).
-	///
-	/// > Note: For historical reasions, a user agent MAY change the request method from POST to
-	/// > GET for the subsequent request. If this behavior is undesired, the 307 (Temporary
-	/// > Redirect) status code can be used instead.
-	Found,
-	/// 303 - See Other (http://tools.ietf.org/html/rfc7231#section-6.4.4)
-	///
-	/// The 303 (See Other) status code indicates that the server is redirecting the user agent to
-	/// a different resource, as indicated by a URI in the Location header field, which is
-	/// intended to provide an idrect response to the original request. A user agent can perform a
-	/// retrieval request targeting that URI (a GET or HEAD request if using HTTP), which might
-	/// also be redirected, and present the eventual result as an answer to the original request. 
-    /// Note that the new URI in the Location header field is not equivalent to the effective 
-    /// request URI.
-    /// 
-    /// This status code is applicable to any HTTP method. It is primarily used to allow the output
-    /// of a POST action redirect the user agent to a selected resource, since doing so provides the
-    /// information corresponding to the POST response in a form that can be separately identified
-    /// bookmakred, and cached, independent of the original request.
-    /// 
-    /// A 303 response to a GET request indicates that the origin server does not have a 
-    /// representation of the target resource that can be transferred by the server over HTTP. 
-    /// However, the Location field value refers to a resource taht is descriptive of the target
-    /// resource, such that making a retrieval request on that other resource might result in a 
-    /// representation that is useful to recipients without implying that it represents the original
-    /// target resource. Note that answers to the question of what can be represented, what 
-    /// representations are adequate, and what might be a useful description are outside the scope 
-    /// of HTTP.
-    ///
-    /// Except for respones to a HEAD request, the representation of a 303 response ought to contain
-    /// a short hypertext note with a hyperlink to the same URI reference provided in the Location
-    /// header field.
-	SeeOther,
-	/// 304 - Not Modified (http://tools.ietf.org/html/rfc7232#section-4.1)
-	NotModified,
-	/// 305 - Use Proxy (http://tools.ietf.org/html/rfc7231#section-6.4.5)
-    /// 
-    /// The 305 (Use Proxy) status code was defined in ap revious version of this specification and
-    /// is now deprecated [Appendix B of \[RFC7231\]](http://tools.ietf.org/html/rfc7231#appendix-B)
-	UseProxy,
-	/// 307 - Temporary Redirect (http://tools.ietf.org/html/rfc7231#section-6.4.7)
-    /// 
-    /// The 307 (Temporary Redirect) status code indicates that the target resource resides 
-    /// temporarily under a different URI and the user agent MUST NOT change the request method
-    /// if it performs an automatic redirection to that URI. Since the redirection can change over
-    /// time, the client ought to continue using the original effective request URI for future 
-    /// requests.
-    ///
-    /// The server SHOULD generate a Location header field in the response containing a URI 
-    /// reference for the different URI. The user agent MAY use the Location field value for 
-    /// automatic redirection. The server's response payload usually contains a short hypertext note
-    /// with a hyperlink to the different URI(s).
-    /// 
-    /// > Note: This status code is similiar to 302 (Found), except that it does not allow chainging
-    /// > the request method from POST to GET. This specification defines no equivalent counterpart
-    /// > for 301 (Moved Permanently) ([\[RFC7238\]](http://tools.ietf.org/html/rfc7238), however, 
-    /// > defines the status code 308 (Permanent Redirect) for this purpose).
-	TemporaryRedirect,
-	/// 400 - Bad Request (http://tools.ietf.org/html/rfc7231#section-6.5.1)
-    ///
-    /// The 400 (Bad Request) status code indicates that the server cannot or will not process the
-    /// request due to something that is perceived to be a client error (e.g., malformed request 
-    /// syntax, invalid request message framing, or deceptive request routing).
-	BadRequest,
-	/// 401 - Unauthorized (http://tools.ietf.org/html/rfc7235#section-3.1)
-	Unauthorized,
-	/// 402 - Payment Required (http://tools.ietf.org/html/rfc7231#section-6.5.2)
-    /// 
-    /// The 402 (Payment Required) status code is reserved for future use.
-	PaymentRequired,
-	/// 403 - Forbidden (http://tools.ietf.org/html/rfc7231#section-6.5.3)
-    /// 
-    /// The 403 (Forbidden) status code indicates that the server understood the request but refuses
-    /// to authorize it. A servier that wishes to make public why the request has been forbidden can
-    /// describe that reason in the response payload (if any).
-    /// 
-    /// If authentication credentials were provided in the request, the server considers them 
-    /// insufficient to grant access. The client SHOULD NOT automatically repate the request with
-    /// the same credentials. The client MAY repeat the request with new or different credentials.
-    /// However, a request might be forbidden for reasons unrelated to credentials.
-    /// 
-    /// An origin server that wishes to "hide" the current existence of a forbidden target resource
-    /// MAY instead respond with a status code of 404 (Not Found).
-	Forbidden,
-	/// 404 - Not Found (http://tools.ietf.org/html/rfc7231#section-6.5.4)
-    /// 
-    /// The 404 (Not Found) status code indicates that the origin server did not find a current
-    /// representation for the target resource or is not willing to disclose that one exists. A 
-    /// 404 status code does not indicate whether this lack of representation is temporary or
-    /// permanent; the 410 (Gone) status code is preferred over 404 if the origin knows, presumably
-    /// through some configurable means, that the condition is likely to be permanent.
-    /// 
-    /// A 404 response is cacheable by default; i.e., unless otherwise indicated by the method 
-    /// definition or explicit cache controls (see 
-    /// [Section 4.2.2 of \[RFC7234\]](http://tools.ietf.org/html/rfc7234#section-4.2.2)).
-	NotFound,
-	/// 405 - Method Not Allowed (http://tools.ietf.org/html/rfc7231#section-6.5.5)
-    /// 
-    /// The 405 (Method Not Allowed) status code indicates that the method received in the 
-    /// request-line is known by teh origin server but not supported by the target resource. The
-    /// origin server MUST generate an Allow header field in a 405 response containing a list of the
-    /// target resource's currently supported methods.
-    /// 
-    /// A 405 response is cacheable by default; i.e., unless otherwise indicated by the method
-    /// definition or explicit cache controls (see 
-    /// [Section 4.2.2 of \[RFC7234\]](http://tools.ietf.org/html/rfc7234#section-4.2.2)).
-	MethodNotAllowed,
-	/// 406 - Not Acceptable (http://tools.ietf.org/html/rfc7231#section-6.5.6)
-    /// 
-    /// The 406 (Not Acceptable) status code indicates that the target resource does not have a
-    /// current representation that would be acceptable to the user agent, according to the 
-    /// proactive negotiation header fields received in the request 
-    /// ([Section 5.3 of \[RFC7231\]](http://tools.ietf.org/html/rfc7231#section-5.3)), and the 
-    /// server is unwilling to supply a default representation.
-    ///
-    /// The server SHOULD generate a payload containing a list of available representation 
-    /// characteristics and corresponding resource identifiers from which the user or agent can
-    /// choose the one most appropriate. A user agent MAY automatically select the most appropriate
-    /// choice from that list. However, this specification does not define any standard for such 
-    /// automatic selection, as described in 
-    /// [Section 6.4.1 of \[RFC7231\]](http://tools.ietf.org/html/rfc7231#section-6.4.1)
-	NotAcceptable,
-	/// 407 - Proxy Authentication Required (http://tools.ietf.org/html/rfc7235#section-3.2)
-	ProxyAuthenticationRequired,
-	/// 408 - Request Timeout (http://tools.ietf.org/html/rfc7231#section-6.5.7)
-    /// 
-    /// The 408 (Request Timeout) status code indicates that the server did not receive a complete
-    /// request message within the time that it was prepared to wait.  A server SHOULD send the
-    /// "close" connection option 
-    /// ([Section 6.1 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-6.1)) in the 
-    /// response, since 408 implies that the server has decided to close the connection rather than
-    /// continue waiting.  If the client has an outstanding request in transit, the client MAY 
-    /// repeat that request on a new connection. 
-	RequestTimeout,
-	/// 409 - Conflict (http://tools.ietf.org/html/rfc7231#section-6.5.8)
-    /// 
-    /// The 409 (Conflict) status code indicates that the request could not be completed due to 
-    /// a conflict with the current state of teh target resource. This code is used in situations
-    /// where the user might be able to resolve the conflict and resubmit the request. The server 
-    /// SHOULD generate a payload that includes enough information for a user to recognize the 
-    /// source of the conflict.
-    /// 
-    /// Conflicts are most likely to occur in response to a PUT request. For example if versioning
-    /// were being used and the representation being PUT included changes to a resource that
-    /// conflict with those mad by an earlier (third-party) request, the origin server might use a
-    /// 409 response to indicate that it can't complete the request. In this case, the response 
-    /// representation would likely contain information useful for merging the differences based on
-    /// the revision history.
-	Conflict,
-	/// 410 - Gone (http://tools.ietf.org/html/rfc7231#section-6.5.9)
-    ///
-    /// The 410 (Gone) status code indicates that access to the target resource is no longer
-    /// available at the origin server and that this condition is likely to be permanent. If the
-    /// origin server does not know, or has no facility to determine, whether or not the condition
-    /// is permanent, the status code 404 (Not Found) ought to be used instead.
-    /// 
-    /// The 410 resposne is primarily intended to assist the task of web maintenance by notifying 
-    /// the recipient that the resource is intentionally unavailable and that the server owners 
-    /// desire that remote links to that resource be removed. Such an event is common for 
-    /// limited-time, promotional, services and resources belonging to individuals no longer
-    /// associated with the origin server's site. It is not necessary to mark all permmanently 
-    /// unavailable resources as "gone" or to keep the mark for any length of time -- that is left
-    /// to the discretion of the server owner.
-    /// 
-    /// A 410 response is cacheable by default; i.e., unless otherwise indicated by the method
-    /// definition or explicit cache controls (see 
-    /// [Section 4.2.2 of \[RFC7234\]](http://tools.ietf.org/html/rfc7234#section-4.2.2)).
-	Gone,
-	/// 411 - Length Required (http://tools.ietf.org/html/rfc7231#section-6.5.10)
-    /// 
-    /// The 411 (Length Required) status code indicates that the server refuses to accept the 
-    /// request without a defined Content-Length 
-    /// ([Section 3.2.2 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-3.3.2)). The
-    /// client MAY repeate the request if it adds a valid Content-Length header field containing 
-    /// the length of the message body in the request message.
-	LengthRequired,
-	/// 412 - Precondition Failed (http://tools.ietf.org/html/rfc7232#section-4.2)
-	PreconditionFailed,
-	/// 413 - Payload Too Large (http://tools.ietf.org/html/rfc7231#section-6.5.11)
-    /// 
-    /// The 413 (Payload Too Large) status code indicates that the server is refusing to process a
-    /// request because the request payload is larger than the server is willing or able to process.
-    /// The server MAY close the connection to prevent the client from continuing the request.
-    /// 
-    /// If the condition is temporary, the server SHOULD generate a Retry-After header field to 
-    /// indicate that it is temporary and after what time the client MAY try again.
-	PayloadTooLarge,
-	/// 414 - URI Too Long (http://tools.ietf.org/html/rfc7231#section-6.5.12)
-    ///
-    /// The 414 (URI Too Long) status code indicates that the server is refusing to service the
-    /// request because the request-target 
-    /// ([Section 5.3 of \[RFC7230\]])(http://tools.ietf.org/html/rfc7231#section-5.3)) is 
-    /// longer thant the server is willing to interpret. This rare condition is only likely to
-    /// occur when a client has improperly converted a POST request to a GET request with long query
-    /// information, when the client has descended into a "black hole" of redirection (e.g., a 
-    /// redirected URI prefix that points to a suffix of itself) or when the server is under attack
-    /// by a client attempting to exploit potentital security holes.
-    /// 
-    /// A 414 response is cacheable by default; i.e., unless otherwise indicated by the method
-    /// definition or explicit cache controls (see 
-    /// [Section 4.2.2 of \[RFC7234\]](http://tools.ietf.org/html/rfc7234#section-4.2.2)).
-	UriTooLong,
-	/// 415 - Unsupported Media Type (http://tools.ietf.org/html/rfc7231#section-6.5.13)
-    /// 
-    /// The 415 (Unsupported Media Type) status code indicates that the origin server is refusing
-    /// to service the request because the payload is in a format not supported by this method on
-    /// the target resource. The format problem might be due to the request's indicated Content-Type
-    /// or Cotnent-Encoding, or as a result of inspecting the data directly.
-	UnsupportedMediaType,
-	/// 416 - Range Not Satisfiable (http://tools.ietf.org/html/rfc7233#section-4.4)
-	RangeNotSatisfiable,
-	/// 417 - Expectation Failed (http://tools.ietf.org/html/rfc7231#section-6.5.14)
-    ///
-    /// The 417 (Expectation Failed) status code indicates that the expectation givine in the
-    /// request's Expect header field 
-    /// ([Section 5.1.1 of \[RFC7231\]](http://tools.ietf.org/html/rfc7231#section-5.1.1)) could
-    /// not be met by at least one of the inbound servers.
-	ExpectationFailed,
-	/// 426 - Upgrade Required (http://tools.ietf.org/html/rfc7231#section-6.5.15)
-    ///
-    /// The 426 (Upgrade Required) status code indicates that the server refuses to perform the
-    /// request using the current protocol but might be willing to do so after the client upgrades
-    /// to a different protocol. The server MUST send an Upgrade header field in a 426 response to
-    /// indicate the required protocol(s) 
-    /// ([Section 6.7 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-6.7)).
-    /// 
-    /// Example:
-    /// 
-    /// ```c 
-    /// HTTP/1.1 426 Upgrade Required
-    /// Upgrade: HTTP/3.0
-    /// Connection: Upgrade
-    /// Content-Length: 53
-    /// Content-Type: text/plain
-    ///  
-    /// This service requires use of HTTP/3.0 protocol.
-    /// ```
-	UpgradeRequired,
-	/// 500 - Internal Server Error (http://tools.ietf.org/html/rfc7231#section-6.6.1)
-    /// 
-    /// The 500 (Internal Server Error) status code indicates that the server encountered an
-    /// unexpected condition that prevented it from fulfilling the request.
-	InternalServerError,
-	/// 501 - Not Implemented (http://tools.ietf.org/html/rfc7231#section-6.6.2)
-    ///
-    /// The 501 (Not Implemented) status code indicates that the server does not support the
-    /// functionality required to fulfill the request. This is the appropriate response when the
-    /// server does not recognize the request method and is not capable of supporting it for any
-    /// resource.
-    /// 
-    /// A 501 response is cacheable by default; i.e., unless otherwise indicated by the method
-    /// definition or explicit cache controls (see 
-    /// [Section 4.2.2 of \[RFC7234\]](http://tools.ietf.org/html/rfc7234#section-4.2.2)).
-	NotImplemented,
-	/// 502 - Bad Gateway (http://tools.ietf.org/html/rfc7231#section-6.6.3)
-    /// 
-    /// The 502 (Bad Gateway) status code indicates that the server, while acting as a gateway or
-    /// or proxy, received an invalid response from an inbound server it accessed while attempting
-    /// to fulfill the request.
-	BadGateway,
-	/// 503 - Service Unavailable (http://tools.ietf.org/html/rfc7231#section-6.6.4)
-    ///
-    /// The 503 (Service Unvailable) status code indicates that the server is currently unable to
-    /// handle the request due to a temporary overload or scheduled maintenance, which will likely
-    /// be alleviated after some delay. The server MAY send a Retry-After header field 
-    /// ([Section 7.1.3 of \[RFC7231\]](http://tools.ietf.org/html/rfc7231#section-7.1.3)) to
-    /// suggest an appropriate amount of time for the client to wait before retrying the request.
-    /// 
-    /// > Note: The existence of teh 503 status code does not imply that a server has to use it when
-    /// > becoming overloaded. Some servers might simply refuse the connection.
-	ServiceUnavailable,
-	/// 504 - Gateway Timeout (http://tools.ietf.org/html/rfc7231#section-6.6.5)
-    ///
-    /// The 504 (Gateway Timeout) status code indicates that the server, while acting as a gateway
-    /// or proxy, did not receive a timely response from an upstream server it needed to access in
-    /// order to complete the request.
-	GatewayTimeout,
-	/// 505 - HTTP Version Not Supported (http://tools.ietf.org/html/rfc7231#section-6.6.6)
-    /// 
-    /// The 505 (HTTP Version Not Supported) status code indicates that the server does not support,
-    /// or refuses to support, the major version of HTTP that was used in the request message. The 
-    /// server is indicating that it is unable or unwilling to complete the request using the same 
-    /// major version as the client, as described in 
-    /// [Section 2.6 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-2.6), other than 
-    /// with this error message. The server SHOULD generate a representation for the 505 response
-    /// that describes why that version is not supported and what other protocals are supported by
-    /// that server.
-	HttpVersionNotSupported,
-	/// HTTP status codes are extensible. HTTP clients are not required to understand the meaning of
-	/// all registered status codes, though such understanding is obviously desirable. However, a
-	/// client MUST understand the class of any status code, as indicated by the first digit, and 
-	/// treat an unrecognized status code as being equivalent to the x00 status code of that class, 
-	/// with the exception that a recipient MUST NOT cache a response with an unrecognized status 
-    /// code.
-	///
-	/// For example, if an unrecognized status code of 471 is received by a client, the client can
-	/// assume that there was something wrong with its request and treat the response as if it had
-	/// received a 400 (Bad Request) status code. The response message will usually contain a
-	/// representation that explains the status.
-	Extension(u16, String)
+/// The invocation of this macro is generated by build.rs from `statuscodes/registry.csv` and the
+/// documentation in `statuscodes/*.md`. The documentation of the `Extension` variant comes first,
+/// followed by one entry per registered code:
+///
+/// ```c
+/// #[doc = "..."]
+/// Variant = code, "Reason Phrase", "reference", cacheable;
+/// ```
+macro_rules! status_codes {
+	(
+		$(#[doc = $ext_doc:literal])*
+		Extension;
+		$(
+			$(#[doc = $doc:literal])*
+			$variant:ident = $code:literal, $phrase:literal, $reference:literal, $cacheable:literal;
+		)+
+	) => {
+		/// A StatusCode represents a HTTP response status code.
+		///
+		/// The status-code element is a three-digit integer code giving the result of the attempt
+		/// to understand and satisfy the request.
+		///
+		/// StatusCode is `Copy`, so it can be passed around and inspected by value. Status codes
+		/// are ordered by their numeric value.
+		#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+		pub enum StatusCode {
+			$($(#[doc = $doc])* $variant,)+
+			$(#[doc = $ext_doc])*
+			Extension(u16, &'static str),
+		}
+
+		impl StatusCode {
+			/// Creates a status code from its numeric value.
+			///
+			/// Values that are not registered codes, including values outside of 100-999, become
+			/// an `Extension` code without a reason phrase. Use `try_from_u16` for codes received
+			/// from a peer.
+			pub fn from_u16(n: u16) -> StatusCode {
+				match n {
+					$($code => StatusCode::$variant,)+
+					_ => StatusCode::Extension(n, ""),
+				}
+			}
+
+			/// Returns the numeric value of this status code.
+			pub fn to_u16(self) -> u16 {
+				match self {
+					$(StatusCode::$variant => $code,)+
+					StatusCode::Extension(code, _) => code,
+				}
+			}
+
+			/// Returns the reason phrase for this status code.
+			///
+			/// Registered status codes return the phrase recommended by their defining
+			/// specification. Extension codes return the phrase they were created with, which may
+			/// be empty.
+			pub fn reason_phrase(self) -> &'static str {
+				match self {
+					$(StatusCode::$variant => $phrase,)+
+					StatusCode::Extension(_, phrase) => phrase,
+				}
+			}
+
+			/// Returns the URL of the section of the specification that defines this status code,
+			/// or `None` for extension codes.
+			pub fn reference(self) -> Option<&'static str> {
+				match self {
+					$(StatusCode::$variant => Some($reference),)+
+					StatusCode::Extension(..) => None,
+				}
+			}
+
+			/// Returns the documentation of this status code, in Markdown.
+			///
+			/// For registered codes, the first line names the status code and links to its
+			/// definition; it is followed by the relevant text of the defining specification.
+			pub fn documentation(self) -> &'static str {
+				match self {
+					$(StatusCode::$variant => concat!($($doc, "\n"),*),)+
+					StatusCode::Extension(..) => concat!($($ext_doc, "\n"),*),
+				}
+			}
+
+			/// Returns true if a response with this status code can be reused by a cache with
+			/// heuristic expiration, i.e. unless otherwise indicated by the method definition or
+			/// explicit cache controls
+			/// ([Section 6.1 of \[RFC7231\]](http://tools.ietf.org/html/rfc7231#section-6.1)).
+			/// Unrecognized status codes are never cacheable.
+			///
+			/// Besides the codes listed by RFC 7231, 308 (Permanent Redirect) and 451 (Unavailable
+			/// For Legal Reasons) are defined as cacheable by default by their own specifications.
+			pub fn is_cacheable_by_default(self) -> bool {
+				match self {
+					$(StatusCode::$variant => $cacheable,)+
+					StatusCode::Extension(..) => false,
+				}
+			}
+		}
+	}
 }
 
-#[derive(PartialEq, Debug)]
+include!(concat!(env!("OUT_DIR"), "/statuscodes.rs"));
+
+impl PartialOrd for StatusCode {
+	fn partial_cmp(&self, other: &StatusCode) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for StatusCode {
+	fn cmp(&self, other: &StatusCode) -> Ordering {
+		self.sort_key().cmp(&other.sort_key())
+	}
+}
+
+impl fmt::Display for StatusCode {
+	/// Formats the status code as it appears in a status-line, e.g. "404 Not Found".
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let phrase = self.reason_phrase();
+		if phrase.is_empty() {
+			write!(f, "{}", self.to_u16())
+		} else {
+			write!(f, "{} {}", self.to_u16(), phrase)
+		}
+	}
+}
+
+/// An error returned when a value is not a valid three-digit status code.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct InvalidStatusCode {
+	code: u16,
+}
+
+impl InvalidStatusCode {
+	/// Returns the value that was rejected.
+	pub fn code(&self) -> u16 {
+		self.code
+	}
+}
+
+impl fmt::Display for InvalidStatusCode {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "invalid status code {}: not in the range 100-999", self.code)
+	}
+}
+
+impl Error for InvalidStatusCode {}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
 pub enum StatusCodeClass {
 	/// 1xx - Informational (http://tools.ietf.org/html/rfc7231#section-6.2)
 	///
@@ -605,114 +239,213 @@ pub enum StatusCodeClass {
 }
 
 impl StatusCode {
-	pub fn from_u16(n: u16) -> StatusCode {
-		use statuscodes::StatusCode::*;
-
+	/// Creates a status code from its numeric value, rejecting values that are not a three-digit
+	/// integer code.
+	///
+	/// Unlike `from_u16`, which accepts any value, this is intended for codes received from a
+	/// peer, where a value outside of 100-999 indicates a broken or malicious message.
+	pub fn try_from_u16(n: u16) -> Result<StatusCode, InvalidStatusCode> {
 		match n {
-			100 => Continue,
-			101 => SwitchingProtocols,
-			200 => Ok,
-			201 => Created,
-			202 => Accepted,
-			203 => NonAuthoritativeInformation,
-			204 => NoContent,
-			205 => ResetContent,
-			206 => PartialContent,
-			300 => MultipleChoices,
-			301 => MovedPermanently,
-			302 => Found,
-			303 => SeeOther,
-			304 => NotModified,
-			305 => UseProxy,
-			307 => TemporaryRedirect,
-			400 => BadRequest,
-			401 => Unauthorized,
-			402 => PaymentRequired,
-			403 => Forbidden,
-			404 => NotFound,
-			405 => MethodNotAllowed,
-			406 => NotAcceptable,
-			407 => ProxyAuthenticationRequired,
-			408 => RequestTimeout,
-			409 => Conflict,
-			410 => Gone,
-			411 => LengthRequired,
-			412 => PreconditionFailed,
-			413 => PayloadTooLarge,
-			414 => UriTooLong,
-			415 => UnsupportedMediaType,
-			416 => RangeNotSatisfiable,
-			417 => ExpectationFailed,
-			426 => UpgradeRequired,
-			500 => InternalServerError,
-			501 => NotImplemented,
-			502 => BadGateway,
-			503 => ServiceUnavailable,
-			504 => GatewayTimeout,
-			505 => HttpVersionNotSupported,
-			_ => Extension(n, "Unknown extension code".to_string())
-		}
-	}
-
-	pub fn to_u16(self) -> u16 {
-		use statuscodes::StatusCode::*;
+			100..=999 => Ok(StatusCode::from_u16(n)),
+			_ => Err(InvalidStatusCode { code: n }),
+		}
+	}
 
-		match self {
-			Continue => 100,
-			SwitchingProtocols => 101,
-			Ok => 200,
-			Created => 201,
-			Accepted => 202,
-			NonAuthoritativeInformation => 203,
-			NoContent => 204,
-			ResetContent => 205,
-			PartialContent => 206,
-			MultipleChoices => 300,
-			MovedPermanently => 301,
-			Found => 302,
-			SeeOther => 303,
-			NotModified => 304,
-			UseProxy => 305,
-			TemporaryRedirect => 307,
-			BadRequest => 400,
-			Unauthorized => 401,
-			PaymentRequired => 402,
-			Forbidden => 403,
-			NotFound => 404,
-			MethodNotAllowed => 405,
-			NotAcceptable => 406,
-			ProxyAuthenticationRequired => 407,
-			RequestTimeout => 408,
-			Conflict => 409,
-			Gone => 410,
-			LengthRequired => 411,
-			PreconditionFailed => 412,
-			PayloadTooLarge => 413,
-			UriTooLong => 414,
-			UnsupportedMediaType => 415,
-			RangeNotSatisfiable => 416,
-			ExpectationFailed => 417,
-			UpgradeRequired => 426,
-			InternalServerError => 500,
-			NotImplemented => 501,
-			BadGateway => 502,
-			ServiceUnavailable => 503,
-			GatewayTimeout => 504,
-			HttpVersionNotSupported => 505,
-			Extension(code, _) => code,
+	/// Creates a status code from its numeric value, attaching `phrase` as the reason phrase if
+	/// the code is not one of the registered codes.
+	///
+	/// The phrase is ignored for registered codes, which always use their canonical phrase. A
+	/// phrase that is not a valid reason-phrase, e.g. one containing CR or LF, is replaced by an
+	/// empty phrase, so that it cannot end the status-line early when the code is sent.
+	pub fn from_u16_with_phrase(n: u16, phrase: &'static str) -> StatusCode {
+		match StatusCode::from_u16(n) {
+			StatusCode::Extension(code, _) if is_reason_phrase(phrase) => {
+				StatusCode::Extension(code, phrase)
+			}
+			StatusCode::Extension(code, _) => StatusCode::Extension(code, ""),
+			status => status,
 		}
 	}
 
+	/// The key status codes are ordered by: the numeric value first, with registered codes
+	/// ahead of an extension that reuses their value, so that the order agrees with `Eq`.
+	fn sort_key(self) -> (u16, bool, &'static str) {
+		let is_extension = matches!(self, StatusCode::Extension(..));
+		(self.to_u16(), is_extension, self.reason_phrase())
+	}
+
 	pub fn class(self) -> Option<StatusCodeClass> {
 		match self.to_u16() {
-			100 ... 199 => Some(StatusCodeClass::Informational),
-			200 ... 299 => Some(StatusCodeClass::Successful),
-			300 ... 399 => Some(StatusCodeClass::Redirection),
-			400 ... 499 => Some(StatusCodeClass::ClientError),
-			500 ... 599 => Some(StatusCodeClass::ServerError),
+			100..=199 => Some(StatusCodeClass::Informational),
+			200..=299 => Some(StatusCodeClass::Successful),
+			300..=399 => Some(StatusCodeClass::Redirection),
+			400..=499 => Some(StatusCodeClass::ClientError),
+			500..=599 => Some(StatusCodeClass::ServerError),
 			_ => None,
 		}
 	}
+
+	/// Returns false for `Extension` status codes, i.e. codes that this implementation does not
+	/// understand.
+	///
+	/// A recipient MUST NOT cache a response with an unrecognized status code
+	/// ([Section 6 of \[RFC7231\]](http://tools.ietf.org/html/rfc7231#section-6)), so caching
+	/// layers should check this on the original status code, not on the one returned by
+	/// `effective`.
+	pub fn is_recognized(self) -> bool {
+		!matches!(self, StatusCode::Extension(..))
+	}
+
+	/// Returns the status code a client should act upon.
+	///
+	/// An unrecognized status code is treated as being equivalent to the x00 status code of its
+	/// class ([Section 6 of \[RFC7231\]](http://tools.ietf.org/html/rfc7231#section-6)), so
+	/// `Extension(471, _)` is handled as `BadRequest`. Recognized codes, and extension codes
+	/// without a class, are returned unchanged.
+	pub fn effective(self) -> StatusCode {
+		match (self, self.class()) {
+			(StatusCode::Extension(..), Some(class)) => class.canonical_code(),
+			_ => self,
+		}
+	}
+
+	/// Returns true for 1xx (Informational) status codes, which are interim responses sent prior
+	/// to a final response
+	/// ([Section 6.2 of \[RFC7231\]](http://tools.ietf.org/html/rfc7231#section-6.2)).
+	pub fn is_informational(self) -> bool {
+		self.class() == Some(StatusCodeClass::Informational)
+	}
+
+	/// Returns true for status codes that complete a request, i.e. any code in the 2xx to 5xx
+	/// classes.
+	pub fn is_final(self) -> bool {
+		match self.class() {
+			Some(StatusCodeClass::Informational) | None => false,
+			Some(_) => true,
+		}
+	}
+
+	/// Returns true if a response with this status code is allowed to carry a payload body.
+	///
+	/// 1xx, 204 (No Content) and 304 (Not Modified) responses never contain a message body
+	/// ([Section 3.3 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-3.3)), and a
+	/// server MUST NOT generate a payload in a 205 (Reset Content) response
+	/// ([Section 6.3.6 of \[RFC7231\]](http://tools.ietf.org/html/rfc7231#section-6.3.6)).
+	/// Whether a body is present also depends on the request method, which is not considered here.
+	pub fn permits_body(self) -> bool {
+		use statuscodes::StatusCode::*;
+
+		match self {
+			NoContent | ResetContent | NotModified => false,
+			status => !status.is_informational(),
+		}
+	}
+
+	/// Returns true for redirects where the user agent MUST NOT change the request method when
+	/// following them: 307 (Temporary Redirect)
+	/// ([Section 6.4.7 of \[RFC7231\]](http://tools.ietf.org/html/rfc7231#section-6.4.7)) and
+	/// 308 (Permanent Redirect)
+	/// ([Section 3 of \[RFC7538\]](http://tools.ietf.org/html/rfc7538#section-3)).
+	pub fn is_redirect_preserving_method(self) -> bool {
+		use statuscodes::StatusCode::*;
+
+		matches!(self, TemporaryRedirect | PermanentRedirect)
+	}
+
+	/// Returns true if the defining specification allows the client to repeat the same request
+	/// later, or on another connection, without modifying it:
+	///
+	/// * 408 (Request Timeout) - the client MAY repeat the request on a new connection;
+	/// * 421 (Misdirected Request) - the client MAY retry over a different connection;
+	/// * 425 (Too Early) - the user agent is expected to retry outside of early data;
+	/// * 429 (Too Many Requests) - the client may retry after the rate limit has passed;
+	/// * 503 (Service Unavailable) - the condition is temporary.
+	///
+	/// Callers remain responsible for respecting any Retry-After header field and the
+	/// idempotency of the request method.
+	pub fn is_retryable(self) -> bool {
+		use statuscodes::StatusCode::*;
+
+		matches!(self, RequestTimeout | MisdirectedRequest | TooEarly | TooManyRequests |
+			ServiceUnavailable)
+	}
+}
+
+impl StatusCodeClass {
+	/// Returns the name of this class, e.g. "Client Error".
+	pub fn name(self) -> &'static str {
+		match self {
+			StatusCodeClass::Informational => "Informational",
+			StatusCodeClass::Successful => "Successful",
+			StatusCodeClass::Redirection => "Redirection",
+			StatusCodeClass::ClientError => "Client Error",
+			StatusCodeClass::ServerError => "Server Error",
+		}
+	}
+
+	/// Returns the x00 status code of this class, which is what an unrecognized status code of the
+	/// class is treated as being equivalent to.
+	pub fn canonical_code(self) -> StatusCode {
+		match self {
+			StatusCodeClass::Informational => StatusCode::Continue,
+			StatusCodeClass::Successful => StatusCode::Ok,
+			StatusCodeClass::Redirection => StatusCode::MultipleChoices,
+			StatusCodeClass::ClientError => StatusCode::BadRequest,
+			StatusCodeClass::ServerError => StatusCode::InternalServerError,
+		}
+	}
+}
+
+impl fmt::Display for StatusCodeClass {
+	/// Formats the class by its first digit, e.g. "4xx".
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let digit = match *self {
+			StatusCodeClass::Informational => 1,
+			StatusCodeClass::Successful => 2,
+			StatusCodeClass::Redirection => 3,
+			StatusCodeClass::ClientError => 4,
+			StatusCodeClass::ServerError => 5,
+		};
+		write!(f, "{}xx", digit)
+	}
+}
+
+impl FromStr for StatusCodeClass {
+	type Err = InvalidStatusCodeClass;
+
+	/// Parses a class from the form used by `Display`, e.g. "4xx".
+	fn from_str(s: &str) -> Result<StatusCodeClass, InvalidStatusCodeClass> {
+		match s {
+			"1xx" => Ok(StatusCodeClass::Informational),
+			"2xx" => Ok(StatusCodeClass::Successful),
+			"3xx" => Ok(StatusCodeClass::Redirection),
+			"4xx" => Ok(StatusCodeClass::ClientError),
+			"5xx" => Ok(StatusCodeClass::ServerError),
+			_ => Err(InvalidStatusCodeClass),
+		}
+	}
+}
+
+/// An error returned when a string is not one of "1xx" to "5xx".
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct InvalidStatusCodeClass;
+
+impl fmt::Display for InvalidStatusCodeClass {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("invalid status code class: expected one of 1xx, 2xx, 3xx, 4xx or 5xx")
+	}
+}
+
+impl Error for InvalidStatusCodeClass {}
+
+/// Returns true if `phrase` may be sent as the reason phrase of a status-line
+/// ([Section 3.1.2 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-3.1.2)).
+///
+/// ```c
+/// reason-phrase = *( HTAB / SP / VCHAR / obs-text )
+/// ```
+fn is_reason_phrase(phrase: &str) -> bool {
+	phrase.bytes().all(|b| b == b'\t' || b == b' ' || (0x21..=0x7e).contains(&b) || b >= 0x80)
 }
 
 #[cfg(test)]
@@ -725,14 +458,83 @@ mod test {
 		let test_cases = vec![
 			(100, StatusCode::Continue),
 			(101, StatusCode::SwitchingProtocols),
+			(102, StatusCode::Processing),
+			(103, StatusCode::EarlyHints),
 			(200, StatusCode::Ok),
 			(201, StatusCode::Created),
 			(202, StatusCode::Accepted),
+			(203, StatusCode::NonAuthoritativeInformation),
+			(204, StatusCode::NoContent),
+			(205, StatusCode::ResetContent),
+			(206, StatusCode::PartialContent),
+			(207, StatusCode::MultiStatus),
+			(208, StatusCode::AlreadyReported),
+			(226, StatusCode::ImUsed),
+			(300, StatusCode::MultipleChoices),
+			(301, StatusCode::MovedPermanently),
+			(302, StatusCode::Found),
+			(303, StatusCode::SeeOther),
+			(304, StatusCode::NotModified),
+			(305, StatusCode::UseProxy),
+			(307, StatusCode::TemporaryRedirect),
+			(308, StatusCode::PermanentRedirect),
+			(400, StatusCode::BadRequest),
+			(401, StatusCode::Unauthorized),
+			(402, StatusCode::PaymentRequired),
+			(403, StatusCode::Forbidden),
+			(404, StatusCode::NotFound),
+			(405, StatusCode::MethodNotAllowed),
+			(406, StatusCode::NotAcceptable),
+			(407, StatusCode::ProxyAuthenticationRequired),
+			(408, StatusCode::RequestTimeout),
+			(409, StatusCode::Conflict),
+			(410, StatusCode::Gone),
+			(411, StatusCode::LengthRequired),
+			(412, StatusCode::PreconditionFailed),
+			(413, StatusCode::PayloadTooLarge),
+			(414, StatusCode::UriTooLong),
+			(415, StatusCode::UnsupportedMediaType),
+			(416, StatusCode::RangeNotSatisfiable),
+			(417, StatusCode::ExpectationFailed),
+			(421, StatusCode::MisdirectedRequest),
+			(422, StatusCode::UnprocessableEntity),
+			(423, StatusCode::Locked),
+			(424, StatusCode::FailedDependency),
+			(425, StatusCode::TooEarly),
+			(426, StatusCode::UpgradeRequired),
+			(428, StatusCode::PreconditionRequired),
+			(429, StatusCode::TooManyRequests),
+			(431, StatusCode::RequestHeaderFieldsTooLarge),
+			(451, StatusCode::UnavailableForLegalReasons),
+			(500, StatusCode::InternalServerError),
+			(501, StatusCode::NotImplemented),
+			(502, StatusCode::BadGateway),
+			(503, StatusCode::ServiceUnavailable),
+			(504, StatusCode::GatewayTimeout),
+			(505, StatusCode::HttpVersionNotSupported),
+			(506, StatusCode::VariantAlsoNegotiates),
+			(507, StatusCode::InsufficientStorage),
+			(508, StatusCode::LoopDetected),
+			(510, StatusCode::NotExtended),
+			(511, StatusCode::NetworkAuthenticationRequired),
 		];
 
 		for (n, expect) in test_cases {
 			let actual = StatusCode::from_u16(n);
 			assert_eq!(expect, actual);
+			assert_eq!(n, actual.to_u16());
+		}
+	}
+
+	#[test]
+	fn statuscode_from_u16_unregistered_is_extension() {
+		use super::StatusCode;
+
+		for n in [199, 209, 306, 418, 420, 430, 471, 509, 599] {
+			match StatusCode::from_u16(n) {
+				StatusCode::Extension(code, _) => assert_eq!(n, code),
+				other => panic!("{} should be an extension code, got {:?}", n, other),
+			}
 		}
 	}
 
@@ -780,10 +582,313 @@ mod test {
 			assert_eq!(expect, actual);
 		}
 
-		for n in vec![0, 10, 600] {
+		for n in [0, 10, 600] {
 			let expect = None;
 			let actual = StatusCode::from_u16(n).class();
 			assert_eq!(expect, actual);
 		}
 	}
+
+	#[test]
+	fn statuscode_reason_phrase_test() {
+		use super::StatusCode;
+
+		let test_cases = vec![
+			(100, "Continue"),
+			(200, "OK"),
+			(203, "Non-Authoritative Information"),
+			(226, "IM Used"),
+			(404, "Not Found"),
+			(414, "URI Too Long"),
+			(429, "Too Many Requests"),
+			(505, "HTTP Version Not Supported"),
+			(511, "Network Authentication Required"),
+		];
+
+		for (n, expect) in test_cases {
+			let actual = StatusCode::from_u16(n);
+			assert_eq!(expect, actual.reason_phrase());
+		}
+
+		for n in 100..600 {
+			let status = StatusCode::from_u16(n);
+			match status {
+				StatusCode::Extension(..) => assert_eq!("", status.reason_phrase()),
+				_ => assert!(!status.reason_phrase().is_empty()),
+			}
+		}
+	}
+
+	#[test]
+	fn statuscode_extension_carries_phrase() {
+		use super::StatusCode;
+
+		let status = StatusCode::from_u16_with_phrase(471, "Too Many Widgets");
+		assert_eq!(StatusCode::Extension(471, "Too Many Widgets"), status);
+		assert_eq!("Too Many Widgets", status.reason_phrase());
+
+		let status = StatusCode::from_u16_with_phrase(471, "Trop de gadgets\t\u{e9}");
+		assert_eq!("Trop de gadgets\t\u{e9}", status.reason_phrase());
+
+		for phrase in ["Widgets\r\nInjected: 1", "Widgets\n", "\0", "\x7f"] {
+			let status = StatusCode::from_u16_with_phrase(471, phrase);
+			assert_eq!(StatusCode::Extension(471, ""), status, "{:?}", phrase);
+		}
+
+		let status = StatusCode::from_u16_with_phrase(404, "Nope");
+		assert_eq!(StatusCode::NotFound, status);
+		assert_eq!("Not Found", status.reason_phrase());
+	}
+
+	#[test]
+	fn statuscode_display_test() {
+		use super::StatusCode;
+
+		assert_eq!("200 OK", StatusCode::Ok.to_string());
+		assert_eq!("404 Not Found", StatusCode::NotFound.to_string());
+		assert_eq!("471", StatusCode::from_u16(471).to_string());
+		assert_eq!("471 Too Many Widgets",
+			StatusCode::from_u16_with_phrase(471, "Too Many Widgets").to_string());
+	}
+
+	#[test]
+	fn statuscode_try_from_u16_test() {
+		use super::StatusCode;
+
+		for n in [0, 1, 42, 99, 1000, 65535] {
+			let err = StatusCode::try_from_u16(n).unwrap_err();
+			assert_eq!(n, err.code());
+		}
+
+		for n in [100, 404, 471, 599, 600, 999] {
+			assert_eq!(StatusCode::from_u16(n), StatusCode::try_from_u16(n).unwrap());
+		}
+	}
+
+	#[test]
+	fn statuscode_is_copy_test() {
+		use super::{StatusCode, StatusCodeClass};
+
+		let status = StatusCode::TooManyRequests;
+		assert_eq!(Some(StatusCodeClass::ClientError), status.class());
+		assert_eq!(429, status.to_u16());
+		assert_eq!("Too Many Requests", status.reason_phrase());
+		assert_eq!(StatusCode::TooManyRequests, status);
+	}
+
+	#[test]
+	fn statuscode_ord_test() {
+		use super::StatusCode;
+
+		let mut actual = vec![
+			StatusCode::from_u16(471),
+			StatusCode::NotFound,
+			StatusCode::Extension(404, "Not Found"),
+			StatusCode::Continue,
+			StatusCode::InternalServerError,
+			StatusCode::PermanentRedirect,
+		];
+		actual.sort();
+
+		let expect = vec![
+			StatusCode::Continue,
+			StatusCode::PermanentRedirect,
+			StatusCode::NotFound,
+			StatusCode::Extension(404, "Not Found"),
+			StatusCode::from_u16(471),
+			StatusCode::InternalServerError,
+		];
+		assert_eq!(expect, actual);
+
+		for n in 100..599 {
+			assert!(StatusCode::from_u16(n) < StatusCode::from_u16(n + 1));
+		}
+	}
+
+	#[test]
+	fn statuscode_hash_test() {
+		use std::collections::HashSet;
+		use super::StatusCode;
+
+		let codes: HashSet<StatusCode> = (100..600).map(StatusCode::from_u16).collect();
+		assert_eq!(500, codes.len());
+		assert!(codes.contains(&StatusCode::TooManyRequests));
+		assert!(codes.contains(&StatusCode::from_u16(471)));
+		assert!(!codes.contains(&StatusCode::from_u16_with_phrase(471, "Custom")));
+	}
+
+	#[test]
+	fn statuscode_is_informational_test() {
+		use super::StatusCode;
+
+		// RFC 7231, Section 6.2: 1xx responses are interim responses.
+		for n in 100..600 {
+			let status = StatusCode::from_u16(n);
+			assert_eq!(n < 200, status.is_informational());
+			assert_eq!(n >= 200, status.is_final());
+		}
+
+		for n in [0, 99, 600, 999] {
+			let status = StatusCode::from_u16(n);
+			assert!(!status.is_informational());
+			assert!(!status.is_final());
+		}
+	}
+
+	#[test]
+	fn statuscode_permits_body_test() {
+		use super::StatusCode;
+
+		// RFC 7230, Section 3.3: 1xx, 204 and 304 responses never contain a message body.
+		// RFC 7231, Section 6.3.6: a server MUST NOT generate a payload in a 205 response.
+		let no_body = [100, 101, 102, 103, 204, 205, 304];
+		for n in 100..600 {
+			let expect = !no_body.contains(&n) && n >= 200;
+			assert_eq!(expect, StatusCode::from_u16(n).permits_body(), "{}", n);
+		}
+	}
+
+	#[test]
+	fn statuscode_is_cacheable_by_default_test() {
+		use super::StatusCode;
+
+		// RFC 7231, Section 6.1, plus RFC 7538, Section 3 (308) and RFC 7725, Section 3 (451).
+		let cacheable = [200, 203, 204, 206, 300, 301, 308, 404, 405, 410, 414, 451, 501];
+		for n in 100..600 {
+			let expect = cacheable.contains(&n);
+			assert_eq!(expect, StatusCode::from_u16(n).is_cacheable_by_default(), "{}", n);
+		}
+	}
+
+	#[test]
+	fn statuscode_is_redirect_preserving_method_test() {
+		use super::StatusCode;
+
+		// RFC 7231, Section 6.4.7 (307) and RFC 7538, Section 3 (308). 301 and 302 allow
+		// rewriting POST to GET (RFC 7231, Sections 6.4.2 and 6.4.3), 303 redirects to GET.
+		for n in 100..600 {
+			let expect = n == 307 || n == 308;
+			assert_eq!(expect, StatusCode::from_u16(n).is_redirect_preserving_method(), "{}", n);
+		}
+	}
+
+	#[test]
+	fn statuscode_is_retryable_test() {
+		use super::StatusCode;
+
+		// RFC 7231, Section 6.5.7 (408) and Section 6.6.4 (503), RFC 7540, Section 9.1.2 (421),
+		// RFC 8470, Section 5.2 (425) and RFC 6585, Section 4 (429).
+		let retryable = [408, 421, 425, 429, 503];
+		for n in 100..600 {
+			let expect = retryable.contains(&n);
+			assert_eq!(expect, StatusCode::from_u16(n).is_retryable(), "{}", n);
+		}
+	}
+
+	#[test]
+	fn statuscodeclass_canonical_code_test() {
+		use super::{StatusCode, StatusCodeClass};
+
+		let test_cases = vec![
+			(StatusCodeClass::Informational, StatusCode::Continue),
+			(StatusCodeClass::Successful, StatusCode::Ok),
+			(StatusCodeClass::Redirection, StatusCode::MultipleChoices),
+			(StatusCodeClass::ClientError, StatusCode::BadRequest),
+			(StatusCodeClass::ServerError, StatusCode::InternalServerError),
+		];
+
+		for (class, expect) in test_cases {
+			let actual = class.canonical_code();
+			assert_eq!(expect, actual);
+			assert_eq!(Some(class), actual.class());
+			assert_eq!(0, actual.to_u16() % 100);
+		}
+	}
+
+	#[test]
+	fn statuscode_effective_test() {
+		use super::StatusCode;
+
+		let test_cases = vec![
+			(StatusCode::from_u16(471), StatusCode::BadRequest),
+			(StatusCode::from_u16_with_phrase(471, "Custom"), StatusCode::BadRequest),
+			(StatusCode::from_u16(199), StatusCode::Continue),
+			(StatusCode::from_u16(299), StatusCode::Ok),
+			(StatusCode::from_u16(399), StatusCode::MultipleChoices),
+			(StatusCode::from_u16(599), StatusCode::InternalServerError),
+			(StatusCode::from_u16(600), StatusCode::from_u16(600)),
+			(StatusCode::TooManyRequests, StatusCode::TooManyRequests),
+			(StatusCode::NotFound, StatusCode::NotFound),
+		];
+
+		for (status, expect) in test_cases {
+			assert_eq!(expect, status.effective());
+		}
+	}
+
+	#[test]
+	fn statuscode_is_recognized_test() {
+		use super::StatusCode;
+
+		assert!(StatusCode::TooManyRequests.is_recognized());
+		assert!(!StatusCode::from_u16(471).is_recognized());
+
+		// An unrecognized 299 behaves like 200, but MUST NOT be cached.
+		let status = StatusCode::from_u16(299);
+		assert!(status.effective().is_cacheable_by_default());
+		assert!(!status.is_recognized());
+		assert!(!status.is_cacheable_by_default());
+	}
+
+	#[test]
+	fn statuscode_documentation_test() {
+		use super::StatusCode;
+
+		let docs = StatusCode::PreconditionFailed.documentation();
+		assert!(docs.starts_with("412 - Precondition Failed ("));
+
+		let docs = StatusCode::Continue.documentation();
+		assert!(docs.contains("100-continue expectation"));
+
+		let docs = StatusCode::from_u16(471).documentation();
+		assert!(docs.contains("HTTP status codes are extensible."));
+	}
+
+	#[test]
+	fn statuscode_reference_test() {
+		use super::StatusCode;
+
+		assert_eq!(Some("http://tools.ietf.org/html/rfc7232#section-4.2"),
+			StatusCode::PreconditionFailed.reference());
+		assert_eq!(Some("http://tools.ietf.org/html/rfc6585#section-4"),
+			StatusCode::TooManyRequests.reference());
+		assert_eq!(None, StatusCode::from_u16(471).reference());
+
+		for n in 100..600 {
+			let status = StatusCode::from_u16(n);
+			assert_eq!(status.is_recognized(), status.reference().is_some());
+		}
+	}
+
+	#[test]
+	fn statuscodeclass_display_from_str_test() {
+		use super::StatusCodeClass;
+
+		let test_cases = vec![
+			(StatusCodeClass::Informational, "1xx"),
+			(StatusCodeClass::Successful, "2xx"),
+			(StatusCodeClass::Redirection, "3xx"),
+			(StatusCodeClass::ClientError, "4xx"),
+			(StatusCodeClass::ServerError, "5xx"),
+		];
+
+		for (class, s) in test_cases {
+			assert_eq!(s, class.to_string());
+			assert_eq!(Ok(class), s.parse());
+		}
+
+		for s in ["", "4", "6xx", "4XX", "400"] {
+			assert!(s.parse::<StatusCodeClass>().is_err(), "{}", s);
+		}
+	}
 }
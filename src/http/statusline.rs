@@ -0,0 +1,190 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use statuscodes::{InvalidStatusCode, StatusCode};
+use version::{HttpVersion, HttpVersionError};
+
+/// A StatusLine represents the first line of a HTTP response message.
+///
+/// The first line of a response message is the status-line, consisting of the protocol version, a
+/// space (SP), the status code, another space, a possibly empty textual phrase describing the
+/// status code, and ending with CRLF
+/// ([Section 3.1.2 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-3.1.2)).
+///
+/// ```c
+/// status-line = HTTP-version SP status-code SP reason-phrase CRLF
+/// ```
+#[derive(PartialEq, Debug)]
+pub struct StatusLine {
+	/// The protocol version of the response.
+	pub version: HttpVersion,
+	/// The status code of the response.
+	pub status: StatusCode,
+	/// The reason phrase exactly as it was received.
+	///
+	/// A client SHOULD ignore the reason-phrase content, so it is kept as raw bytes; it may
+	/// contain obs-text that is not valid UTF-8.
+	pub reason: Vec<u8>,
+}
+
+impl StatusLine {
+	/// Parses a status-line from bytes.
+	///
+	/// The trailing CRLF is optional, so both a complete line as read from the wire and a line
+	/// that has already been split off from the rest of the message are accepted.
+	pub fn parse(line: &[u8]) -> Result<StatusLine, StatusLineError> {
+		let line = strip_line_ending(line);
+
+		if line.len() < 12 || line[8] != b' ' {
+			return Err(StatusLineError::Malformed);
+		}
+
+		let version = HttpVersion::parse(&line[..8])?;
+		let status = parse_status_code(&line[9..12])?;
+
+		let reason = match line.get(12) {
+			None => &line[12..],
+			Some(&b' ') => &line[13..],
+			Some(_) => return Err(StatusLineError::Malformed),
+		};
+		if !reason.iter().all(|&b| is_reason_phrase_byte(b)) {
+			return Err(StatusLineError::InvalidReasonPhrase);
+		}
+
+		Ok(StatusLine {
+			version,
+			status,
+			reason: reason.to_vec(),
+		})
+	}
+}
+
+impl FromStr for StatusLine {
+	type Err = StatusLineError;
+
+	fn from_str(s: &str) -> Result<StatusLine, StatusLineError> {
+		StatusLine::parse(s.as_bytes())
+	}
+}
+
+/// An error returned when a status-line cannot be parsed.
+#[derive(PartialEq, Debug)]
+pub enum StatusLineError {
+	/// The line does not have the `HTTP-version SP status-code SP reason-phrase` structure.
+	Malformed,
+	/// The protocol version is not of the form "HTTP/x.y", or has an unknown major version.
+	InvalidVersion,
+	/// The status code is not three digits, or is not in the range 100-999.
+	InvalidStatusCode,
+	/// The reason phrase contains a control character.
+	InvalidReasonPhrase,
+}
+
+impl fmt::Display for StatusLineError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let description = match *self {
+			StatusLineError::Malformed => "malformed status-line",
+			StatusLineError::InvalidVersion => "invalid HTTP-version in status-line",
+			StatusLineError::InvalidStatusCode => "invalid status-code in status-line",
+			StatusLineError::InvalidReasonPhrase => "invalid reason-phrase in status-line",
+		};
+		f.write_str(description)
+	}
+}
+
+impl Error for StatusLineError {}
+
+impl From<InvalidStatusCode> for StatusLineError {
+	fn from(_: InvalidStatusCode) -> StatusLineError {
+		StatusLineError::InvalidStatusCode
+	}
+}
+
+impl From<HttpVersionError> for StatusLineError {
+	fn from(_: HttpVersionError) -> StatusLineError {
+		StatusLineError::InvalidVersion
+	}
+}
+
+fn strip_line_ending(line: &[u8]) -> &[u8] {
+	let line = line.strip_suffix(b"\n").unwrap_or(line);
+	line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+fn parse_status_code(digits: &[u8]) -> Result<StatusCode, StatusLineError> {
+	if !digits.iter().all(u8::is_ascii_digit) {
+		return Err(StatusLineError::InvalidStatusCode);
+	}
+
+	let n = digits.iter().fold(0, |n, &d| n * 10 + (d - b'0') as u16);
+	Ok(StatusCode::try_from_u16(n)?)
+}
+
+/// reason-phrase = *( HTAB / SP / VCHAR / obs-text )
+fn is_reason_phrase_byte(b: u8) -> bool {
+	b == b'\t' || b == b' ' || (0x21..=0x7e).contains(&b) || b >= 0x80
+}
+
+#[cfg(test)]
+mod test {
+
+	#[test]
+	fn statusline_parse_test() {
+		use super::StatusLine;
+		use statuscodes::StatusCode;
+		use version::HttpVersion::{Http10, Http11};
+
+		let test_cases = vec![
+			("HTTP/1.1 404 Not Found\r\n", Http11, StatusCode::NotFound, "Not Found"),
+			("HTTP/1.0 200 OK", Http10, StatusCode::Ok, "OK"),
+			("HTTP/1.1 200 \r\n", Http11, StatusCode::Ok, ""),
+			("HTTP/1.1 200\n", Http11, StatusCode::Ok, ""),
+			("HTTP/1.1 429 Slow\tDown \r\n", Http11, StatusCode::TooManyRequests, "Slow\tDown "),
+			("HTTP/1.1 471 Custom\r\n", Http11, StatusCode::from_u16(471), "Custom"),
+			("HTTP/1.2 200 OK", Http11, StatusCode::Ok, "OK"),
+		];
+
+		for (line, version, status, reason) in test_cases {
+			let expect = StatusLine {
+				version,
+				status,
+				reason: reason.as_bytes().to_vec(),
+			};
+			assert_eq!(Ok(expect), line.parse::<StatusLine>());
+		}
+	}
+
+	#[test]
+	fn statusline_parse_obs_text_test() {
+		use super::StatusLine;
+
+		let line = StatusLine::parse(b"HTTP/1.1 200 \xe9t\xe9\r\n").unwrap();
+		assert_eq!(b"\xe9t\xe9".to_vec(), line.reason);
+	}
+
+	#[test]
+	fn statusline_parse_error_test() {
+		use super::{StatusLine, StatusLineError};
+
+		let test_cases = vec![
+			("", StatusLineError::Malformed),
+			("HTTP/1.1", StatusLineError::Malformed),
+			("HTTP/1.1  200 OK", StatusLineError::InvalidStatusCode),
+			("HTTP/1.1 2000 OK", StatusLineError::Malformed),
+			("HTTP/1.1\t200 OK", StatusLineError::Malformed),
+			("HTTP/11. 200 OK", StatusLineError::InvalidVersion),
+			("http/1.1 200 OK", StatusLineError::InvalidVersion),
+			("HTTP/4.0 200 OK", StatusLineError::InvalidVersion),
+			("HTTP/1.1 2x0 OK", StatusLineError::InvalidStatusCode),
+			("HTTP/1.1 042 OK", StatusLineError::InvalidStatusCode),
+			("HTTP/1.1 000 OK", StatusLineError::InvalidStatusCode),
+			("HTTP/1.1 200 O\x00K", StatusLineError::InvalidReasonPhrase),
+			("HTTP/1.1 200 OK\r\r\n", StatusLineError::InvalidReasonPhrase),
+		];
+
+		for (line, expect) in test_cases {
+			assert_eq!(Err(expect), StatusLine::parse(line.as_bytes()), "{:?}", line);
+		}
+	}
+}
@@ -0,0 +1,87 @@
+//! Character classes shared by the parsers of the protocol elements
+//! ([Section 3.2.6 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-3.2.6)).
+
+/// Returns true if `b` is a tchar, i.e. a character that may appear in a token.
+///
+/// ```c
+/// tchar = "!" / "#" / "$" / "%" / "&" / "'" / "*" / "+" / "-" / "." /
+///         "^" / "_" / "`" / "|" / "~" / DIGIT / ALPHA
+/// ```
+pub fn is_tchar(b: u8) -> bool {
+	match b {
+		b'!' | b'#' | b'$' | b'%' | b'&' | b'\'' | b'*' | b'+' | b'-' | b'.' | b'^' | b'_' |
+		b'`' | b'|' | b'~' => true,
+		_ => b.is_ascii_alphanumeric(),
+	}
+}
+
+/// Returns true if `s` is a token, i.e. one or more tchars.
+pub fn is_token(s: &[u8]) -> bool {
+	!s.is_empty() && s.iter().all(|&b| is_tchar(b))
+}
+
+/// Returns true if `b` may appear in a field-value
+/// ([Section 3.2 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-3.2)).
+///
+/// ```c
+/// field-vchar = VCHAR / obs-text
+/// ```
+///
+/// Spaces and horizontal tabs are also allowed, as they separate the field-vchars of a value.
+pub fn is_field_value_byte(b: u8) -> bool {
+	b == b'\t' || b == b' ' || (0x21..=0x7e).contains(&b) || b >= 0x80
+}
+
+/// Parses a quoted-string at the start of `s`, returning the unescaped string and the rest of `s`.
+///
+/// ```c
+/// quoted-string = DQUOTE *( qdtext / quoted-pair ) DQUOTE
+/// qdtext        = HTAB / SP /%x21 / %x23-5B / %x5D-7E / obs-text
+/// quoted-pair   = "\" ( HTAB / SP / VCHAR / obs-text )
+/// ```
+pub fn parse_quoted_string(s: &str) -> Option<(String, &str)> {
+	let mut value = String::new();
+	let mut chars = s.char_indices().skip(1);
+	while let Some((i, c)) = chars.next() {
+		let c = match c {
+			'"' => return Some((value, &s[i + 1..])),
+			'\\' => chars.next()?.1,
+			c => c,
+		};
+		if c != '\t' && c.is_ascii_control() {
+			return None;
+		}
+		value.push(c);
+	}
+	None
+}
+
+#[cfg(test)]
+mod test {
+
+	#[test]
+	fn is_token_test() {
+		use super::is_token;
+
+		for s in ["GET", "x-custom", "!#$%&'*+-.^_`|~09azAZ"] {
+			assert!(is_token(s.as_bytes()), "{}", s);
+		}
+
+		for s in ["", "GET ", "a:b", "a/b", "\"quoted\"", "tab\t", "(x)", "caf\u{e9}"] {
+			assert!(!is_token(s.as_bytes()), "{}", s);
+		}
+	}
+
+	#[test]
+	fn is_field_value_byte_test() {
+		use super::is_field_value_byte;
+
+		for &b in b"\t !~\x80\xff" {
+			assert!(is_field_value_byte(b), "{:?}", b);
+		}
+
+		for &b in b"\0\r\n\x08\x1f\x7f" {
+			assert!(!is_field_value_byte(b), "{:?}", b);
+		}
+	}
+}
@@ -0,0 +1,1118 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use date::HttpDate;
+use header::{HeaderName, HeaderValue, InvalidHeaderValue};
+use method::Method;
+use token;
+use uri::{Authority, Uri};
+
+/// A Header is a header field with a typed representation of its value.
+///
+/// `HeaderMap::get_typed` decodes a header from the field lines in a map, and
+/// `HeaderMap::set_typed` encodes it into a single field line.
+pub trait Header: Sized {
+	/// The name of the header field.
+	const NAME: HeaderName;
+
+	/// Parses the values of all field lines with the header's name, in order.
+	///
+	/// `values` is never empty. Returns `None` if the values do not match the header's grammar.
+	fn decode(values: &[&HeaderValue]) -> Option<Self>;
+
+	/// Encodes the header as a single field value.
+	fn encode(&self) -> HeaderValue;
+}
+
+/// An error returned when the value of a header field does not match its grammar.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct InvalidHeader {
+	name: HeaderName,
+}
+
+impl InvalidHeader {
+	/// Creates an error for the header field with the given name.
+	pub fn new(name: HeaderName) -> InvalidHeader {
+		InvalidHeader { name }
+	}
+
+	/// Returns the name of the header field.
+	pub fn name(&self) -> &HeaderName {
+		&self.name
+	}
+}
+
+impl fmt::Display for InvalidHeader {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "invalid {} header", self.name)
+	}
+}
+
+impl Error for InvalidHeader {}
+
+/// Content-Length (http://tools.ietf.org/html/rfc7230#section-3.3.2)
+///
+/// When a message does not have a Transfer-Encoding header field, a Content-Length header field
+/// can provide the anticipated size, as a decimal number of octets, for a potential payload body.
+///
+/// If a message is received that has multiple Content-Length header fields with field-values
+/// consisting of the same decimal value, or a single Content-Length header field with a field
+/// value containing a list of identical decimal values (e.g., "Content-Length: 42, 42"), the
+/// recipient MAY either reject the message as invalid or replace the duplicated field-values with
+/// a single valid Content-Length field containing that decimal value. This implementation does the
+/// latter; differing values are invalid.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ContentLength(pub u64);
+
+impl Header for ContentLength {
+	const NAME: HeaderName = HeaderName::CONTENT_LENGTH;
+
+	fn decode(values: &[&HeaderValue]) -> Option<ContentLength> {
+		let mut length = None;
+		for element in list(values)? {
+			let n = decimal(element)?;
+			if length.is_some_and(|length| length != n) {
+				return None;
+			}
+			length = Some(n);
+		}
+		length.map(ContentLength)
+	}
+
+	fn encode(&self) -> HeaderValue {
+		HeaderValue::from(self.0)
+	}
+}
+
+/// Content-Type (http://tools.ietf.org/html/rfc7231#section-3.1.1.5)
+///
+/// The "Content-Type" header field indicates the media type of the associated representation.
+///
+/// ```c
+/// media-type = type "/" subtype *( OWS ";" OWS parameter )
+/// type       = token
+/// subtype    = token
+/// parameter  = token "=" ( token / quoted-string )
+/// ```
+///
+/// The type, subtype, and parameter name tokens are case-insensitive, and are kept in lowercase.
+/// Parameter values might or might not be case-sensitive, depending on the semantics of the
+/// parameter name, and are kept as they were received, without quotes.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ContentType {
+	essence: String,
+	parameters: Vec<(String, String)>,
+}
+
+impl ContentType {
+	/// Returns the type and subtype, e.g. "text/html".
+	pub fn essence(&self) -> &str {
+		&self.essence
+	}
+
+	/// Returns the value of a parameter. The name is matched case-insensitively.
+	pub fn parameter(&self, name: &str) -> Option<&str> {
+		self.parameters.iter()
+			.find(|(n, _)| n.eq_ignore_ascii_case(name))
+			.map(|(_, value)| value.as_str())
+	}
+
+	/// Returns the value of the charset parameter.
+	pub fn charset(&self) -> Option<&str> {
+		self.parameter("charset")
+	}
+}
+
+impl Header for ContentType {
+	const NAME: HeaderName = HeaderName::CONTENT_TYPE;
+
+	fn decode(values: &[&HeaderValue]) -> Option<ContentType> {
+		single(values)?.parse().ok()
+	}
+
+	fn encode(&self) -> HeaderValue {
+		encoded(self.to_string())
+	}
+}
+
+impl FromStr for ContentType {
+	type Err = InvalidHeaderValue;
+
+	fn from_str(s: &str) -> Result<ContentType, InvalidHeaderValue> {
+		let (essence, parameters) = match s.find(';') {
+			Some(i) => (&s[..i], &s[i + 1..]),
+			None => (s, ""),
+		};
+		let essence = essence.trim_matches(is_ows);
+		let valid = match essence.find('/') {
+			Some(i) => is_token(&essence[..i]) && is_token(&essence[i + 1..]),
+			None => false,
+		};
+		if !valid {
+			return Err(InvalidHeaderValue);
+		}
+
+		Ok(ContentType {
+			essence: essence.to_ascii_lowercase(),
+			parameters: parse_parameters(parameters).ok_or(InvalidHeaderValue)?,
+		})
+	}
+}
+
+impl fmt::Display for ContentType {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(&self.essence)?;
+		for (name, value) in &self.parameters {
+			if is_token(value) {
+				write!(f, "; {}={}", name, value)?;
+			} else {
+				write!(f, "; {}=\"{}\"", name, value.replace('\\', "\\\\").replace('"', "\\\""))?;
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Location (http://tools.ietf.org/html/rfc7231#section-7.1.2)
+///
+/// The "Location" header field is used in some responses to refer to a specific resource in
+/// relation to the response. For 201 (Created) responses, the Location value refers to the primary
+/// resource created by the request. For 3xx (Redirection) responses, the Location value refers to
+/// the preferred target resource for automatically redirecting the request.
+///
+/// ```c
+/// Location = URI-reference
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Location(pub Uri);
+
+impl Header for Location {
+	const NAME: HeaderName = HeaderName::LOCATION;
+
+	fn decode(values: &[&HeaderValue]) -> Option<Location> {
+		single(values)?.parse().ok().map(Location)
+	}
+
+	fn encode(&self) -> HeaderValue {
+		encoded(self.0.to_string())
+	}
+}
+
+/// Allow (http://tools.ietf.org/html/rfc7231#section-7.4.1)
+///
+/// The "Allow" header field lists the set of methods advertised as supported by the target
+/// resource. An origin server MUST generate an Allow field in a 405 (Method Not Allowed) response.
+/// An empty Allow field value indicates that the resource allows no methods.
+///
+/// ```c
+/// Allow = #method
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Allow(pub Vec<Method>);
+
+impl Header for Allow {
+	const NAME: HeaderName = HeaderName::ALLOW;
+
+	fn decode(values: &[&HeaderValue]) -> Option<Allow> {
+		let methods = list(values)?.into_iter()
+			.map(|element| Method::from_bytes(element.as_bytes()).ok())
+			.collect::<Option<Vec<Method>>>()?;
+		Some(Allow(methods))
+	}
+
+	fn encode(&self) -> HeaderValue {
+		let methods: Vec<&str> = self.0.iter().map(Method::as_str).collect();
+		encoded(methods.join(", "))
+	}
+}
+
+/// Retry-After (http://tools.ietf.org/html/rfc7231#section-7.1.3)
+///
+/// Servers send the "Retry-After" header field to indicate how long the user agent ought to wait
+/// before making a follow-up request. When sent with a 503 (Service Unavailable) response,
+/// Retry-After indicates how long the service is expected to be unavailable to the client. When
+/// sent with any 3xx (Redirection) response, Retry-After indicates the minimum time that the user
+/// agent is asked to wait before issuing the redirected request.
+///
+/// ```c
+/// Retry-After = HTTP-date / delay-seconds
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RetryAfter {
+	/// The time after which to retry.
+	Date(HttpDate),
+	/// The number of seconds to wait before retrying.
+	Delay(u64),
+}
+
+impl Header for RetryAfter {
+	const NAME: HeaderName = HeaderName::RETRY_AFTER;
+
+	fn decode(values: &[&HeaderValue]) -> Option<RetryAfter> {
+		let value = single(values)?;
+		match decimal(value) {
+			Some(delay) => Some(RetryAfter::Delay(delay)),
+			None => value.parse().ok().map(RetryAfter::Date),
+		}
+	}
+
+	fn encode(&self) -> HeaderValue {
+		match *self {
+			RetryAfter::Date(date) => encoded(date.to_string()),
+			RetryAfter::Delay(delay) => HeaderValue::from(delay),
+		}
+	}
+}
+
+/// Date (http://tools.ietf.org/html/rfc7231#section-7.1.1.2)
+///
+/// The "Date" header field represents the date and time at which the message was originated. An
+/// origin server MUST NOT send a Date header field if it does not have a clock capable of
+/// providing a reasonable approximation of the current instance in Coordinated Universal Time;
+/// otherwise it MUST send a Date header field in 2xx, 3xx and 4xx responses, and MAY send one in
+/// 1xx and 5xx responses.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Date(pub HttpDate);
+
+impl Header for Date {
+	const NAME: HeaderName = HeaderName::DATE;
+
+	fn decode(values: &[&HeaderValue]) -> Option<Date> {
+		single(values)?.parse().ok().map(Date)
+	}
+
+	fn encode(&self) -> HeaderValue {
+		encoded(self.0.to_string())
+	}
+}
+
+/// Last-Modified (http://tools.ietf.org/html/rfc7232#section-2.2)
+///
+/// The "Last-Modified" header field in a response provides a timestamp indicating the date and
+/// time at which the origin server believes the selected representation was last modified, as
+/// determined at the conclusion of handling the request.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LastModified(pub HttpDate);
+
+impl Header for LastModified {
+	const NAME: HeaderName = HeaderName::LAST_MODIFIED;
+
+	fn decode(values: &[&HeaderValue]) -> Option<LastModified> {
+		single(values)?.parse().ok().map(LastModified)
+	}
+
+	fn encode(&self) -> HeaderValue {
+		encoded(self.0.to_string())
+	}
+}
+
+/// Host (http://tools.ietf.org/html/rfc7230#section-5.4)
+///
+/// The "Host" header field in a request provides the host and port information from the target
+/// URI, enabling the origin server to distinguish among resources while servicing requests for
+/// multiple host names on a single IP address.
+///
+/// ```c
+/// Host = uri-host [ ":" port ]
+/// ```
+///
+/// If the target URI does not include an authority component, a client MUST send a Host header
+/// field with an empty field-value, so the host may be empty. A server MUST respond with a 400
+/// (Bad Request) status code to any HTTP/1.1 request message that contains more than one Host
+/// header field or a Host header field with an invalid field-value.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Host {
+	host: String,
+	port: Option<u16>,
+}
+
+impl Host {
+	/// Creates a Host header from a host and an optional port.
+	///
+	/// The host is a registered name, an IPv4 address, or an IP-literal in brackets, e.g.
+	/// "\[::1\]". Returns an error if it is none of these.
+	pub fn new(host: &str, port: Option<u16>) -> Result<Host, InvalidHeaderValue> {
+		match host.parse::<Authority>() {
+			Ok(ref authority) if authority.host() == host => Ok(Host {
+				host: host.to_string(),
+				port,
+			}),
+			_ => Err(InvalidHeaderValue),
+		}
+	}
+
+	/// Returns the host.
+	pub fn host(&self) -> &str {
+		&self.host
+	}
+
+	/// Returns the port, if one was given.
+	pub fn port(&self) -> Option<u16> {
+		self.port
+	}
+}
+
+impl Header for Host {
+	const NAME: HeaderName = HeaderName::HOST;
+
+	fn decode(values: &[&HeaderValue]) -> Option<Host> {
+		single(values)?.parse().ok()
+	}
+
+	fn encode(&self) -> HeaderValue {
+		encoded(self.to_string())
+	}
+}
+
+impl FromStr for Host {
+	type Err = InvalidHeaderValue;
+
+	fn from_str(s: &str) -> Result<Host, InvalidHeaderValue> {
+		let authority: Authority = s.parse().map_err(|_| InvalidHeaderValue)?;
+		if authority.userinfo().is_some() {
+			return Err(InvalidHeaderValue);
+		}
+		Ok(Host {
+			host: authority.host().to_string(),
+			port: authority.port(),
+		})
+	}
+}
+
+impl fmt::Display for Host {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(&self.host)?;
+		if let Some(port) = self.port {
+			write!(f, ":{}", port)?;
+		}
+		Ok(())
+	}
+}
+
+/// Connection (http://tools.ietf.org/html/rfc7230#section-6.1)
+///
+/// The "Connection" header field allows the sender to indicate desired control options for the
+/// current connection. The connection options are case-insensitive, and include "close",
+/// "keep-alive", and the names of hop-by-hop header fields such as Upgrade.
+///
+/// ```c
+/// Connection        = 1#connection-option
+/// connection-option = token
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Connection(pub Vec<HeaderName>);
+
+impl Connection {
+	/// Creates a header with the "close" option, which signals that the sender is going to close
+	/// the connection after completion of the response.
+	pub fn close() -> Connection {
+		Connection(vec![HeaderName::from_static("close")])
+	}
+
+	/// Creates a header with the "keep-alive" option, which an HTTP/1.0 sender uses to request a
+	/// persistent connection.
+	pub fn keep_alive() -> Connection {
+		Connection(vec![HeaderName::from_static("keep-alive")])
+	}
+
+	/// Creates a header with the "Upgrade" option, which a sender of the Upgrade header field
+	/// MUST also send.
+	pub fn upgrade() -> Connection {
+		Connection(vec![HeaderName::UPGRADE])
+	}
+
+	/// Returns true if the header lists the given option.
+	pub fn contains(&self, option: &str) -> bool {
+		self.0.iter().any(|name| *name == option)
+	}
+}
+
+impl Header for Connection {
+	const NAME: HeaderName = HeaderName::CONNECTION;
+
+	fn decode(values: &[&HeaderValue]) -> Option<Connection> {
+		non_empty(field_names(values)?).map(Connection)
+	}
+
+	fn encode(&self) -> HeaderValue {
+		encoded(join(&self.0))
+	}
+}
+
+/// Expect (http://tools.ietf.org/html/rfc7231#section-5.1.1)
+///
+/// The "Expect" header field in a request indicates a certain set of behaviors (expectations)
+/// that need to be supported by the server in order to properly handle this request. The only
+/// such expectation defined by this specification is 100-continue, which the client sends to wait
+/// for a 100 (Continue) response before sending a potentially large body. A server that receives
+/// an Expect field-value other than 100-continue MAY respond with a 417 (Expectation Failed)
+/// status code to indicate that the unexpected expectation cannot be met.
+///
+/// ```c
+/// Expect = "100-continue"
+/// ```
+///
+/// A received field may hold other expectations, which are kept as they were received.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Expect(Vec<String>);
+
+impl Expect {
+	/// Creates a header with the given expectations.
+	///
+	/// Returns an error if there are no expectations or one of them is not a token.
+	pub fn new(expectations: &[&str]) -> Result<Expect, InvalidHeaderValue> {
+		if expectations.is_empty() || !expectations.iter().all(|e| is_token(e)) {
+			return Err(InvalidHeaderValue);
+		}
+		Ok(Expect(expectations.iter().map(|e| e.to_string()).collect()))
+	}
+
+	/// Creates a header with the 100-continue expectation.
+	pub fn continue_100() -> Expect {
+		Expect(vec!["100-continue".to_string()])
+	}
+
+	/// Returns the expectations, in order.
+	pub fn expectations(&self) -> &[String] {
+		&self.0
+	}
+
+	/// Returns true if 100-continue is the only expectation, which is the only one a server can
+	/// meet.
+	pub fn is_continue(&self) -> bool {
+		!self.0.is_empty() && self.0.iter().all(|e| e.eq_ignore_ascii_case("100-continue"))
+	}
+}
+
+impl Header for Expect {
+	const NAME: HeaderName = HeaderName::EXPECT;
+
+	fn decode(values: &[&HeaderValue]) -> Option<Expect> {
+		let expectations = non_empty(list(values)?)?;
+		Some(Expect(expectations.into_iter().map(str::to_string).collect()))
+	}
+
+	fn encode(&self) -> HeaderValue {
+		encoded(join(&self.0))
+	}
+}
+
+/// Upgrade (http://tools.ietf.org/html/rfc7230#section-6.7)
+///
+/// The "Upgrade" header field is intended to provide a simple mechanism for transitioning from
+/// HTTP/1.1 to some other protocol on the same connection. A server that sends a 101 (Switching
+/// Protocols) response MUST send an Upgrade header field to indicate the new protocol(s) to which
+/// the connection is being switched, and a server that sends a 426 (Upgrade Required) response
+/// MUST send an Upgrade header field to indicate the acceptable protocols, in order of descending
+/// preference.
+///
+/// ```c
+/// Upgrade = 1#protocol
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Upgrade(pub Vec<UpgradeProtocol>);
+
+impl Upgrade {
+	/// Creates a header offering the WebSocket protocol
+	/// ([Section 4.1 of \[RFC6455\]](http://tools.ietf.org/html/rfc6455#section-4.1)).
+	pub fn websocket() -> Upgrade {
+		Upgrade(vec![UpgradeProtocol { name: "websocket".to_string(), version: None }])
+	}
+
+	/// Returns true if the header lists a protocol with the given name, ignoring case.
+	pub fn contains(&self, name: &str) -> bool {
+		self.0.iter().any(|protocol| protocol.name.eq_ignore_ascii_case(name))
+	}
+}
+
+impl Header for Upgrade {
+	const NAME: HeaderName = HeaderName::UPGRADE;
+
+	fn decode(values: &[&HeaderValue]) -> Option<Upgrade> {
+		let protocols = list(values)?.into_iter()
+			.map(|element| element.parse().ok())
+			.collect::<Option<Vec<UpgradeProtocol>>>()?;
+		non_empty(protocols).map(Upgrade)
+	}
+
+	fn encode(&self) -> HeaderValue {
+		encoded(join(&self.0))
+	}
+}
+
+/// A protocol listed in an Upgrade header field.
+///
+/// ```c
+/// protocol         = protocol-name ["/" protocol-version]
+/// protocol-name    = token
+/// protocol-version = token
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct UpgradeProtocol {
+	name: String,
+	version: Option<String>,
+}
+
+impl UpgradeProtocol {
+	/// Creates a protocol from its name and optional version, e.g. `("HTTP", Some("2.0"))`.
+	///
+	/// Returns an error if the name or version is not a token.
+	pub fn new(name: &str, version: Option<&str>) -> Result<UpgradeProtocol, InvalidHeaderValue> {
+		if !is_token(name) || !version.is_none_or(is_token) {
+			return Err(InvalidHeaderValue);
+		}
+		Ok(UpgradeProtocol {
+			name: name.to_string(),
+			version: version.map(str::to_string),
+		})
+	}
+
+	/// Returns the protocol name.
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// Returns the protocol version, if one was given.
+	pub fn version(&self) -> Option<&str> {
+		self.version.as_deref()
+	}
+}
+
+impl FromStr for UpgradeProtocol {
+	type Err = InvalidHeaderValue;
+
+	fn from_str(s: &str) -> Result<UpgradeProtocol, InvalidHeaderValue> {
+		match s.find('/') {
+			Some(i) => UpgradeProtocol::new(&s[..i], Some(&s[i + 1..])),
+			None => UpgradeProtocol::new(s, None),
+		}
+	}
+}
+
+impl fmt::Display for UpgradeProtocol {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(&self.name)?;
+		if let Some(ref version) = self.version {
+			write!(f, "/{}", version)?;
+		}
+		Ok(())
+	}
+}
+
+/// Transfer-Encoding (http://tools.ietf.org/html/rfc7230#section-3.3.1)
+///
+/// The "Transfer-Encoding" header field lists the transfer coding names corresponding to the
+/// sequence of transfer codings that have been (or will be) applied to the payload body in order
+/// to form the message body. If any transfer coding other than chunked is applied to a request
+/// payload body, the sender MUST apply chunked as the final transfer coding.
+///
+/// ```c
+/// Transfer-Encoding = 1#transfer-coding
+/// transfer-coding   = "chunked" / "compress" / "deflate" / "gzip" / transfer-extension
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TransferEncoding(Vec<String>);
+
+impl TransferEncoding {
+	/// Creates a header with the given transfer codings, in the order they were applied, e.g.
+	/// `&["gzip", "chunked"]`.
+	///
+	/// Returns an error if there are no codings or one of them is not a transfer-coding.
+	pub fn new(codings: &[&str]) -> Result<TransferEncoding, InvalidHeaderValue> {
+		if codings.is_empty() || !codings.iter().all(|coding| is_transfer_coding(coding)) {
+			return Err(InvalidHeaderValue);
+		}
+		Ok(TransferEncoding(codings.iter().map(|coding| coding.to_string()).collect()))
+	}
+
+	/// Creates a header with only the chunked transfer coding.
+	pub fn chunked() -> TransferEncoding {
+		TransferEncoding(vec!["chunked".to_string()])
+	}
+
+	/// Returns the transfer codings, in the order they were applied.
+	pub fn codings(&self) -> &[String] {
+		&self.0
+	}
+
+	/// Returns true if chunked is the final transfer coding, which means the message body is
+	/// delimited by the chunked coding.
+	pub fn is_chunked(&self) -> bool {
+		self.0.last().is_some_and(|coding| coding.eq_ignore_ascii_case("chunked"))
+	}
+}
+
+impl Header for TransferEncoding {
+	const NAME: HeaderName = HeaderName::TRANSFER_ENCODING;
+
+	fn decode(values: &[&HeaderValue]) -> Option<TransferEncoding> {
+		let codings = non_empty(list(values)?)?;
+		if !codings.iter().all(|coding| is_transfer_coding(coding)) {
+			return None;
+		}
+		Some(TransferEncoding(codings.into_iter().map(str::to_string).collect()))
+	}
+
+	fn encode(&self) -> HeaderValue {
+		encoded(join(&self.0))
+	}
+}
+
+/// Vary (http://tools.ietf.org/html/rfc7231#section-7.1.4)
+///
+/// The "Vary" header field in a response describes what parts of a request message, aside from the
+/// method, Host header field, and request target, might influence the origin server's process for
+/// selecting and representing this response.
+///
+/// ```c
+/// Vary = "*" / 1#field-name
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Vary {
+	/// "*" signals that anything about the request might play a role in selecting the response
+	/// representation, possibly including elements outside the message syntax.
+	Any,
+	/// The request header fields that were used to select the representation.
+	Fields(Vec<HeaderName>),
+}
+
+impl Header for Vary {
+	const NAME: HeaderName = HeaderName::VARY;
+
+	fn decode(values: &[&HeaderValue]) -> Option<Vary> {
+		if list(values)?.contains(&"*") {
+			return Some(Vary::Any);
+		}
+		non_empty(field_names(values)?).map(Vary::Fields)
+	}
+
+	fn encode(&self) -> HeaderValue {
+		match *self {
+			Vary::Any => HeaderValue::from_static("*"),
+			Vary::Fields(ref names) => encoded(join(names)),
+		}
+	}
+}
+
+/// ETag (http://tools.ietf.org/html/rfc7232#section-2.3)
+///
+/// The "ETag" header field in a response provides the current entity-tag for the selected
+/// representation, as determined at the conclusion of handling the request.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ETag(pub EntityTag);
+
+impl Header for ETag {
+	const NAME: HeaderName = HeaderName::ETAG;
+
+	fn decode(values: &[&HeaderValue]) -> Option<ETag> {
+		single(values)?.parse().ok().map(ETag)
+	}
+
+	fn encode(&self) -> HeaderValue {
+		encoded(self.0.to_string())
+	}
+}
+
+/// An EntityTag is an opaque validator for differentiating between multiple representations of the
+/// same resource.
+///
+/// ```c
+/// entity-tag = [ weak ] opaque-tag
+/// weak       = %x57.2F ; "W/", case-sensitive
+/// opaque-tag = DQUOTE *etagc DQUOTE
+/// etagc      = %x21 / %x23-7E / obs-text
+///            ; VCHAR except double quotes, plus obs-text
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct EntityTag {
+	weak: bool,
+	tag: String,
+}
+
+impl EntityTag {
+	/// Creates a strong entity-tag from the characters between its quotes.
+	///
+	/// Returns an error if the tag contains a double quote, a space or a control character.
+	pub fn strong(tag: &str) -> Result<EntityTag, InvalidHeaderValue> {
+		EntityTag::new(false, tag)
+	}
+
+	/// Creates a weak entity-tag from the characters between its quotes.
+	///
+	/// Returns an error if the tag contains a double quote, a space or a control character.
+	pub fn weak(tag: &str) -> Result<EntityTag, InvalidHeaderValue> {
+		EntityTag::new(true, tag)
+	}
+
+	fn new(weak: bool, tag: &str) -> Result<EntityTag, InvalidHeaderValue> {
+		if !tag.bytes().all(|b| b == 0x21 || (0x23..=0x7e).contains(&b) || b >= 0x80) {
+			return Err(InvalidHeaderValue);
+		}
+		Ok(EntityTag { weak, tag: tag.to_string() })
+	}
+
+	/// Returns the characters between the quotes.
+	pub fn tag(&self) -> &str {
+		&self.tag
+	}
+
+	/// Returns true if the entity-tag is weak.
+	pub fn is_weak(&self) -> bool {
+		self.weak
+	}
+
+	/// Strong comparison: two entity-tags are equivalent if both are not weak and their
+	/// opaque-tags match character-by-character
+	/// ([Section 2.3.2 of \[RFC7232\]](http://tools.ietf.org/html/rfc7232#section-2.3.2)).
+	pub fn strong_eq(&self, other: &EntityTag) -> bool {
+		!self.weak && !other.weak && self.tag == other.tag
+	}
+
+	/// Weak comparison: two entity-tags are equivalent if their opaque-tags match
+	/// character-by-character, regardless of either or both being tagged as "weak".
+	pub fn weak_eq(&self, other: &EntityTag) -> bool {
+		self.tag == other.tag
+	}
+}
+
+impl FromStr for EntityTag {
+	type Err = InvalidHeaderValue;
+
+	fn from_str(s: &str) -> Result<EntityTag, InvalidHeaderValue> {
+		let (weak, opaque_tag) = match s.strip_prefix("W/") {
+			Some(opaque_tag) => (true, opaque_tag),
+			None => (false, s),
+		};
+		let tag = opaque_tag.strip_prefix('"')
+			.and_then(|tag| tag.strip_suffix('"'))
+			.ok_or(InvalidHeaderValue)?;
+		EntityTag::new(weak, tag)
+	}
+}
+
+impl fmt::Display for EntityTag {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if self.weak {
+			f.write_str("W/")?;
+		}
+		write!(f, "\"{}\"", self.tag)
+	}
+}
+
+/// Converts a string that is known to be a valid field value.
+fn encoded(s: String) -> HeaderValue {
+	HeaderValue::from_bytes(s.as_bytes()).expect("typed header encoded to an invalid field value")
+}
+
+/// Returns the value of a header field that does not allow more than one field line.
+fn single<'a>(values: &[&'a HeaderValue]) -> Option<&'a str> {
+	match *values {
+		[value] => value.to_str(),
+		_ => None,
+	}
+}
+
+/// Splits the values of a list-based header field into their elements
+/// ([Section 7 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-7)).
+///
+/// A recipient MUST accept empty list elements, so these are skipped.
+fn list<'a>(values: &[&'a HeaderValue]) -> Option<Vec<&'a str>> {
+	let mut elements = Vec::new();
+	for value in values {
+		for element in value.to_str()?.split(',') {
+			let element = element.trim_matches(is_ows);
+			if !element.is_empty() {
+				elements.push(element);
+			}
+		}
+	}
+	Some(elements)
+}
+
+fn field_names(values: &[&HeaderValue]) -> Option<Vec<HeaderName>> {
+	list(values)?.into_iter().map(|element| element.parse().ok()).collect()
+}
+
+fn non_empty<T>(elements: Vec<T>) -> Option<Vec<T>> {
+	if elements.is_empty() { None } else { Some(elements) }
+}
+
+fn join<T: fmt::Display>(elements: &[T]) -> String {
+	let elements: Vec<String> = elements.iter().map(T::to_string).collect();
+	elements.join(", ")
+}
+
+/// Parses a non-negative decimal integer, e.g. the value of Content-Length.
+fn decimal(s: &str) -> Option<u64> {
+	if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+		return None;
+	}
+	s.parse().ok()
+}
+
+/// OWS = *( SP / HTAB )
+fn is_ows(c: char) -> bool {
+	c == ' ' || c == '\t'
+}
+
+fn is_token(s: &str) -> bool {
+	token::is_token(s.as_bytes())
+}
+
+/// transfer-coding = token *( OWS ";" OWS transfer-parameter )
+fn is_transfer_coding(s: &str) -> bool {
+	let (name, parameters) = match s.find(';') {
+		Some(i) => (&s[..i], &s[i + 1..]),
+		None => (s, ""),
+	};
+	is_token(name.trim_end_matches(is_ows)) && parse_parameters(parameters).is_some()
+}
+
+/// Parses the parameters of a media-type, following the first ";".
+fn parse_parameters(mut s: &str) -> Option<Vec<(String, String)>> {
+	let mut parameters = Vec::new();
+	loop {
+		s = s.trim_start_matches(is_ows);
+		if s.is_empty() {
+			return Some(parameters);
+		}
+
+		let name_len = s.bytes().take_while(|&b| token::is_tchar(b)).count();
+		if name_len == 0 || s.as_bytes().get(name_len) != Some(&b'=') {
+			return None;
+		}
+		let name = s[..name_len].to_ascii_lowercase();
+		s = &s[name_len + 1..];
+
+		let value = if s.starts_with('"') {
+			let (value, rest) = token::parse_quoted_string(s)?;
+			s = rest;
+			value
+		} else {
+			let value_len = s.bytes().take_while(|&b| token::is_tchar(b)).count();
+			if value_len == 0 {
+				return None;
+			}
+			let value = s[..value_len].to_string();
+			s = &s[value_len..];
+			value
+		};
+		parameters.push((name, value));
+
+		s = s.trim_start_matches(is_ows);
+		if !s.is_empty() {
+			s = s.strip_prefix(';')?;
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use header::HeaderValue;
+
+	fn values(values: &[&'static str]) -> Vec<HeaderValue> {
+		values.iter().map(|value| HeaderValue::from_static(value)).collect()
+	}
+
+	fn decode<H: super::Header>(lines: &[&'static str]) -> Option<H> {
+		let lines = values(lines);
+		let lines: Vec<&HeaderValue> = lines.iter().collect();
+		H::decode(&lines)
+	}
+
+	#[test]
+	fn content_length_test() {
+		use super::ContentLength;
+
+		let test_cases = vec![
+			(vec!["42"], Some(ContentLength(42))),
+			(vec!["0"], Some(ContentLength(0))),
+			(vec!["042"], Some(ContentLength(42))),
+			(vec!["42, 42"], Some(ContentLength(42))),
+			(vec!["42", "42"], Some(ContentLength(42))),
+			(vec!["42", "43"], None),
+			(vec!["42, 43"], None),
+			(vec!["+42"], None),
+			(vec!["-1"], None),
+			(vec!["4 2"], None),
+			(vec![""], None),
+			(vec!["18446744073709551616"], None),
+		];
+
+		for (lines, expect) in test_cases {
+			assert_eq!(expect, decode(&lines), "{:?}", lines);
+		}
+	}
+
+	#[test]
+	fn content_type_test() {
+		use super::ContentType;
+
+		let content_type: ContentType = decode(&["Text/HTML; Charset=\"utf-8\""]).unwrap();
+		assert_eq!("text/html", content_type.essence());
+		assert_eq!(Some("utf-8"), content_type.charset());
+		assert_eq!("text/html; charset=utf-8", content_type.to_string());
+
+		let content_type: ContentType =
+			"multipart/form-data ; boundary=\"a b\\\"c\" ;x=y".parse().unwrap();
+		assert_eq!(Some("a b\"c"), content_type.parameter("Boundary"));
+		assert_eq!(Some("y"), content_type.parameter("x"));
+		assert_eq!("multipart/form-data; boundary=\"a b\\\"c\"; x=y", content_type.to_string());
+
+		for s in ["", "text", "text/", "/html", "text/html; charset", "text/html; charset=",
+			"text/html; charset=\"utf-8", "text/html; a=b c", "te xt/html"] {
+			assert!(s.parse::<ContentType>().is_err(), "{:?}", s);
+		}
+	}
+
+	#[test]
+	fn host_test() {
+		use super::Host;
+
+		let test_cases = vec![
+			("example.com", "example.com", None),
+			("example.com:8080", "example.com", Some(8080)),
+			("example.com:", "example.com", None),
+			("192.0.2.1:80", "192.0.2.1", Some(80)),
+			("[::1]", "[::1]", None),
+			("[::1]:443", "[::1]", Some(443)),
+			("xn--bcher-kva.example", "xn--bcher-kva.example", None),
+			("b%C3%BCcher.example", "b%C3%BCcher.example", None),
+			("", "", None),
+		];
+
+		for (s, host, port) in test_cases {
+			let parsed: Host = decode(&[s]).unwrap();
+			assert_eq!(host, parsed.host());
+			assert_eq!(port, parsed.port());
+		}
+
+		for s in ["a b", "example.com:http", "example.com:65536", "[::1", "::1]", "a/b", "%zz"] {
+			assert!(s.parse::<Host>().is_err(), "{:?}", s);
+		}
+		assert_eq!(None, decode::<Host>(&["a.example", "b.example"]));
+	}
+
+	#[test]
+	fn list_headers_test() {
+		use header::HeaderName;
+		use method::Method;
+		use super::{Allow, Connection, Expect, TransferEncoding, Upgrade, UpgradeProtocol, Vary};
+
+		let allow: Allow = decode(&["GET, HEAD", "", " ,POST,"]).unwrap();
+		assert_eq!(Allow(vec![Method::Get, Method::Head, Method::Post]), allow);
+		assert_eq!(Some(Allow(vec![])), decode(&[""]));
+		assert_eq!(None, decode::<Allow>(&["GET HEAD"]));
+
+		let connection: Connection = decode(&["keep-alive, Upgrade"]).unwrap();
+		assert!(connection.contains("Keep-Alive"));
+		assert!(connection.contains("upgrade"));
+		assert!(!connection.contains("close"));
+		assert_eq!(None, decode::<Connection>(&[", "]));
+
+		assert!(decode::<Expect>(&["100-Continue"]).unwrap().is_continue());
+		assert!(!decode::<Expect>(&["100-continue, x-extension=1"]).unwrap().is_continue());
+		assert_eq!(None, decode::<Expect>(&[""]));
+		assert_eq!(Ok(Expect::continue_100()), Expect::new(&["100-continue"]));
+		for expectations in [&[][..], &["a\r\nInjected: 1"], &["a, b"], &[""]] {
+			assert!(Expect::new(expectations).is_err(), "{:?}", expectations);
+		}
+
+		let upgrade: Upgrade = decode(&["HTTP/2.0, SHTTP/1.3", "IRC/6.9, RTA/x11"]).unwrap();
+		assert_eq!(4, upgrade.0.len());
+		assert_eq!(UpgradeProtocol::new("HTTP", Some("2.0")).unwrap(), upgrade.0[0]);
+		assert!(upgrade.contains("irc"));
+		assert_eq!(None, decode::<Upgrade>(&["HTTP/"]));
+
+		let transfer_encoding: TransferEncoding = decode(&["gzip", "Chunked"]).unwrap();
+		assert!(transfer_encoding.is_chunked());
+		assert!(!decode::<TransferEncoding>(&["chunked, gzip;level=1"]).unwrap().is_chunked());
+		assert_eq!(None, decode::<TransferEncoding>(&["chunked gzip"]));
+		assert_eq!(None, decode::<TransferEncoding>(&[""]));
+		let transfer_encoding = TransferEncoding::new(&["gzip;level=1", "chunked"]).unwrap();
+		assert_eq!(Some(transfer_encoding), decode(&["gzip;level=1, chunked"]));
+		for codings in [&[][..], &["chunked\r\nInjected: 1"], &["gzip, chunked"], &["gzip;level"]] {
+			assert!(TransferEncoding::new(codings).is_err(), "{:?}", codings);
+		}
+
+		assert_eq!(Some(Vary::Any), decode(&["*"]));
+		assert_eq!(Some(Vary::Any), decode(&["Accept", "*"]));
+		let vary = Vary::Fields(vec![HeaderName::ACCEPT_ENCODING, HeaderName::USER_AGENT]);
+		assert_eq!(Some(vary), decode(&["accept-encoding,user-agent"]));
+	}
+
+	#[test]
+	fn dates_test() {
+		use date::HttpDate;
+		use super::{Date, LastModified, RetryAfter};
+
+		let date: HttpDate = "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap();
+		assert_eq!(Some(Date(date)), decode(&["Wed, 21 Oct 2015 07:28:00 GMT"]));
+		assert_eq!(Some(LastModified(date)), decode(&["Wednesday, 21-Oct-15 07:28:00 GMT"]));
+		assert_eq!(None, decode::<Date>(&["Wed, 21 Oct 2015 07:28:00 GMT", "now"]));
+		assert_eq!(Some(RetryAfter::Date(date)), decode(&["Wed, 21 Oct 2015 07:28:00 GMT"]));
+		assert_eq!(Some(RetryAfter::Delay(120)), decode(&["120"]));
+		assert_eq!(None, decode::<RetryAfter>(&["soon"]));
+	}
+
+	#[test]
+	fn entity_tag_test() {
+		use super::{ETag, EntityTag};
+
+		assert_eq!(Some(ETag(EntityTag::strong("xyzzy").unwrap())), decode(&["\"xyzzy\""]));
+		assert_eq!(Some(ETag(EntityTag::weak("xyzzy").unwrap())), decode(&["W/\"xyzzy\""]));
+		assert_eq!(Some(ETag(EntityTag::strong("").unwrap())), decode(&["\"\""]));
+		for s in ["xyzzy", "\"xyzzy", "w/\"xyzzy\"", "\"xy\"zy\"", "\"xy zy\""] {
+			assert!(s.parse::<EntityTag>().is_err(), "{:?}", s);
+		}
+
+		// The example from Section 2.3.2 of RFC 7232.
+		let test_cases = vec![
+			("W/\"1\"", "W/\"1\"", false, true),
+			("W/\"1\"", "W/\"2\"", false, false),
+			("W/\"1\"", "\"1\"", false, true),
+			("\"1\"", "\"1\"", true, true),
+		];
+
+		for (a, b, strong, weak) in test_cases {
+			let (a, b): (EntityTag, EntityTag) = (a.parse().unwrap(), b.parse().unwrap());
+			assert_eq!(strong, a.strong_eq(&b));
+			assert_eq!(weak, a.weak_eq(&b));
+		}
+	}
+
+	#[test]
+	fn encode_test() {
+		use date::HttpDate;
+		use header::HeaderName;
+		use method::Method;
+		use super::*;
+
+		let date: HttpDate = "Sun, 06 Nov 1994 08:49:37 GMT".parse().unwrap();
+		let test_cases = vec![
+			(ContentLength(42).encode(), "42"),
+			(Location("/widgets/7".parse().unwrap()).encode(), "/widgets/7"),
+			(Allow(vec![Method::Get, Method::Head]).encode(), "GET, HEAD"),
+			(RetryAfter::Delay(120).encode(), "120"),
+			(RetryAfter::Date(date).encode(), "Sun, 06 Nov 1994 08:49:37 GMT"),
+			(Date(date).encode(), "Sun, 06 Nov 1994 08:49:37 GMT"),
+			(LastModified(date).encode(), "Sun, 06 Nov 1994 08:49:37 GMT"),
+			(Host::new("example.com", Some(8080)).unwrap().encode(), "example.com:8080"),
+			(Connection::close().encode(), "close"),
+			(Connection(vec![HeaderName::UPGRADE, HeaderName::TE]).encode(), "Upgrade, TE"),
+			(Upgrade::websocket().encode(), "websocket"),
+			(Vary::Any.encode(), "*"),
+			(Vary::Fields(vec![HeaderName::ACCEPT, HeaderName::COOKIE]).encode(), "Accept, Cookie"),
+			(ETag(EntityTag::weak("v2").unwrap()).encode(), "W/\"v2\""),
+		];
+
+		for (value, expect) in test_cases {
+			assert_eq!(expect, value);
+		}
+
+		assert!(Host::new("a@b", None).is_err());
+		assert!(Host::new("a b", None).is_err());
+		assert!(UpgradeProtocol::new("HTTP", Some("")).is_err());
+	}
+}
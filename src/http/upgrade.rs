@@ -0,0 +1,262 @@
+//! Switching a connection from HTTP/1.1 to another protocol.
+
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+
+use error::HttpError;
+use message::{Request, Response};
+use statuscodes::StatusCode;
+use typed_headers::{Connection, Upgrade, UpgradeProtocol};
+use version::HttpVersion;
+
+/// An Upgraded connection has switched from HTTP/1.1 to another protocol with a 101 (Switching
+/// Protocols) response.
+///
+/// The peer can send bytes of the new protocol right after the message that completed the
+/// switch, so some may already have been read along with it. Reading an upgraded connection
+/// returns those buffered bytes first, then reads from the stream.
+#[derive(Debug)]
+pub struct Upgraded {
+	stream: TcpStream,
+	buffered: Vec<u8>,
+	pos: usize,
+}
+
+impl Upgraded {
+	/// Creates an upgraded connection from the stream and the bytes already read from it.
+	pub fn new(stream: TcpStream, buffered: Vec<u8>) -> Upgraded {
+		Upgraded { stream, buffered, pos: 0 }
+	}
+
+	/// Returns the bytes already read from the stream that have not been read from the
+	/// connection.
+	pub fn buffered(&self) -> &[u8] {
+		&self.buffered[self.pos..]
+	}
+
+	/// Returns the stream.
+	pub fn get_ref(&self) -> &TcpStream {
+		&self.stream
+	}
+
+	/// Returns the stream and the bytes already read from it, which come before anything read
+	/// from the stream.
+	pub fn into_parts(mut self) -> (TcpStream, Vec<u8>) {
+		self.buffered.drain(..self.pos);
+		(self.stream, self.buffered)
+	}
+}
+
+impl Read for Upgraded {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		if self.pos == self.buffered.len() {
+			return self.stream.read(buf);
+		}
+		let n = (&self.buffered[self.pos..]).read(buf)?;
+		self.pos += n;
+		Ok(n)
+	}
+}
+
+impl Write for Upgraded {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.stream.write(buf)
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.stream.flush()
+	}
+}
+
+/// Returns the protocols a request asks to upgrade to, in order of the client's preference.
+///
+/// A server MUST ignore an Upgrade header field that is received in an HTTP/1.0 request, and a
+/// sender of Upgrade MUST also send the "upgrade" connection option, since Upgrade is a
+/// hop-by-hop header field
+/// ([Section 6.7 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-6.7)). A request that
+/// does not meet these asks for nothing.
+pub fn requested_protocols<B>(request: &Request<B>) -> Vec<UpgradeProtocol> {
+	if request.version != HttpVersion::Http11 {
+		return Vec::new();
+	}
+	let connection = request.headers.get_typed::<Connection>();
+	if !connection.is_ok_and(|connection| connection.is_some_and(|c| c.contains("upgrade"))) {
+		return Vec::new();
+	}
+	match request.headers.get_typed::<Upgrade>() {
+		Ok(Some(upgrade)) => upgrade.0,
+		_ => Vec::new(),
+	}
+}
+
+/// Returns the first of `supported`, in order of the server's preference, that a request asks to
+/// upgrade to.
+///
+/// A supported protocol without a version matches any version the client asks for.
+pub fn offered_protocol<'a, B>(request: &Request<B>, supported: &'a [UpgradeProtocol])
+	-> Option<&'a UpgradeProtocol>
+{
+	let requested = requested_protocols(request);
+	supported.iter().find(|protocol| requested.iter().any(|offer| matches(protocol, offer)))
+}
+
+/// Returns true if every protocol a 101 (Switching Protocols) response switches to was asked for
+/// by its request; a server MUST NOT switch to a protocol that was not indicated by the client.
+pub fn is_valid_switch<B, C>(request: &Request<B>, response: &Response<C>) -> bool {
+	let requested = requested_protocols(request);
+	match response.headers.get_typed::<Upgrade>() {
+		Ok(Some(upgrade)) => upgrade.0.iter()
+			.all(|protocol| requested.iter().any(|offer| matches(protocol, offer))),
+		_ => false,
+	}
+}
+
+/// Creates a 101 (Switching Protocols) response that switches to `protocol`.
+pub fn switching_protocols(protocol: UpgradeProtocol) -> Response {
+	Response::builder()
+		.status(StatusCode::SwitchingProtocols)
+		.typed_header(Upgrade(vec![protocol]))
+		.typed_header(Connection::upgrade())
+		.body(Vec::new())
+}
+
+/// Creates a 426 (Upgrade Required) response listing the acceptable protocols, in order of
+/// preference.
+pub fn upgrade_required(protocols: &[UpgradeProtocol]) -> Response {
+	let mut response = Response::from(HttpError::upgrade_required());
+	response.headers.set_typed(Upgrade(protocols.to_vec()));
+	response.headers.set_typed(Connection::upgrade());
+	response
+}
+
+fn matches(protocol: &UpgradeProtocol, offer: &UpgradeProtocol) -> bool {
+	protocol.name().eq_ignore_ascii_case(offer.name()) &&
+		protocol.version().is_none_or(|version| offer.version() == Some(version))
+}
+
+#[cfg(test)]
+mod test {
+	use std::io::{Read, Write};
+	use std::net::TcpListener;
+	use std::thread;
+
+	use client::{Client, ClientError};
+	use message::{Request, Response};
+	use server::{Handler, Server};
+	use statuscodes::StatusCode;
+	use typed_headers::{Connection, Host, Upgrade, UpgradeProtocol};
+	use version::HttpVersion;
+
+	fn protocol(s: &str) -> UpgradeProtocol {
+		s.parse().unwrap()
+	}
+
+	fn upgrade_request(protocols: &[&str]) -> Request {
+		Request::builder()
+			.typed_header(Host::new("x", None).unwrap())
+			.typed_header(Upgrade(protocols.iter().map(|s| protocol(s)).collect()))
+			.typed_header(Connection::upgrade())
+			.body(Vec::new())
+	}
+
+	#[test]
+	fn offered_protocol_test() {
+		use super::offered_protocol;
+
+		let supported = [protocol("echo/2"), protocol("Shout")];
+		let test_cases = vec![
+			(upgrade_request(&["echo/2"]), Some("echo/2")),
+			(upgrade_request(&["shout/1", "echo/2"]), Some("echo/2")),
+			(upgrade_request(&["echo/1", "SHOUT/9"]), Some("Shout")),
+			(upgrade_request(&["echo", "whisper"]), None),
+			(Request { version: HttpVersion::Http10, ..upgrade_request(&["echo/2"]) }, None),
+		];
+
+		for (request, expect) in test_cases {
+			let actual = offered_protocol(&request, &supported).map(|p| p.to_string());
+			assert_eq!(expect.map(str::to_string), actual, "{:?}", request.headers);
+		}
+
+		let mut request = upgrade_request(&["echo/2"]);
+		request.headers.set_typed(Connection::keep_alive());
+		assert_eq!(None, offered_protocol(&request, &supported));
+	}
+
+	#[test]
+	fn upgraded_test() {
+		use super::Upgraded;
+
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let addr = listener.local_addr().unwrap();
+		thread::spawn(move || {
+			let (mut stream, _) = listener.accept().unwrap();
+			stream.write_all(b" world").unwrap();
+		});
+
+		let mut upgraded = Upgraded::new(::std::net::TcpStream::connect(addr).unwrap(), b"hello".to_vec());
+		let mut first = [0; 3];
+		upgraded.read_exact(&mut first).unwrap();
+		assert_eq!(b"lo", upgraded.buffered());
+		let mut rest = String::new();
+		upgraded.read_to_string(&mut rest).unwrap();
+		assert_eq!("lo world", rest);
+	}
+
+	#[test]
+	fn upgrade_test() {
+		use super::{offered_protocol, switching_protocols, Upgraded};
+
+		// After switching, the server echoes each byte in upper case.
+		struct Shout(Vec<UpgradeProtocol>);
+
+		impl Handler for Shout {
+			fn handle(&self, request: Request) -> Response {
+				let protocol = offered_protocol(&request, &self.0).unwrap().clone();
+				switching_protocols(protocol)
+			}
+
+			fn required_protocols(&self) -> &[UpgradeProtocol] {
+				&self.0
+			}
+
+			fn upgrade(&self, _: Request, mut stream: Upgraded) {
+				let mut byte = [0];
+				while stream.read(&mut byte).unwrap_or(0) == 1 {
+					stream.write_all(&byte.to_ascii_uppercase()).unwrap();
+				}
+			}
+		}
+
+		let server = Server::bind("127.0.0.1:0").unwrap();
+		let addr = server.local_addr().unwrap();
+		thread::spawn(move || server.serve(Shout(vec![protocol("shout")])));
+
+		let client = Client::connect(addr).unwrap();
+		let (response, mut upgraded) = client.upgrade(&upgrade_request(&["shout/1"])).unwrap();
+		assert_eq!(StatusCode::SwitchingProtocols, response.status);
+		upgraded.write_all(b"hi!").unwrap();
+		let mut reply = [0; 3];
+		upgraded.read_exact(&mut reply).unwrap();
+		assert_eq!(b"HI!", &reply);
+
+		// Bytes of the new protocol sent right after the request reach the upgraded connection.
+		let mut stream = ::std::net::TcpStream::connect(addr).unwrap();
+		stream.write_all(b"GET / HTTP/1.1\r\nHost: x\r\nUpgrade: shout\r\nConnection: upgrade\r\n\r\nabc")
+			.unwrap();
+		stream.shutdown(::std::net::Shutdown::Write).unwrap();
+		let mut response = String::new();
+		stream.read_to_string(&mut response).unwrap();
+		assert!(response.starts_with("HTTP/1.1 101 Switching Protocols\r\n"), "{}", response);
+		assert!(response.ends_with("\r\n\r\nABC"), "{}", response);
+
+		// A request that does not ask for the required protocol is refused.
+		let client = Client::connect(addr).unwrap();
+		match client.upgrade(&upgrade_request(&["whisper"])) {
+			Err(ClientError::UpgradeRefused(response)) => {
+				assert_eq!(StatusCode::UpgradeRequired, response.status);
+				assert_eq!(Some(Upgrade(vec![protocol("shout")])), response.headers.get_typed().unwrap());
+			}
+			other => panic!("{:?}", other.map(|(response, _)| response)),
+		}
+	}
+}
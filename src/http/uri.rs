@@ -0,0 +1,1025 @@
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::str::{self, FromStr};
+
+use method::Method;
+use statuscodes::StatusCode;
+
+/// The default limit on the length of a request-target, in octets.
+///
+/// It is RECOMMENDED that all HTTP senders and recipients support, at a minimum, request-line
+/// lengths of 8000 octets
+/// ([Section 3.1.1 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-3.1.1)).
+pub const DEFAULT_MAX_URI_LENGTH: usize = 8000;
+
+/// A Uri is a URI-reference: either an absolute URI or a relative reference
+/// ([Section 4.1 of \[RFC3986\]](http://tools.ietf.org/html/rfc3986#section-4.1)).
+///
+/// ```c
+/// URI-reference = URI / relative-ref
+/// URI           = scheme ":" hier-part [ "?" query ] [ "#" fragment ]
+/// relative-ref  = relative-part [ "?" query ] [ "#" fragment ]
+/// ```
+///
+/// Components are kept exactly as they were parsed, including their percent-encoding; `normalize`
+/// returns an equivalent URI in normal form, and `resolve` resolves a relative reference, such as
+/// the value of a Location header field, against a base URI.
+///
+/// The default Uri is the empty relative reference.
+#[derive(Clone, PartialEq, Eq, Hash, Default, Debug)]
+pub struct Uri {
+	scheme: Option<String>,
+	authority: Option<Authority>,
+	path: String,
+	query: Option<String>,
+	fragment: Option<String>,
+}
+
+impl Uri {
+	/// Parses a URI-reference.
+	///
+	/// Besides the syntax of RFC 3986, an "http" or "https" URI must have a non-empty host, as a
+	/// recipient MUST reject such a URI with an empty host as invalid
+	/// ([Section 2.7.1 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-2.7.1)).
+	pub fn parse(s: &[u8]) -> Result<Uri, UriError> {
+		let s = ascii(s)?;
+
+		let (s, fragment) = split_off(s, '#');
+		let (s, query) = split_off(s, '?');
+		let (scheme, rest) = match s.find(':') {
+			Some(i) if is_scheme(&s[..i]) => (Some(&s[..i]), &s[i + 1..]),
+			_ => (None, s),
+		};
+		let (authority, path) = match rest.strip_prefix("//") {
+			Some(rest) => {
+				let end = rest.find('/').unwrap_or(rest.len());
+				(Some(rest[..end].parse::<Authority>()?), &rest[end..])
+			}
+			None => (None, rest),
+		};
+
+		if !is_valid(path, |b| is_pchar(b) || b == b'/') ||
+			!query.is_none_or(|query| is_valid(query, is_query_char)) ||
+			!fragment.is_none_or(|fragment| is_valid(fragment, is_query_char)) {
+			return Err(UriError::Malformed);
+		}
+		// A relative-path reference cannot have a colon in its first segment, as it would be
+		// mistaken for a scheme name.
+		if scheme.is_none() && authority.is_none() &&
+			path.split('/').next().is_some_and(|segment| segment.contains(':')) {
+			return Err(UriError::Malformed);
+		}
+		let is_http = scheme.is_some_and(|scheme| {
+			scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("https")
+		});
+		if is_http && authority.as_ref().is_none_or(|authority| authority.host.is_empty()) {
+			return Err(UriError::Malformed);
+		}
+
+		Ok(Uri {
+			scheme: scheme.map(str::to_string),
+			authority,
+			path: path.to_string(),
+			query: query.map(str::to_string),
+			fragment: fragment.map(str::to_string),
+		})
+	}
+
+	/// Parses a URI-reference, returning `UriError::TooLong` if it is longer than `max_length`
+	/// octets.
+	pub fn parse_with_limit(s: &[u8], max_length: usize) -> Result<Uri, UriError> {
+		if s.len() > max_length {
+			return Err(UriError::TooLong);
+		}
+		Uri::parse(s)
+	}
+
+	/// Returns the scheme, e.g. "http", or `None` for a relative reference.
+	pub fn scheme(&self) -> Option<&str> {
+		self.scheme.as_deref()
+	}
+
+	/// Returns the authority, e.g. "example.com:8080".
+	pub fn authority(&self) -> Option<&Authority> {
+		self.authority.as_ref()
+	}
+
+	/// Returns the host of the authority.
+	pub fn host(&self) -> Option<&str> {
+		self.authority.as_ref().map(Authority::host)
+	}
+
+	/// Returns the port of the authority.
+	pub fn port(&self) -> Option<u16> {
+		self.authority.as_ref().and_then(Authority::port)
+	}
+
+	/// Returns the path, which may be empty.
+	pub fn path(&self) -> &str {
+		&self.path
+	}
+
+	/// Returns the query, without the leading "?".
+	pub fn query(&self) -> Option<&str> {
+		self.query.as_deref()
+	}
+
+	/// Returns the fragment, without the leading "#".
+	pub fn fragment(&self) -> Option<&str> {
+		self.fragment.as_deref()
+	}
+
+	/// Returns true if the URI has a scheme, i.e. it is not a relative reference.
+	pub fn is_absolute(&self) -> bool {
+		self.scheme.is_some()
+	}
+
+	/// Returns the path and query, as sent in the origin-form of a request-target. An empty path
+	/// is returned as "/".
+	pub fn path_and_query(&self) -> String {
+		let path = if self.path.is_empty() { "/" } else { &self.path };
+		match self.query {
+			Some(ref query) => format!("{}?{}", path, query),
+			None => path.to_string(),
+		}
+	}
+
+	/// Resolves a URI-reference against this URI as the base URI
+	/// ([Section 5.2 of \[RFC3986\]](http://tools.ietf.org/html/rfc3986#section-5.2)).
+	///
+	/// This is how a user agent determines the target of a redirect: if the Location value is a
+	/// relative reference, the final value is computed by resolving it against the effective
+	/// request URI. If the reference has no fragment, the target inherits the fragment of the
+	/// original request, as specified in Section 7.1.2 of RFC 7231; this method follows RFC 3986
+	/// strictly and does not.
+	pub fn resolve(&self, reference: &Uri) -> Uri {
+		let (scheme, authority, path, query) = if reference.scheme.is_some() {
+			(reference.scheme.clone(), reference.authority.clone(),
+				remove_dot_segments(&reference.path), reference.query.clone())
+		} else if reference.authority.is_some() {
+			(self.scheme.clone(), reference.authority.clone(),
+				remove_dot_segments(&reference.path), reference.query.clone())
+		} else if reference.path.is_empty() {
+			(self.scheme.clone(), self.authority.clone(),
+				self.path.clone(), reference.query.clone().or_else(|| self.query.clone()))
+		} else if reference.path.starts_with('/') {
+			(self.scheme.clone(), self.authority.clone(),
+				remove_dot_segments(&reference.path), reference.query.clone())
+		} else {
+			(self.scheme.clone(), self.authority.clone(),
+				remove_dot_segments(&self.merge(&reference.path)), reference.query.clone())
+		};
+
+		Uri {
+			scheme,
+			authority,
+			path,
+			query,
+			fragment: reference.fragment.clone(),
+		}
+	}
+
+	/// Parses a URI-reference and resolves it against this URI.
+	pub fn join(&self, reference: &str) -> Result<Uri, UriError> {
+		Ok(self.resolve(&reference.parse()?))
+	}
+
+	/// Merges a relative-path reference with the path of this URI
+	/// ([Section 5.2.3 of \[RFC3986\]](http://tools.ietf.org/html/rfc3986#section-5.2.3)).
+	fn merge(&self, path: &str) -> String {
+		if self.authority.is_some() && self.path.is_empty() {
+			return format!("/{}", path);
+		}
+		match self.path.rfind('/') {
+			Some(i) => format!("{}{}", &self.path[..=i], path),
+			None => path.to_string(),
+		}
+	}
+
+	/// Returns an equivalent URI in normal form
+	/// ([Section 6.2.2 of \[RFC3986\]](http://tools.ietf.org/html/rfc3986#section-6.2.2)).
+	///
+	/// * The scheme and host are converted to lowercase.
+	/// * Percent-encoded unreserved characters are decoded, and the hexadecimal digits of the
+	///   remaining percent-encodings are converted to uppercase.
+	/// * Dot-segments are removed from the path of an absolute URI.
+	/// * For "http" and "https", an empty path is replaced by "/" and the default port is omitted
+	///   ([Section 6.2.3 of \[RFC3986\]](http://tools.ietf.org/html/rfc3986#section-6.2.3)).
+	pub fn normalize(&self) -> Uri {
+		let scheme = self.scheme.as_ref().map(|scheme| scheme.to_ascii_lowercase());
+		let default_port = match scheme.as_deref() {
+			Some("http") => Some(80),
+			Some("https") => Some(443),
+			_ => None,
+		};
+
+		let authority = self.authority.as_ref().map(|authority| Authority {
+			userinfo: authority.userinfo.as_ref().map(|userinfo| normalize_percent_encoding(userinfo)),
+			host: normalize_host(&authority.host),
+			port: authority.port.filter(|&port| Some(port) != default_port),
+		});
+
+		let mut path = normalize_percent_encoding(&self.path);
+		if scheme.is_some() {
+			path = remove_dot_segments(&path);
+		}
+		if default_port.is_some() && path.is_empty() {
+			path = "/".to_string();
+		}
+
+		Uri {
+			scheme,
+			authority,
+			path,
+			query: self.query.as_ref().map(|query| normalize_percent_encoding(query)),
+			fragment: self.fragment.as_ref().map(|fragment| normalize_percent_encoding(fragment)),
+		}
+	}
+}
+
+impl FromStr for Uri {
+	type Err = UriError;
+
+	fn from_str(s: &str) -> Result<Uri, UriError> {
+		Uri::parse(s.as_bytes())
+	}
+}
+
+impl fmt::Display for Uri {
+	/// Recomposes the URI from its components
+	/// ([Section 5.3 of \[RFC3986\]](http://tools.ietf.org/html/rfc3986#section-5.3)).
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if let Some(ref scheme) = self.scheme {
+			write!(f, "{}:", scheme)?;
+		}
+		if let Some(ref authority) = self.authority {
+			write!(f, "//{}", authority)?;
+		}
+		f.write_str(&self.path)?;
+		if let Some(ref query) = self.query {
+			write!(f, "?{}", query)?;
+		}
+		if let Some(ref fragment) = self.fragment {
+			write!(f, "#{}", fragment)?;
+		}
+		Ok(())
+	}
+}
+
+/// An Authority is the naming authority component of a URI
+/// ([Section 3.2 of \[RFC3986\]](http://tools.ietf.org/html/rfc3986#section-3.2)).
+///
+/// ```c
+/// authority = [ userinfo "@" ] host [ ":" port ]
+/// host      = IP-literal / IPv4address / reg-name
+/// port      = *DIGIT
+/// ```
+///
+/// The host may be empty, and an IP-literal keeps its brackets, e.g. "\[::1\]". A port that is
+/// empty is treated as absent, and one that is larger than 65535 is rejected.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Authority {
+	userinfo: Option<String>,
+	host: String,
+	port: Option<u16>,
+}
+
+impl Authority {
+	/// Returns the userinfo, without the trailing "@".
+	pub fn userinfo(&self) -> Option<&str> {
+		self.userinfo.as_deref()
+	}
+
+	/// Returns the host.
+	pub fn host(&self) -> &str {
+		&self.host
+	}
+
+	/// Returns the port, if one was given.
+	pub fn port(&self) -> Option<u16> {
+		self.port
+	}
+}
+
+impl FromStr for Authority {
+	type Err = UriError;
+
+	fn from_str(s: &str) -> Result<Authority, UriError> {
+		let (userinfo, s) = match s.rfind('@') {
+			Some(i) => (Some(&s[..i]), &s[i + 1..]),
+			None => (None, s),
+		};
+		// The port follows the last colon, unless that colon is inside an IP-literal.
+		let (host, port) = match s.rfind(':') {
+			Some(i) if !s[i..].contains(']') => (&s[..i], &s[i + 1..]),
+			_ => (s, ""),
+		};
+
+		let valid_userinfo = userinfo.is_none_or(|userinfo| {
+			is_valid(userinfo, |b| is_unreserved(b) || is_sub_delim(b) || b == b':')
+		});
+		if !valid_userinfo || !is_host(host) || !port.bytes().all(|b| b.is_ascii_digit()) {
+			return Err(UriError::Malformed);
+		}
+		let port = match port {
+			"" => None,
+			port => Some(port.parse().map_err(|_| UriError::Malformed)?),
+		};
+
+		Ok(Authority {
+			userinfo: userinfo.map(str::to_string),
+			host: host.to_string(),
+			port,
+		})
+	}
+}
+
+impl fmt::Display for Authority {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if let Some(ref userinfo) = self.userinfo {
+			write!(f, "{}@", userinfo)?;
+		}
+		f.write_str(&self.host)?;
+		if let Some(port) = self.port {
+			write!(f, ":{}", port)?;
+		}
+		Ok(())
+	}
+}
+
+/// A RequestTarget identifies the target resource upon which to apply a request
+/// ([Section 5.3 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-5.3)).
+///
+/// ```c
+/// request-target = origin-form
+///                / absolute-form
+///                / authority-form
+///                / asterisk-form
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum RequestTarget {
+	/// The most common form, used for requests to an origin server: an absolute path and optional
+	/// query, e.g. "/where?q=now". The URI has neither a scheme nor an authority.
+	///
+	/// ```c
+	/// origin-form = absolute-path [ "?" query ]
+	/// ```
+	Origin(Uri),
+	/// An absolute URI, used for requests to a proxy, e.g. "http://www.example.org/pub/WWW/".
+	///
+	/// ```c
+	/// absolute-form = absolute-URI
+	/// ```
+	Absolute(Uri),
+	/// Only used for CONNECT requests: the host and port of the tunnel destination, e.g.
+	/// "www.example.com:80".
+	///
+	/// ```c
+	/// authority-form = authority
+	/// ```
+	Authority(Authority),
+	/// Only used for a server-wide OPTIONS request: "*".
+	///
+	/// ```c
+	/// asterisk-form = "*"
+	/// ```
+	Asterisk,
+}
+
+impl RequestTarget {
+	/// Parses a request-target, with a limit of `DEFAULT_MAX_URI_LENGTH` octets.
+	///
+	/// The form is chosen by the method: a CONNECT request uses the authority-form, "*" is only
+	/// accepted for OPTIONS, and otherwise a target starting with "/" is in origin-form and any
+	/// other target in absolute-form.
+	pub fn parse(method: &Method, target: &[u8]) -> Result<RequestTarget, UriError> {
+		RequestTarget::parse_with_limit(method, target, DEFAULT_MAX_URI_LENGTH)
+	}
+
+	/// Parses a request-target, returning `UriError::TooLong` if it is longer than `max_length`
+	/// octets. A server that receives a request-target longer than any URI it wishes to parse
+	/// MUST respond with a 414 (URI Too Long) status code.
+	pub fn parse_with_limit(method: &Method, target: &[u8], max_length: usize)
+		-> Result<RequestTarget, UriError> {
+		if target.len() > max_length {
+			return Err(UriError::TooLong);
+		}
+		let s = ascii(target)?;
+
+		if *method == Method::Connect {
+			let authority: Authority = s.parse()?;
+			// The authority-form excludes userinfo, and the port is needed to open the tunnel.
+			if authority.userinfo.is_some() || authority.host.is_empty() || authority.port.is_none() {
+				return Err(UriError::Malformed);
+			}
+			return Ok(RequestTarget::Authority(authority));
+		}
+
+		if s == "*" {
+			return match *method {
+				Method::Options => Ok(RequestTarget::Asterisk),
+				_ => Err(UriError::Malformed),
+			};
+		}
+
+		if s.starts_with('/') {
+			// An absolute-path may start with an empty segment, so "//x" is a path rather than an
+			// authority here.
+			let (path, query) = split_off(s, '?');
+			if !is_valid(path, |b| is_pchar(b) || b == b'/') ||
+				!query.is_none_or(|query| is_valid(query, is_query_char)) {
+				return Err(UriError::Malformed);
+			}
+			return Ok(RequestTarget::Origin(Uri {
+				scheme: None,
+				authority: None,
+				path: path.to_string(),
+				query: query.map(str::to_string),
+				fragment: None,
+			}));
+		}
+
+		let uri = Uri::parse(target)?;
+		if !uri.is_absolute() || uri.fragment.is_some() {
+			return Err(UriError::Malformed);
+		}
+		Ok(RequestTarget::Absolute(uri))
+	}
+
+	/// Returns the path of the target resource. This is "*" for the asterisk-form, and empty for
+	/// the authority-form.
+	pub fn path(&self) -> &str {
+		match *self {
+			RequestTarget::Origin(ref uri) | RequestTarget::Absolute(ref uri) => uri.path(),
+			RequestTarget::Authority(_) => "",
+			RequestTarget::Asterisk => "*",
+		}
+	}
+
+	/// Returns the query of the target resource.
+	pub fn query(&self) -> Option<&str> {
+		match *self {
+			RequestTarget::Origin(ref uri) | RequestTarget::Absolute(ref uri) => uri.query(),
+			_ => None,
+		}
+	}
+}
+
+impl Default for RequestTarget {
+	/// Returns the origin-form "/".
+	fn default() -> RequestTarget {
+		RequestTarget::Origin(Uri { path: "/".to_string(), ..Uri::default() })
+	}
+}
+
+impl TryFrom<Uri> for RequestTarget {
+	type Error = UriError;
+
+	/// Converts a URI to the form a client sends to an origin server or proxy: an absolute URI
+	/// becomes the absolute-form, and a relative reference the origin-form of its path and query.
+	/// The fragment is dropped, as it is not part of the request-target.
+	///
+	/// An empty path is sent as "/". Returns `UriError::Malformed` for a relative reference with
+	/// an authority, or with a path that does not start with "/", as neither can be sent in the
+	/// origin-form without changing the resource it refers to.
+	fn try_from(uri: Uri) -> Result<RequestTarget, UriError> {
+		if uri.is_absolute() {
+			return Ok(RequestTarget::Absolute(Uri { fragment: None, ..uri }));
+		}
+		if uri.authority.is_some() || !(uri.path.is_empty() || uri.path.starts_with('/')) {
+			return Err(UriError::Malformed);
+		}
+		let path = if uri.path.is_empty() { "/".to_string() } else { uri.path };
+		Ok(RequestTarget::Origin(Uri {
+			scheme: None,
+			authority: None,
+			path,
+			query: uri.query,
+			fragment: None,
+		}))
+	}
+}
+
+impl fmt::Display for RequestTarget {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			RequestTarget::Origin(ref uri) | RequestTarget::Absolute(ref uri) => uri.fmt(f),
+			RequestTarget::Authority(ref authority) => authority.fmt(f),
+			RequestTarget::Asterisk => f.write_str("*"),
+		}
+	}
+}
+
+/// An error returned when a URI or request-target cannot be parsed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UriError {
+	/// The URI does not match the grammar.
+	Malformed,
+	/// The URI is longer than the configured limit.
+	TooLong,
+}
+
+impl UriError {
+	/// Returns the status code a server responds with when it receives such a request-target:
+	/// 400 (Bad Request), or 414 (URI Too Long).
+	pub fn status(self) -> StatusCode {
+		match self {
+			UriError::Malformed => StatusCode::BadRequest,
+			UriError::TooLong => StatusCode::UriTooLong,
+		}
+	}
+}
+
+impl fmt::Display for UriError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			UriError::Malformed => f.write_str("malformed URI"),
+			UriError::TooLong => f.write_str("URI too long"),
+		}
+	}
+}
+
+impl Error for UriError {}
+
+/// Percent-encodes every byte that is not an unreserved character
+/// ([Section 2.1 of \[RFC3986\]](http://tools.ietf.org/html/rfc3986#section-2.1)), so that the
+/// result can be used as any component of a URI.
+pub fn percent_encode(bytes: &[u8]) -> String {
+	let mut encoded = String::with_capacity(bytes.len());
+	for &b in bytes {
+		if is_unreserved(b) {
+			encoded.push(b as char);
+		} else {
+			encoded.push_str(&format!("%{:02X}", b));
+		}
+	}
+	encoded
+}
+
+/// Decodes the percent-encoded octets in `s`.
+///
+/// The result is not necessarily UTF-8. Returns an error if a "%" is not followed by two
+/// hexadecimal digits.
+pub fn percent_decode(s: &str) -> Result<Vec<u8>, UriError> {
+	let bytes = s.as_bytes();
+	let mut decoded = Vec::with_capacity(bytes.len());
+	let mut i = 0;
+	while i < bytes.len() {
+		if bytes[i] == b'%' {
+			decoded.push(percent_encoded_octet(&bytes[i..]).ok_or(UriError::Malformed)?);
+			i += 3;
+		} else {
+			decoded.push(bytes[i]);
+			i += 1;
+		}
+	}
+	Ok(decoded)
+}
+
+/// Decodes a pct-encoded octet at the start of `s`.
+fn percent_encoded_octet(s: &[u8]) -> Option<u8> {
+	match *s {
+		[b'%', high, low, ..] => Some((hex_digit(high)? << 4) | hex_digit(low)?),
+		_ => None,
+	}
+}
+
+fn hex_digit(b: u8) -> Option<u8> {
+	(b as char).to_digit(16).map(|d| d as u8)
+}
+
+/// Decodes percent-encoded unreserved characters and uppercases the remaining percent-encodings.
+fn normalize_percent_encoding(s: &str) -> String {
+	let bytes = s.as_bytes();
+	let mut normalized = String::with_capacity(bytes.len());
+	let mut i = 0;
+	while i < bytes.len() {
+		match percent_encoded_octet(&bytes[i..]) {
+			Some(b) if is_unreserved(b) => normalized.push(b as char),
+			Some(b) => normalized.push_str(&format!("%{:02X}", b)),
+			None => {
+				normalized.push(bytes[i] as char);
+				i += 1;
+				continue;
+			}
+		}
+		i += 3;
+	}
+	normalized
+}
+
+/// Normalizes the percent-encodings of a host, and converts the rest of it to lowercase, including
+/// the characters that were decoded.
+fn normalize_host(host: &str) -> String {
+	let mut hex_digits = 0;
+	normalize_percent_encoding(host).chars()
+		.map(|c| {
+			if hex_digits > 0 {
+				hex_digits -= 1;
+				return c;
+			}
+			if c == '%' {
+				hex_digits = 2;
+			}
+			c.to_ascii_lowercase()
+		})
+		.collect()
+}
+
+/// Removes the "." and ".." segments from a path
+/// ([Section 5.2.4 of \[RFC3986\]](http://tools.ietf.org/html/rfc3986#section-5.2.4)).
+fn remove_dot_segments(path: &str) -> String {
+	fn pop_segment(output: &mut String) {
+		let i = output.rfind('/').unwrap_or(0);
+		output.truncate(i);
+	}
+
+	let mut input = path;
+	let mut output = String::with_capacity(path.len());
+	while !input.is_empty() {
+		if let Some(rest) = input.strip_prefix("../") {
+			input = rest;
+		} else if let Some(rest) = input.strip_prefix("./") {
+			input = rest;
+		} else if input.starts_with("/./") {
+			input = &input[2..];
+		} else if input == "/." {
+			input = "/";
+		} else if input.starts_with("/../") {
+			input = &input[3..];
+			pop_segment(&mut output);
+		} else if input == "/.." {
+			input = "/";
+			pop_segment(&mut output);
+		} else if input == "." || input == ".." {
+			input = "";
+		} else {
+			let start = if input.starts_with('/') { 1 } else { 0 };
+			let end = input[start..].find('/').map_or(input.len(), |i| i + start);
+			output.push_str(&input[..end]);
+			input = &input[end..];
+		}
+	}
+	output
+}
+
+/// Splits `s` at the first occurrence of `delimiter`, returning the part after it separately.
+fn split_off(s: &str, delimiter: char) -> (&str, Option<&str>) {
+	match s.find(delimiter) {
+		Some(i) => (&s[..i], Some(&s[i + 1..])),
+		None => (s, None),
+	}
+}
+
+/// Converts the bytes of a URI to a string; a URI consists of US-ASCII characters only.
+fn ascii(s: &[u8]) -> Result<&str, UriError> {
+	if !s.is_ascii() {
+		return Err(UriError::Malformed);
+	}
+	Ok(str::from_utf8(s).unwrap())
+}
+
+/// Returns true if `s` consists of pct-encoded octets and bytes for which `allowed` is true.
+fn is_valid<F: Fn(u8) -> bool>(s: &str, allowed: F) -> bool {
+	let bytes = s.as_bytes();
+	let mut i = 0;
+	while i < bytes.len() {
+		if bytes[i] == b'%' {
+			if percent_encoded_octet(&bytes[i..]).is_none() {
+				return false;
+			}
+			i += 3;
+		} else if allowed(bytes[i]) {
+			i += 1;
+		} else {
+			return false;
+		}
+	}
+	true
+}
+
+/// scheme = ALPHA *( ALPHA / DIGIT / "+" / "-" / "." )
+fn is_scheme(s: &str) -> bool {
+	s.starts_with(|c: char| c.is_ascii_alphabetic()) &&
+		s.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'+' || b == b'-' || b == b'.')
+}
+
+/// Returns true if `s` is a host: an IP-literal, an IPv4address or a reg-name, which may be empty.
+///
+/// ```c
+/// IP-literal = "[" ( IPv6address / IPvFuture  ) "]"
+/// reg-name   = *( unreserved / pct-encoded / sub-delims )
+/// ```
+fn is_host(s: &str) -> bool {
+	match s.strip_prefix('[') {
+		Some(literal) => match literal.strip_suffix(']') {
+			Some(address) => !address.is_empty() &&
+				address.bytes().all(|b| is_unreserved(b) || is_sub_delim(b) || b == b':'),
+			None => false,
+		},
+		// An IPv4address is also a reg-name.
+		None => is_valid(s, |b| is_unreserved(b) || is_sub_delim(b)),
+	}
+}
+
+/// unreserved = ALPHA / DIGIT / "-" / "." / "_" / "~"
+fn is_unreserved(b: u8) -> bool {
+	b.is_ascii_alphanumeric() || b == b'-' || b == b'.' || b == b'_' || b == b'~'
+}
+
+/// sub-delims = "!" / "$" / "&" / "'" / "(" / ")" / "*" / "+" / "," / ";" / "="
+fn is_sub_delim(b: u8) -> bool {
+	b"!$&'()*+,;=".contains(&b)
+}
+
+/// pchar = unreserved / pct-encoded / sub-delims / ":" / "@"
+fn is_pchar(b: u8) -> bool {
+	is_unreserved(b) || is_sub_delim(b) || b == b':' || b == b'@'
+}
+
+/// query = fragment = *( pchar / "/" / "?" )
+fn is_query_char(b: u8) -> bool {
+	is_pchar(b) || b == b'/' || b == b'?'
+}
+
+#[cfg(test)]
+mod test {
+
+	#[test]
+	fn uri_parse_test() {
+		use super::Uri;
+
+		// (uri, scheme, host, port, path, query, fragment)
+		let test_cases = vec![
+			("http://example.com", Some("http"), Some("example.com"), None, "", None, None),
+			("https://user:pw@example.com:8443/a/b?x=1&y#top", Some("https"), Some("example.com"),
+				Some(8443), "/a/b", Some("x=1&y"), Some("top")),
+			("http://[::1]:8080/", Some("http"), Some("[::1]"), Some(8080), "/", None, None),
+			("file:///etc/hosts", Some("file"), Some(""), None, "/etc/hosts", None, None),
+			("mailto:fred@example.com", Some("mailto"), None, None, "fred@example.com", None, None),
+			("urn:isbn:0451450523", Some("urn"), None, None, "isbn:0451450523", None, None),
+			("//example.com/a", None, Some("example.com"), None, "/a", None, None),
+			("/a/b%20c?q=a/b?c", None, None, None, "/a/b%20c", Some("q=a/b?c"), None),
+			("../g;x=1", None, None, None, "../g;x=1", None, None),
+			("./a:b", None, None, None, "./a:b", None, None),
+			("?q", None, None, None, "", Some("q"), None),
+			("#f", None, None, None, "", None, Some("f")),
+			("", None, None, None, "", None, None),
+		];
+
+		for (s, scheme, host, port, path, query, fragment) in test_cases {
+			let uri: Uri = s.parse().unwrap();
+			assert_eq!(scheme, uri.scheme(), "{}", s);
+			assert_eq!(host, uri.host(), "{}", s);
+			assert_eq!(port, uri.port(), "{}", s);
+			assert_eq!(path, uri.path(), "{}", s);
+			assert_eq!(query, uri.query(), "{}", s);
+			assert_eq!(fragment, uri.fragment(), "{}", s);
+			assert_eq!(s, uri.to_string());
+		}
+
+		// An empty port is treated as absent.
+		let uri: Uri = "http://example.com:/".parse().unwrap();
+		assert_eq!(None, uri.port());
+		assert_eq!("http://example.com/", uri.to_string());
+	}
+
+	#[test]
+	fn uri_parse_error_test() {
+		use super::{Uri, UriError};
+
+		let test_cases = vec![
+			"http://exa mple.com/",
+			"http://example.com/a b",
+			"http://example.com/%zz",
+			"http://example.com/%4",
+			"http://example.com:80a/",
+			"http://example.com:65536/",
+			"http://[::1/",
+			"http://a@b@c/",
+			"http:///path",
+			"http:/path",
+			"https:",
+			"1a:b",
+			"http://example.com/\"",
+			"http://example.com/#a#b",
+			"http://example.com/<>",
+			"http://example.com/caf\u{e9}",
+		];
+
+		for s in test_cases {
+			assert_eq!(Err(UriError::Malformed), s.parse::<Uri>(), "{:?}", s);
+		}
+	}
+
+	#[test]
+	fn uri_parse_with_limit_test() {
+		use super::{Uri, UriError};
+
+		assert!(Uri::parse_with_limit(b"/abc", 4).is_ok());
+		assert_eq!(Err(UriError::TooLong), Uri::parse_with_limit(b"/abcd", 4));
+	}
+
+	#[test]
+	fn uri_resolve_test() {
+		use super::Uri;
+
+		// The examples from Section 5.4 of RFC 3986.
+		let base: Uri = "http://a/b/c/d;p?q".parse().unwrap();
+		let test_cases = vec![
+			("g:h", "g:h"),
+			("g", "http://a/b/c/g"),
+			("./g", "http://a/b/c/g"),
+			("g/", "http://a/b/c/g/"),
+			("/g", "http://a/g"),
+			("//g", "http://g"),
+			("?y", "http://a/b/c/d;p?y"),
+			("g?y", "http://a/b/c/g?y"),
+			("#s", "http://a/b/c/d;p?q#s"),
+			("g#s", "http://a/b/c/g#s"),
+			("g?y#s", "http://a/b/c/g?y#s"),
+			(";x", "http://a/b/c/;x"),
+			("g;x", "http://a/b/c/g;x"),
+			("g;x?y#s", "http://a/b/c/g;x?y#s"),
+			("", "http://a/b/c/d;p?q"),
+			(".", "http://a/b/c/"),
+			("./", "http://a/b/c/"),
+			("..", "http://a/b/"),
+			("../", "http://a/b/"),
+			("../g", "http://a/b/g"),
+			("../..", "http://a/"),
+			("../../", "http://a/"),
+			("../../g", "http://a/g"),
+			// Abnormal examples.
+			("../../../g", "http://a/g"),
+			("../../../../g", "http://a/g"),
+			("/./g", "http://a/g"),
+			("/../g", "http://a/g"),
+			("g.", "http://a/b/c/g."),
+			(".g", "http://a/b/c/.g"),
+			("g..", "http://a/b/c/g.."),
+			("..g", "http://a/b/c/..g"),
+			("./../g", "http://a/b/g"),
+			("./g/.", "http://a/b/c/g/"),
+			("g/./h", "http://a/b/c/g/h"),
+			("g/../h", "http://a/b/c/h"),
+			("g;x=1/./y", "http://a/b/c/g;x=1/y"),
+			("g;x=1/../y", "http://a/b/c/y"),
+			("g?y/./x", "http://a/b/c/g?y/./x"),
+			("g?y/../x", "http://a/b/c/g?y/../x"),
+			("g#s/./x", "http://a/b/c/g#s/./x"),
+			("g#s/../x", "http://a/b/c/g#s/../x"),
+		];
+
+		for (reference, expect) in test_cases {
+			let reference: Uri = reference.parse().unwrap();
+			assert_eq!(expect, base.resolve(&reference).to_string(), "{}", reference);
+		}
+
+		let base: Uri = "http://example.com".parse().unwrap();
+		assert_eq!("http://example.com/login", base.join("login").unwrap().to_string());
+	}
+
+	#[test]
+	fn uri_normalize_test() {
+		use super::Uri;
+
+		let test_cases = vec![
+			("HTTP://www.Example.COM:80", "http://www.example.com/"),
+			("https://example.com:443/a/./b/../c", "https://example.com/a/c"),
+			("https://example.com:8443/", "https://example.com:8443/"),
+			("http://example.com/%7euser/%3a%2F", "http://example.com/~user/%3A%2F"),
+			("http://example.com/?q=%41%2b", "http://example.com/?q=A%2B"),
+			("http://Ex%3aample.COM/", "http://ex%3Aample.com/"),
+			("http://%45x%61mple.com/", "http://example.com/"),
+			("mailto:Fred@Example.COM", "mailto:Fred@Example.COM"),
+			("../a/./b", "../a/./b"),
+		];
+
+		for (s, expect) in test_cases {
+			let uri: Uri = s.parse().unwrap();
+			assert_eq!(expect, uri.normalize().to_string(), "{}", s);
+		}
+
+		let a: Uri = "http://example.com/~a".parse().unwrap();
+		let b: Uri = "HTTP://EXAMPLE.com:80/%7Ea".parse().unwrap();
+		assert_eq!(a.normalize(), b.normalize());
+	}
+
+	#[test]
+	fn percent_encoding_test() {
+		use super::{percent_decode, percent_encode, UriError};
+
+		let test_cases = vec![
+			(&b"abc-._~"[..], "abc-._~"),
+			(&b"a b/c?d"[..], "a%20b%2Fc%3Fd"),
+			(&b"caf\xc3\xa9"[..], "caf%C3%A9"),
+			(&b"\x00\xff%"[..], "%00%FF%25"),
+			(&b""[..], ""),
+		];
+
+		for (bytes, encoded) in test_cases {
+			assert_eq!(encoded, percent_encode(bytes));
+			assert_eq!(Ok(bytes.to_vec()), percent_decode(encoded));
+		}
+		assert_eq!(Ok(b"a+b".to_vec()), percent_decode("a+b"));
+		assert_eq!(Ok(b"\xc3\xa9".to_vec()), percent_decode("%c3%A9"));
+
+		for s in ["%", "%4", "%zz", "a%g0"] {
+			assert_eq!(Err(UriError::Malformed), percent_decode(s), "{:?}", s);
+		}
+	}
+
+	#[test]
+	fn request_target_test() {
+		use method::Method;
+		use super::RequestTarget;
+
+		// (method, target, form, path, query)
+		let test_cases = vec![
+			(Method::Get, "/where?q=now", "origin", "/where", Some("q=now")),
+			(Method::Get, "/", "origin", "/", None),
+			(Method::Get, "//double", "origin", "//double", None),
+			(Method::Get, "http://www.example.org/pub/WWW/TheProject.html", "absolute",
+				"/pub/WWW/TheProject.html", None),
+			(Method::Post, "https://example.com?x", "absolute", "", Some("x")),
+			(Method::Connect, "www.example.com:80", "authority", "", None),
+			(Method::Connect, "[::1]:443", "authority", "", None),
+			(Method::Options, "*", "asterisk", "*", None),
+			(Method::Options, "/", "origin", "/", None),
+		];
+
+		for (method, s, form, path, query) in test_cases {
+			let target = RequestTarget::parse(&method, s.as_bytes()).unwrap();
+			let actual = match target {
+				RequestTarget::Origin(_) => "origin",
+				RequestTarget::Absolute(_) => "absolute",
+				RequestTarget::Authority(_) => "authority",
+				RequestTarget::Asterisk => "asterisk",
+			};
+			assert_eq!(form, actual, "{}", s);
+			assert_eq!(path, target.path(), "{}", s);
+			assert_eq!(query, target.query(), "{}", s);
+			assert_eq!(s, target.to_string());
+		}
+	}
+
+	#[test]
+	fn request_target_from_uri_test() {
+		use std::convert::TryFrom;
+
+		use super::{RequestTarget, Uri, UriError};
+
+		let test_cases = vec![
+			("http://example.com/a?b#c", Ok("http://example.com/a?b")),
+			("/a?b#c", Ok("/a?b")),
+			("?q", Ok("/?q")),
+			("", Ok("/")),
+			("widgets?x", Err(UriError::Malformed)),
+			("../a", Err(UriError::Malformed)),
+			("//example.com/a", Err(UriError::Malformed)),
+		];
+
+		for (uri, expect) in test_cases {
+			let target = RequestTarget::try_from(uri.parse::<Uri>().unwrap());
+			let target = target.map(|target| target.to_string());
+			assert_eq!(expect.map(str::to_string), target, "{}", uri);
+		}
+
+		assert_eq!("/", RequestTarget::default().to_string());
+	}
+
+	#[test]
+	fn request_target_error_test() {
+		use method::Method;
+		use statuscodes::StatusCode;
+		use super::{RequestTarget, UriError, DEFAULT_MAX_URI_LENGTH};
+
+		let test_cases = vec![
+			(Method::Get, "*"),
+			(Method::Get, ""),
+			(Method::Get, "where"),
+			(Method::Get, "/a b"),
+			(Method::Get, "/a#frag"),
+			(Method::Get, "http://example.com/#frag"),
+			(Method::Get, "/caf\u{e9}"),
+			(Method::Connect, "www.example.com"),
+			(Method::Connect, "user@www.example.com:443"),
+			(Method::Connect, "/"),
+			(Method::Connect, ":443"),
+		];
+
+		for (method, s) in test_cases {
+			assert_eq!(Err(UriError::Malformed), RequestTarget::parse(&method, s.as_bytes()),
+				"{} {:?}", method, s);
+		}
+
+		let long = format!("/{}", "a".repeat(DEFAULT_MAX_URI_LENGTH));
+		let err = RequestTarget::parse(&Method::Get, long.as_bytes()).unwrap_err();
+		assert_eq!(UriError::TooLong, err);
+		assert_eq!(StatusCode::UriTooLong, err.status());
+		assert!(RequestTarget::parse_with_limit(&Method::Get, long.as_bytes(), 9000).is_ok());
+		assert_eq!(Err(UriError::TooLong), RequestTarget::parse_with_limit(&Method::Get, b"/ab", 2));
+	}
+}
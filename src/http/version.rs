@@ -0,0 +1,297 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// A HttpVersion represents the version of the HTTP protocol used by a message.
+///
+/// HTTP uses a "<major>.<minor>" numbering scheme to indicate versions of the protocol. The
+/// protocol version as a whole indicates the sender's conformance with the set of requirements
+/// laid out in that version's corresponding specification of HTTP
+/// ([Section 2.6 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-2.6)).
+///
+/// Versions are ordered from oldest to newest.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub enum HttpVersion {
+	/// HTTP/0.9 (http://www.w3.org/Protocols/HTTP/AsImplemented.html)
+	///
+	/// The original protocol, consisting of a single request-line with the GET method and a
+	/// response that is just the representation, without a status-line or header fields.
+	Http09,
+	/// HTTP/1.0 (http://tools.ietf.org/html/rfc1945)
+	///
+	/// HTTP/1.0 introduced the status-line, header fields and methods other than GET. Connections
+	/// are closed after each response unless both ends agree to keep them open with the
+	/// "keep-alive" connection option
+	/// ([Appendix A.1.2 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#appendix-A.1.2)).
+	Http10,
+	/// HTTP/1.1 (http://tools.ietf.org/html/rfc7230)
+	///
+	/// HTTP/1.1 made persistent connections the default, introduced the chunked transfer coding
+	/// and the 1xx (Informational) status codes, and requires the Host header field in requests.
+	Http11,
+	/// HTTP/2 (http://tools.ietf.org/html/rfc7540)
+	///
+	/// HTTP/2 keeps the semantics of HTTP/1.1 but uses a binary framing layer that multiplexes
+	/// many streams over a single connection. It does not use the chunked transfer coding.
+	Http2,
+	/// HTTP/3 (http://tools.ietf.org/html/rfc9114)
+	///
+	/// HTTP/3 maps the semantics of HTTP onto the QUIC transport protocol.
+	Http3,
+}
+
+impl HttpVersion {
+	/// Parses a HTTP-version token, e.g. "HTTP/1.1".
+	///
+	/// ```c
+	/// HTTP-version = HTTP-name "/" DIGIT "." DIGIT
+	/// HTTP-name    = %x48.54.54.50 ; "HTTP", case-sensitive
+	/// ```
+	///
+	/// "HTTP/2" and "HTTP/3" are also accepted. A minor version higher than the highest
+	/// implemented minor version of a major version is treated as that highest version, e.g.
+	/// "HTTP/1.2" is `Http11`, as a recipient SHOULD process such a message as if it were in the
+	/// highest minor version it implements. A well-formed version with an unknown major version is
+	/// reported as `HttpVersionError::Unsupported`, to which a server answers 505 (HTTP Version
+	/// Not Supported).
+	pub fn parse(s: &[u8]) -> Result<HttpVersion, HttpVersionError> {
+		let (major, minor) = match *s {
+			[b'H', b'T', b'T', b'P', b'/', major, b'.', minor]
+				if major.is_ascii_digit() && minor.is_ascii_digit() => (major - b'0', minor - b'0'),
+			[b'H', b'T', b'T', b'P', b'/', major] if major.is_ascii_digit() => (major - b'0', 0),
+			_ => return Err(HttpVersionError::Malformed),
+		};
+
+		match (major, minor) {
+			(0, 9) => Ok(HttpVersion::Http09),
+			(1, 0) => Ok(HttpVersion::Http10),
+			(1, _) => Ok(HttpVersion::Http11),
+			(2, _) => Ok(HttpVersion::Http2),
+			(3, _) => Ok(HttpVersion::Http3),
+			_ => Err(HttpVersionError::Unsupported),
+		}
+	}
+
+	/// Returns the major version number.
+	pub fn major(self) -> u8 {
+		match self {
+			HttpVersion::Http09 => 0,
+			HttpVersion::Http10 | HttpVersion::Http11 => 1,
+			HttpVersion::Http2 => 2,
+			HttpVersion::Http3 => 3,
+		}
+	}
+
+	/// Returns the minor version number.
+	pub fn minor(self) -> u8 {
+		match self {
+			HttpVersion::Http09 => 9,
+			HttpVersion::Http11 => 1,
+			_ => 0,
+		}
+	}
+
+	/// Returns the version a server should use in its response to a request of this version, or
+	/// `None` if it should answer with 505 (HTTP Version Not Supported).
+	///
+	/// A server SHOULD send a response version equal to the highest version to which the server
+	/// is conformant that has a major version less than or equal to the one received in the
+	/// request ([Section 2.6 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-2.6)).
+	/// `highest` is the highest version the server implements.
+	pub fn negotiate(self, highest: HttpVersion) -> Option<HttpVersion> {
+		if self.major() > highest.major() {
+			None
+		} else if self.major() == highest.major() {
+			Some(highest)
+		} else {
+			Some(HttpVersion::highest_of_major(self.major()))
+		}
+	}
+
+	fn highest_of_major(major: u8) -> HttpVersion {
+		match major {
+			0 => HttpVersion::Http09,
+			1 => HttpVersion::Http11,
+			2 => HttpVersion::Http2,
+			_ => HttpVersion::Http3,
+		}
+	}
+
+	/// Returns true if connections using this version can be reused for more than one request.
+	///
+	/// HTTP/1.0 connections are only persistent if the "keep-alive" connection option is sent;
+	/// see `is_persistent_by_default`.
+	pub fn supports_persistent_connections(self) -> bool {
+		self != HttpVersion::Http09
+	}
+
+	/// Returns true if connections using this version are persistent unless the "close"
+	/// connection option is sent
+	/// ([Section 6.3 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-6.3)).
+	pub fn is_persistent_by_default(self) -> bool {
+		self >= HttpVersion::Http11
+	}
+
+	/// Returns true if messages of this version can use the chunked transfer coding
+	/// ([Section 4.1 of \[RFC7230\]](http://tools.ietf.org/html/rfc7230#section-4.1)).
+	///
+	/// Chunked was introduced in HTTP/1.1; HTTP/2 and HTTP/3 frame messages themselves and do not
+	/// allow it.
+	pub fn supports_chunked(self) -> bool {
+		self == HttpVersion::Http11
+	}
+
+	/// Returns true if a server may send 1xx (Informational) responses to a client of this
+	/// version. Since HTTP/1.0 did not define any 1xx status codes, a server MUST NOT send a 1xx
+	/// response to an HTTP/1.0 client
+	/// ([Section 6.2 of \[RFC7231\]](http://tools.ietf.org/html/rfc7231#section-6.2)).
+	pub fn supports_informational_responses(self) -> bool {
+		self >= HttpVersion::Http11
+	}
+}
+
+impl FromStr for HttpVersion {
+	type Err = HttpVersionError;
+
+	fn from_str(s: &str) -> Result<HttpVersion, HttpVersionError> {
+		HttpVersion::parse(s.as_bytes())
+	}
+}
+
+impl fmt::Display for HttpVersion {
+	/// Formats the version as a HTTP-version token, e.g. "HTTP/1.1" or "HTTP/2".
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			HttpVersion::Http2 | HttpVersion::Http3 => write!(f, "HTTP/{}", self.major()),
+			_ => write!(f, "HTTP/{}.{}", self.major(), self.minor()),
+		}
+	}
+}
+
+/// An error returned when a HTTP-version cannot be parsed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HttpVersionError {
+	/// The token is not of the form "HTTP/x.y".
+	Malformed,
+	/// The token is well-formed, but names a major version that is not implemented.
+	Unsupported,
+}
+
+impl fmt::Display for HttpVersionError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			HttpVersionError::Malformed => f.write_str("malformed HTTP-version"),
+			HttpVersionError::Unsupported => f.write_str("unsupported HTTP major version"),
+		}
+	}
+}
+
+impl Error for HttpVersionError {}
+
+#[cfg(test)]
+mod test {
+
+	#[test]
+	fn httpversion_parse_test() {
+		use super::HttpVersion;
+
+		let test_cases = vec![
+			("HTTP/0.9", HttpVersion::Http09),
+			("HTTP/1.0", HttpVersion::Http10),
+			("HTTP/1.1", HttpVersion::Http11),
+			("HTTP/1.2", HttpVersion::Http11),
+			("HTTP/1.9", HttpVersion::Http11),
+			("HTTP/2", HttpVersion::Http2),
+			("HTTP/2.0", HttpVersion::Http2),
+			("HTTP/3", HttpVersion::Http3),
+			("HTTP/3.0", HttpVersion::Http3),
+		];
+
+		for (s, expect) in test_cases {
+			assert_eq!(Ok(expect), s.parse(), "{}", s);
+		}
+	}
+
+	#[test]
+	fn httpversion_parse_error_test() {
+		use super::{HttpVersion, HttpVersionError};
+
+		for s in ["", "HTTP", "HTTP/", "HTTP/1.", "http/1.1", "HTTP/1.1 ", "HTTP/11", "HTTP/a.b",
+			"HTTP/1.10", "HTTPS/1.1"] {
+			assert_eq!(Err(HttpVersionError::Malformed), s.parse::<HttpVersion>(), "{}", s);
+		}
+
+		for s in ["HTTP/0.0", "HTTP/0.8", "HTTP/4", "HTTP/9.9"] {
+			assert_eq!(Err(HttpVersionError::Unsupported), s.parse::<HttpVersion>(), "{}", s);
+		}
+	}
+
+	#[test]
+	fn httpversion_display_test() {
+		use super::HttpVersion;
+
+		let test_cases = vec![
+			(HttpVersion::Http09, "HTTP/0.9"),
+			(HttpVersion::Http10, "HTTP/1.0"),
+			(HttpVersion::Http11, "HTTP/1.1"),
+			(HttpVersion::Http2, "HTTP/2"),
+			(HttpVersion::Http3, "HTTP/3"),
+		];
+
+		for (version, expect) in test_cases {
+			assert_eq!(expect, version.to_string());
+			assert_eq!(Ok(version), expect.parse());
+		}
+	}
+
+	#[test]
+	fn httpversion_ord_test() {
+		use super::HttpVersion::*;
+
+		assert!(Http09 < Http10);
+		assert!(Http10 < Http11);
+		assert!(Http11 < Http2);
+		assert!(Http2 < Http3);
+	}
+
+	#[test]
+	fn httpversion_negotiate_test() {
+		use super::HttpVersion::*;
+
+		let test_cases = vec![
+			(Http09, Http11, Some(Http09)),
+			(Http10, Http11, Some(Http11)),
+			(Http11, Http11, Some(Http11)),
+			(Http2, Http11, None),
+			(Http3, Http11, None),
+			(Http10, Http10, Some(Http10)),
+			(Http11, Http10, Some(Http10)),
+			(Http11, Http2, Some(Http11)),
+		];
+
+		for (request, highest, expect) in test_cases {
+			assert_eq!(expect, request.negotiate(highest), "{:?} {:?}", request, highest);
+		}
+	}
+
+	#[test]
+	fn httpversion_capabilities_test() {
+		use super::HttpVersion::*;
+
+		// (version, persistent connections, persistent by default, chunked, 1xx responses)
+		let test_cases = vec![
+			(Http09, false, false, false, false),
+			(Http10, true, false, false, false),
+			(Http11, true, true, true, true),
+			(Http2, true, true, false, true),
+			(Http3, true, true, false, true),
+		];
+
+		for (version, persistent, by_default, chunked, informational) in test_cases {
+			assert_eq!(persistent, version.supports_persistent_connections());
+			assert_eq!(by_default, version.is_persistent_by_default());
+			assert_eq!(chunked, version.supports_chunked());
+			assert_eq!(informational, version.supports_informational_responses());
+		}
+	}
+}
@@ -0,0 +1,1098 @@
+//! The WebSocket protocol ([RFC 6455](http://tools.ietf.org/html/rfc6455)), spoken on a
+//! connection upgraded from HTTP/1.1.
+
+use std::collections::hash_map::RandomState;
+use std::error::Error;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, BufReader, Read, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use client::{Client, ClientError};
+use error::HttpError;
+use header::{HeaderName, HeaderValue};
+use message::{Request, Response};
+use method::Method;
+use typed_headers::{Connection, Upgrade, UpgradeProtocol};
+use upgrade::{self, Upgraded};
+
+/// The default limit on the size of a received message, in bytes.
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+
+/// The default limit on the payload of a sent frame; longer messages are fragmented.
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 64 * 1024;
+
+/// The Sec-WebSocket-Version of the protocol in RFC 6455.
+const VERSION: &str = "13";
+
+/// The GUID that the server appends to the Sec-WebSocket-Key to prove it read the handshake.
+const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// The largest payload of a control frame.
+const MAX_CONTROL_PAYLOAD: usize = 125;
+
+/// The side of a connection an endpoint is on, which determines how its frames are masked.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Role {
+	/// A client MUST mask all frames that it sends to the server.
+	Client,
+	/// A server MUST NOT mask any frames that it sends to the client.
+	Server,
+}
+
+/// An Opcode defines the interpretation of the payload data of a frame.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Opcode {
+	/// %x0 denotes a continuation frame, which carries a fragment of a message after the first.
+	Continuation,
+	/// %x1 denotes a text frame, whose message is UTF-8.
+	Text,
+	/// %x2 denotes a binary frame.
+	Binary,
+	/// %x8 denotes a connection close.
+	Close,
+	/// %x9 denotes a ping.
+	Ping,
+	/// %xA denotes a pong.
+	Pong,
+}
+
+impl Opcode {
+	/// Returns the opcode with the given value, or `None` if the value is reserved.
+	pub fn from_u8(value: u8) -> Option<Opcode> {
+		match value {
+			0x0 => Some(Opcode::Continuation),
+			0x1 => Some(Opcode::Text),
+			0x2 => Some(Opcode::Binary),
+			0x8 => Some(Opcode::Close),
+			0x9 => Some(Opcode::Ping),
+			0xA => Some(Opcode::Pong),
+			_ => None,
+		}
+	}
+
+	/// Returns the value of the opcode.
+	pub fn as_u8(self) -> u8 {
+		match self {
+			Opcode::Continuation => 0x0,
+			Opcode::Text => 0x1,
+			Opcode::Binary => 0x2,
+			Opcode::Close => 0x8,
+			Opcode::Ping => 0x9,
+			Opcode::Pong => 0xA,
+		}
+	}
+
+	/// Returns true for the control opcodes, which communicate state about the connection and
+	/// can be sent in the middle of a fragmented message.
+	pub fn is_control(self) -> bool {
+		matches!(self, Opcode::Close | Opcode::Ping | Opcode::Pong)
+	}
+}
+
+/// A Frame is the unit in which messages are transmitted
+/// ([Section 5.2 of \[RFC6455\]](http://tools.ietf.org/html/rfc6455#section-5.2)).
+///
+/// ```c
+///  0                   1                   2                   3
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-------+-+-------------+-------------------------------+
+/// |F|R|R|R| opcode|M| Payload len |    Extended payload length    |
+/// |I|S|S|S|  (4)  |A|     (7)     |             (16/64)           |
+/// |N|V|V|V|       |S|             |   (if payload len==126/127)   |
+/// | |1|2|3|       |K|             |                               |
+/// +-+-+-+-+-------+-+-------------+ - - - - - - - - - - - - - - - +
+/// |     Extended payload length continued, if payload len == 127  |
+/// + - - - - - - - - - - - - - - - +-------------------------------+
+/// |                               |Masking-key, if MASK set to 1  |
+/// +-------------------------------+-------------------------------+
+/// | Masking-key (continued)       |          Payload Data         |
+/// +-------------------------------- - - - - - - - - - - - - - - - +
+/// ```
+///
+/// No extensions are supported, so the RSV bits are always 0.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Frame {
+	/// Whether this is the final fragment of a message.
+	pub fin: bool,
+	/// The interpretation of the payload.
+	pub opcode: Opcode,
+	/// The key that the payload is masked with on the wire; the payload itself is never masked.
+	pub mask: Option<[u8; 4]>,
+	/// The payload data.
+	pub payload: Vec<u8>,
+}
+
+impl Frame {
+	/// Creates an unmasked final frame.
+	pub fn new(opcode: Opcode, payload: Vec<u8>) -> Frame {
+		Frame { fin: true, opcode, mask: None, payload }
+	}
+
+	/// Reads a frame, unmasking its payload.
+	///
+	/// Returns an error without reading the payload if it is longer than `max_payload`.
+	pub fn read<R: Read>(r: &mut R, max_payload: u64) -> Result<Frame, WebSocketError> {
+		let mut head = [0; 2];
+		r.read_exact(&mut head)?;
+		if head[0] & 0x70 != 0 {
+			return Err(WebSocketError::ReservedBits);
+		}
+		let fin = head[0] & 0x80 != 0;
+		let opcode = Opcode::from_u8(head[0] & 0x0F).ok_or(WebSocketError::ReservedOpcode)?;
+
+		let len = match head[1] & 0x7F {
+			126 => {
+				let mut len = [0; 2];
+				r.read_exact(&mut len)?;
+				u64::from(u16::from_be_bytes(len))
+			}
+			127 => {
+				// The most significant bit MUST be 0.
+				let mut len = [0; 8];
+				r.read_exact(&mut len)?;
+				match u64::from_be_bytes(len) {
+					len if len >> 63 != 0 => return Err(WebSocketError::InvalidLength),
+					len => len,
+				}
+			}
+			len => u64::from(len),
+		};
+		// All control frames MUST have a payload length of 125 bytes or less and MUST NOT be
+		// fragmented.
+		if opcode.is_control() && (!fin || len > MAX_CONTROL_PAYLOAD as u64) {
+			return Err(WebSocketError::InvalidControlFrame);
+		}
+		if len > max_payload {
+			return Err(WebSocketError::MessageTooLarge);
+		}
+
+		let mask = match head[1] & 0x80 {
+			0 => None,
+			_ => {
+				let mut key = [0; 4];
+				r.read_exact(&mut key)?;
+				Some(key)
+			}
+		};
+		let mut payload = vec![0; len as usize];
+		r.read_exact(&mut payload)?;
+		if let Some(key) = mask {
+			apply_mask(&mut payload, key);
+		}
+		Ok(Frame { fin, opcode, mask, payload })
+	}
+
+	/// Writes the frame, masking the payload if the frame has a masking key.
+	pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+		let mut buf = Vec::with_capacity(14 + self.payload.len());
+		buf.push(if self.fin { 0x80 } else { 0 } | self.opcode.as_u8());
+
+		let mask_bit = if self.mask.is_some() { 0x80 } else { 0 };
+		match self.payload.len() {
+			len if len < 126 => buf.push(mask_bit | len as u8),
+			len if len <= 0xFFFF => {
+				buf.push(mask_bit | 126);
+				buf.extend_from_slice(&(len as u16).to_be_bytes());
+			}
+			len => {
+				buf.push(mask_bit | 127);
+				buf.extend_from_slice(&(len as u64).to_be_bytes());
+			}
+		}
+
+		if let Some(key) = self.mask {
+			buf.extend_from_slice(&key);
+		}
+		let start = buf.len();
+		buf.extend_from_slice(&self.payload);
+		if let Some(key) = self.mask {
+			apply_mask(&mut buf[start..], key);
+		}
+		w.write_all(&buf)
+	}
+}
+
+/// A CloseCode indicates the reason an endpoint is closing the connection
+/// ([Section 7.4 of \[RFC6455\]](http://tools.ietf.org/html/rfc6455#section-7.4)).
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct CloseCode(pub u16);
+
+impl CloseCode {
+	/// 1000 indicates a normal closure, meaning that the purpose for which the connection was
+	/// established has been fulfilled.
+	pub const NORMAL: CloseCode = CloseCode(1000);
+	/// 1001 indicates that an endpoint is "going away", such as a server going down or a browser
+	/// having navigated away from a page.
+	pub const GOING_AWAY: CloseCode = CloseCode(1001);
+	/// 1002 indicates that an endpoint is terminating the connection due to a protocol error.
+	pub const PROTOCOL_ERROR: CloseCode = CloseCode(1002);
+	/// 1003 indicates that an endpoint is terminating the connection because it has received a
+	/// type of data it cannot accept.
+	pub const UNSUPPORTED_DATA: CloseCode = CloseCode(1003);
+	/// 1007 indicates that an endpoint is terminating the connection because it has received data
+	/// within a message that was not consistent with the type of the message, e.g. non-UTF-8 data
+	/// within a text message.
+	pub const INVALID_PAYLOAD: CloseCode = CloseCode(1007);
+	/// 1008 indicates that an endpoint is terminating the connection because it has received a
+	/// message that violates its policy.
+	pub const POLICY_VIOLATION: CloseCode = CloseCode(1008);
+	/// 1009 indicates that an endpoint is terminating the connection because it has received a
+	/// message that is too big for it to process.
+	pub const MESSAGE_TOO_BIG: CloseCode = CloseCode(1009);
+	/// 1010 indicates that a client is terminating the connection because the server did not
+	/// negotiate an extension that the client expected.
+	pub const MANDATORY_EXTENSION: CloseCode = CloseCode(1010);
+	/// 1011 indicates that a server is terminating the connection because it encountered an
+	/// unexpected condition that prevented it from fulfilling the request.
+	pub const INTERNAL_ERROR: CloseCode = CloseCode(1011);
+
+	/// Returns true if the code can be sent in a Close frame.
+	///
+	/// These are the codes defined by RFC 6455 and registered with IANA since, and the ranges
+	/// for libraries (3000-3999) and for private use (4000-4999). 1005, 1006 and 1015 are
+	/// reserved for reporting a missing status code, an abnormal closure and a TLS failure, and
+	/// MUST NOT be sent.
+	pub fn is_valid(self) -> bool {
+		matches!(self.0, 1000..=1003 | 1007..=1014 | 3000..=4999)
+	}
+}
+
+impl fmt::Display for CloseCode {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}", self.0)
+	}
+}
+
+/// The status code and reason in the body of a Close frame.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CloseFrame {
+	/// The reason for closing.
+	pub code: CloseCode,
+	/// A description of the reason, for debugging.
+	pub reason: String,
+}
+
+/// A Message is sent and received as one or more frames.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Message {
+	/// A message of UTF-8 text.
+	Text(String),
+	/// A message of binary data.
+	Binary(Vec<u8>),
+	/// A ping, which the receiver answers with a pong carrying the same data.
+	Ping(Vec<u8>),
+	/// A pong, in answer to a ping or as an unsolicited heartbeat.
+	Pong(Vec<u8>),
+	/// The start of the closing handshake, or the answer to it, with an optional status code.
+	Close(Option<CloseFrame>),
+}
+
+/// A WebSocket is one endpoint of a WebSocket connection.
+///
+/// A server accepts the opening handshake with `WebSocket::accept`, then speaks the protocol
+/// on the connection that it receives in `Handler::upgrade`:
+///
+/// ```no_run
+/// use http::{Handler, Message, Request, Response, Role, Server, Upgraded, WebSocket};
+///
+/// struct Echo;
+///
+/// impl Handler for Echo {
+///     fn handle(&self, request: Request) -> Response {
+///         WebSocket::accept(&request)
+///     }
+///
+///     fn upgrade(&self, _: Request, stream: Upgraded) {
+///         let mut socket = WebSocket::from_upgraded(stream, Role::Server);
+///         while let Ok(message) = socket.receive() {
+///             if let Message::Text(text) = message {
+///                 let _ = socket.send(Message::Text(text));
+///             }
+///         }
+///     }
+/// }
+///
+/// Server::bind("127.0.0.1:8080").unwrap().serve(Echo).unwrap();
+/// ```
+///
+/// Pings are answered automatically, and a Close frame from the peer is answered with one
+/// carrying the same status code. When the peer violates the protocol or exceeds the message
+/// size limit, the connection is closed with the matching status code.
+#[derive(Debug)]
+pub struct WebSocket {
+	reader: BufReader<Upgraded>,
+	role: Role,
+	max_message_size: usize,
+	max_frame_size: usize,
+	/// The opcode and payload so far of a fragmented message being received.
+	fragments: Option<(Opcode, Vec<u8>)>,
+	close_sent: bool,
+	close_received: bool,
+}
+
+impl WebSocket {
+	/// Creates an endpoint on a connection that completed the opening handshake, with the default
+	/// limits.
+	pub fn from_upgraded(stream: Upgraded, role: Role) -> WebSocket {
+		WebSocket {
+			reader: BufReader::new(stream),
+			role,
+			max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+			max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+			fragments: None,
+			close_sent: false,
+			close_received: false,
+		}
+	}
+
+	/// Sets the limit on the size of a received message; a larger message closes the connection
+	/// with status code 1009.
+	pub fn max_message_size(mut self, max: usize) -> WebSocket {
+		self.max_message_size = max;
+		self
+	}
+
+	/// Sets the largest payload of a sent frame; longer messages are sent as several fragments.
+	pub fn max_frame_size(mut self, max: usize) -> WebSocket {
+		self.max_frame_size = max.max(1);
+		self
+	}
+
+	/// Answers the opening handshake of a client
+	/// ([Section 4.2 of \[RFC6455\]](http://tools.ietf.org/html/rfc6455#section-4.2)).
+	///
+	/// Returns a 101 (Switching Protocols) response with the Sec-WebSocket-Accept header field if
+	/// the request is a valid handshake. Otherwise returns 426 (Upgrade Required) if the client
+	/// speaks another version of the protocol, and 400 (Bad Request) for anything else.
+	pub fn accept<B>(request: &Request<B>) -> Response {
+		let key = match handshake_key(request) {
+			Ok(key) => key,
+			Err(response) => return response,
+		};
+		let mut response = upgrade::switching_protocols(websocket_protocol());
+		// The key was checked to be base64, so the accept value is too.
+		let accept = HeaderValue::from_bytes(accept_key(key).as_bytes()).unwrap();
+		response.headers.insert(HeaderName::SEC_WEBSOCKET_ACCEPT, accept);
+		response
+	}
+
+	/// Performs the opening handshake on a client connection
+	/// ([Section 4.1 of \[RFC6455\]](http://tools.ietf.org/html/rfc6455#section-4.1)).
+	///
+	/// `request` provides the target and Host header field, and any others such as Origin; the
+	/// header fields of the handshake are added to it.
+	pub fn connect<B: AsRef<[u8]>>(client: Client, mut request: Request<B>)
+		-> Result<WebSocket, WebSocketError>
+	{
+		let mut nonce = [0; 16];
+		nonce[..8].copy_from_slice(&random_u64().to_be_bytes());
+		nonce[8..].copy_from_slice(&random_u64().to_be_bytes());
+		let key = base64_encode(&nonce);
+
+		request.method = Method::Get;
+		request.headers.set_typed(Upgrade::websocket());
+		request.headers.set_typed(Connection::upgrade());
+		let key_value = HeaderValue::from_bytes(key.as_bytes()).unwrap();
+		request.headers.insert(HeaderName::SEC_WEBSOCKET_KEY, key_value);
+		request.headers.insert(HeaderName::SEC_WEBSOCKET_VERSION, HeaderValue::from_static(VERSION));
+		let (response, upgraded) = client.upgrade(&request)?;
+
+		// The client MUST fail the connection if the Sec-WebSocket-Accept is not the one expected,
+		// or if the response selects an extension or subprotocol that was not requested.
+		let accept = response.headers.get(HeaderName::SEC_WEBSOCKET_ACCEPT).map(HeaderValue::as_bytes);
+		if accept != Some(accept_key(&key).as_bytes()) ||
+			response.headers.contains_key(HeaderName::SEC_WEBSOCKET_EXTENSIONS) ||
+			response.headers.contains_key(HeaderName::SEC_WEBSOCKET_PROTOCOL)
+		{
+			return Err(WebSocketError::InvalidHandshake);
+		}
+		Ok(WebSocket::from_upgraded(upgraded, Role::Client))
+	}
+
+	/// Returns the connection.
+	pub fn get_ref(&self) -> &Upgraded {
+		self.reader.get_ref()
+	}
+
+	/// Sends a message, fragmenting text and binary messages longer than the frame size.
+	///
+	/// Sending a Close message starts the closing handshake; the peer's answer is the last message
+	/// to receive. No messages can be sent after it.
+	pub fn send(&mut self, message: Message) -> Result<(), WebSocketError> {
+		if self.close_sent {
+			return Err(WebSocketError::Closed);
+		}
+		match message {
+			Message::Text(text) => self.send_data(Opcode::Text, text.as_bytes()),
+			Message::Binary(data) => self.send_data(Opcode::Binary, &data),
+			Message::Ping(data) => self.send_control(Opcode::Ping, data),
+			Message::Pong(data) => self.send_control(Opcode::Pong, data),
+			Message::Close(frame) => {
+				let mut payload = Vec::new();
+				if let Some(frame) = frame {
+					if !frame.code.is_valid() {
+						return Err(WebSocketError::InvalidCloseFrame);
+					}
+					payload.extend_from_slice(&frame.code.0.to_be_bytes());
+					payload.extend_from_slice(frame.reason.as_bytes());
+				}
+				self.send_control(Opcode::Close, payload)?;
+				self.close_sent = true;
+				Ok(())
+			}
+		}
+	}
+
+	/// Starts the closing handshake with a status code and reason.
+	pub fn close(&mut self, code: CloseCode, reason: &str) -> Result<(), WebSocketError> {
+		self.send(Message::Close(Some(CloseFrame { code, reason: reason.to_string() })))
+	}
+
+	/// Receives the next message, reassembling fragmented messages.
+	///
+	/// Returns `Closed` once the closing handshake is complete, or after the connection was closed
+	/// because of an error.
+	pub fn receive(&mut self) -> Result<Message, WebSocketError> {
+		loop {
+			if self.close_received {
+				return Err(WebSocketError::Closed);
+			}
+			let frame = match self.read_frame() {
+				Ok(frame) => frame,
+				Err(err) => return Err(self.fail(err)),
+			};
+
+			match frame.opcode {
+				Opcode::Ping => {
+					if !self.close_sent {
+						self.send_control(Opcode::Pong, frame.payload.clone())?;
+					}
+					return Ok(Message::Ping(frame.payload));
+				}
+				Opcode::Pong => return Ok(Message::Pong(frame.payload)),
+				Opcode::Close => {
+					let close = match parse_close(&frame.payload) {
+						Ok(close) => close,
+						Err(err) => return Err(self.fail(err)),
+					};
+					self.close_received = true;
+					if !self.close_sent {
+						// The peer may close the connection right after its Close frame.
+						let payload = close.as_ref().map(|close| close.code.0.to_be_bytes().to_vec());
+						let _ = self.send_control(Opcode::Close, payload.unwrap_or_default());
+						self.close_sent = true;
+					}
+					return Ok(Message::Close(close));
+				}
+				Opcode::Continuation => match self.fragments.take() {
+					Some((opcode, mut data)) => {
+						data.extend_from_slice(&frame.payload);
+						if frame.fin {
+							return self.complete(opcode, data);
+						}
+						self.fragments = Some((opcode, data));
+					}
+					None => return Err(self.fail(WebSocketError::InvalidFragmentation)),
+				},
+				opcode => {
+					if self.fragments.is_some() {
+						return Err(self.fail(WebSocketError::InvalidFragmentation));
+					}
+					if frame.fin {
+						return self.complete(opcode, frame.payload);
+					}
+					self.fragments = Some((opcode, frame.payload));
+				}
+			}
+		}
+	}
+
+	/// Reads a frame whose payload fits in the rest of the message size limit.
+	fn read_frame(&mut self) -> Result<Frame, WebSocketError> {
+		let received = self.fragments.as_ref().map_or(0, |fragments| fragments.1.len());
+		let max_payload = self.max_message_size.saturating_sub(received).max(MAX_CONTROL_PAYLOAD);
+		let frame = Frame::read(&mut self.reader, max_payload as u64)?;
+
+		if !frame.opcode.is_control() && frame.payload.len() > self.max_message_size - received {
+			return Err(WebSocketError::MessageTooLarge);
+		}
+		// A server MUST close the connection upon receiving a frame that is not masked, and a
+		// client MUST close a connection if it detects a masked frame.
+		if frame.mask.is_some() != (self.role == Role::Server) {
+			return Err(WebSocketError::InvalidMasking);
+		}
+		Ok(frame)
+	}
+
+	/// Returns a message whose frames have all been received.
+	fn complete(&mut self, opcode: Opcode, data: Vec<u8>) -> Result<Message, WebSocketError> {
+		match opcode {
+			Opcode::Text => match String::from_utf8(data) {
+				Ok(text) => Ok(Message::Text(text)),
+				Err(_) => Err(self.fail(WebSocketError::InvalidUtf8)),
+			},
+			_ => Ok(Message::Binary(data)),
+		}
+	}
+
+	/// Closes the connection after an error caused by the peer, with the matching status code.
+	fn fail(&mut self, err: WebSocketError) -> WebSocketError {
+		if let Some(code) = err.close_code() {
+			if !self.close_sent {
+				let _ = self.send_control(Opcode::Close, code.0.to_be_bytes().to_vec());
+				self.close_sent = true;
+			}
+			self.close_received = true;
+		}
+		err
+	}
+
+	fn send_data(&mut self, opcode: Opcode, data: &[u8]) -> Result<(), WebSocketError> {
+		let mut fragments = data.chunks(self.max_frame_size).peekable();
+		let mut opcode = opcode;
+		loop {
+			let payload = fragments.next().unwrap_or_default();
+			let frame = Frame {
+				fin: fragments.peek().is_none(),
+				opcode,
+				mask: None,
+				payload: payload.to_vec(),
+			};
+			self.send_frame(frame)?;
+			if fragments.peek().is_none() {
+				return Ok(());
+			}
+			opcode = Opcode::Continuation;
+		}
+	}
+
+	fn send_control(&mut self, opcode: Opcode, payload: Vec<u8>) -> Result<(), WebSocketError> {
+		if payload.len() > MAX_CONTROL_PAYLOAD {
+			return Err(WebSocketError::InvalidControlFrame);
+		}
+		self.send_frame(Frame::new(opcode, payload))
+	}
+
+	fn send_frame(&mut self, mut frame: Frame) -> Result<(), WebSocketError> {
+		if self.role == Role::Client {
+			frame.mask = Some((random_u64() as u32).to_be_bytes());
+		}
+		let stream = self.reader.get_mut();
+		frame.write(stream)?;
+		stream.flush()?;
+		Ok(())
+	}
+}
+
+/// Returns the Sec-WebSocket-Key of a valid opening handshake, or the response refusing it.
+fn handshake_key<B>(request: &Request<B>) -> Result<&str, Response> {
+	let protocol = websocket_protocol();
+	if request.method != Method::Get || upgrade::offered_protocol(request, &[protocol]).is_none() {
+		return Err(HttpError::bad_request().with_detail("not a WebSocket handshake").into());
+	}
+
+	let version = request.headers.get(HeaderName::SEC_WEBSOCKET_VERSION);
+	if version.map(HeaderValue::as_bytes) != Some(VERSION.as_bytes()) {
+		let mut response = upgrade::upgrade_required(&[websocket_protocol()]);
+		response.headers.insert(HeaderName::SEC_WEBSOCKET_VERSION, HeaderValue::from_static(VERSION));
+		return Err(response);
+	}
+
+	// The key is a base64-encoded 16-byte nonce.
+	let key = request.headers.get(HeaderName::SEC_WEBSOCKET_KEY).and_then(HeaderValue::to_str);
+	match key {
+		Some(key) if base64_decode(key).is_some_and(|nonce| nonce.len() == 16) => Ok(key),
+		_ => Err(HttpError::bad_request().with_detail("invalid Sec-WebSocket-Key").into()),
+	}
+}
+
+fn websocket_protocol() -> UpgradeProtocol {
+	UpgradeProtocol::new("websocket", None).unwrap()
+}
+
+/// Returns the Sec-WebSocket-Accept for a Sec-WebSocket-Key: the base64-encoded SHA-1 of the key
+/// concatenated with the GUID.
+fn accept_key(key: &str) -> String {
+	base64_encode(&sha1(format!("{}{}", key, GUID).as_bytes()))
+}
+
+fn parse_close(payload: &[u8]) -> Result<Option<CloseFrame>, WebSocketError> {
+	if payload.is_empty() {
+		return Ok(None);
+	}
+	if payload.len() < 2 {
+		return Err(WebSocketError::InvalidCloseFrame);
+	}
+	let code = CloseCode(u16::from_be_bytes([payload[0], payload[1]]));
+	match String::from_utf8(payload[2..].to_vec()) {
+		Ok(reason) if code.is_valid() => Ok(Some(CloseFrame { code, reason })),
+		_ => Err(WebSocketError::InvalidCloseFrame),
+	}
+}
+
+fn apply_mask(data: &mut [u8], key: [u8; 4]) {
+	for (i, byte) in data.iter_mut().enumerate() {
+		*byte ^= key[i % 4];
+	}
+}
+
+/// Returns 8 bytes that the peer cannot predict, from the randomly keyed hasher of the standard
+/// library. Masking keys MUST be unpredictable, so that scripts cannot choose the bytes that
+/// appear on the wire.
+fn random_u64() -> u64 {
+	static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+	let mut hasher = RandomState::new().build_hasher();
+	hasher.write_usize(COUNTER.fetch_add(1, Ordering::Relaxed));
+	hasher.finish()
+}
+
+/// Computes the SHA-1 digest of `data`
+/// ([Section 6 of \[RFC3174\]](http://tools.ietf.org/html/rfc3174#section-6)).
+fn sha1(data: &[u8]) -> [u8; 20] {
+	let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+	let mut message = data.to_vec();
+	message.push(0x80);
+	while message.len() % 64 != 56 {
+		message.push(0);
+	}
+	message.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+
+	for block in message.chunks(64) {
+		let mut w = [0u32; 80];
+		for (word, bytes) in w.iter_mut().zip(block.chunks(4)) {
+			*word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+		}
+		for t in 16..80 {
+			w[t] = (w[t - 3] ^ w[t - 8] ^ w[t - 14] ^ w[t - 16]).rotate_left(1);
+		}
+
+		let [mut a, mut b, mut c, mut d, mut e] = h;
+		for (t, &word) in w.iter().enumerate() {
+			let (f, k) = match t {
+				0..=19 => ((b & c) | (!b & d), 0x5A827999),
+				20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+				40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+				_ => (b ^ c ^ d, 0xCA62C1D6),
+			};
+			let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(word);
+			e = d;
+			d = c;
+			c = b.rotate_left(30);
+			b = a;
+			a = temp;
+		}
+		for (state, x) in h.iter_mut().zip([a, b, c, d, e]) {
+			*state = state.wrapping_add(x);
+		}
+	}
+
+	let mut digest = [0; 20];
+	for (bytes, h) in digest.chunks_mut(4).zip(h) {
+		bytes.copy_from_slice(&h.to_be_bytes());
+	}
+	digest
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes `data` in base64 with padding
+/// ([Section 4 of \[RFC4648\]](http://tools.ietf.org/html/rfc4648#section-4)).
+fn base64_encode(data: &[u8]) -> String {
+	let mut s = String::with_capacity(data.len().div_ceil(3) * 4);
+	for chunk in data.chunks(3) {
+		let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | u32::from(b) << (16 - 8 * i));
+		for i in 0..4 {
+			if i <= chunk.len() {
+				s.push(BASE64[(n >> (18 - 6 * i)) as usize & 63] as char);
+			} else {
+				s.push('=');
+			}
+		}
+	}
+	s
+}
+
+/// Decodes base64 with padding, returning `None` if `s` is not valid base64.
+fn base64_decode(s: &str) -> Option<Vec<u8>> {
+	let s = s.as_bytes();
+	if !s.len().is_multiple_of(4) {
+		return None;
+	}
+	let mut data = Vec::with_capacity(s.len() / 4 * 3);
+	for (i, chunk) in s.chunks(4).enumerate() {
+		let padding = chunk.iter().rev().take_while(|&&c| c == b'=').count();
+		if padding > 2 || (padding > 0 && i != s.len() / 4 - 1) {
+			return None;
+		}
+		let mut n = 0u32;
+		for &c in &chunk[..4 - padding] {
+			n = n << 6 | BASE64.iter().position(|&b| b == c)? as u32;
+		}
+		n <<= 6 * padding;
+		data.extend_from_slice(&n.to_be_bytes()[1..4 - padding]);
+	}
+	Some(data)
+}
+
+/// An error returned when the WebSocket protocol cannot be spoken with the peer.
+#[derive(Debug)]
+pub enum WebSocketError {
+	/// The opening handshake could not be sent, or the server refused it.
+	Client(ClientError),
+	/// The response of the server does not complete the opening handshake.
+	InvalidHandshake,
+	/// A frame has an RSV bit set, which requires an extension that was not negotiated.
+	ReservedBits,
+	/// A frame has a reserved opcode.
+	ReservedOpcode,
+	/// The 64-bit payload length of a frame has the most significant bit set.
+	InvalidLength,
+	/// A control frame is fragmented, or its payload is longer than 125 bytes.
+	InvalidControlFrame,
+	/// A frame sent by the client is not masked, or a frame sent by the server is.
+	InvalidMasking,
+	/// A continuation frame does not continue a message, or a message starts before the previous
+	/// one is complete.
+	InvalidFragmentation,
+	/// A Close frame has a status code that cannot be sent, or a reason that is not UTF-8.
+	InvalidCloseFrame,
+	/// A text message is not UTF-8.
+	InvalidUtf8,
+	/// A message is larger than the size limit.
+	MessageTooLarge,
+	/// The connection is closed.
+	Closed,
+	/// Reading or writing the connection failed.
+	Io(io::Error),
+}
+
+impl WebSocketError {
+	/// Returns the status code an endpoint closes the connection with after receiving data that
+	/// causes the error, or `None` if the error is not caused by the peer.
+	pub fn close_code(&self) -> Option<CloseCode> {
+		match *self {
+			WebSocketError::ReservedBits |
+			WebSocketError::ReservedOpcode |
+			WebSocketError::InvalidLength |
+			WebSocketError::InvalidControlFrame |
+			WebSocketError::InvalidMasking |
+			WebSocketError::InvalidFragmentation |
+			WebSocketError::InvalidCloseFrame => Some(CloseCode::PROTOCOL_ERROR),
+			WebSocketError::InvalidUtf8 => Some(CloseCode::INVALID_PAYLOAD),
+			WebSocketError::MessageTooLarge => Some(CloseCode::MESSAGE_TOO_BIG),
+			_ => None,
+		}
+	}
+}
+
+impl fmt::Display for WebSocketError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			WebSocketError::Client(ref err) => write!(f, "WebSocket handshake failed: {}", err),
+			WebSocketError::InvalidHandshake => f.write_str("invalid WebSocket handshake response"),
+			WebSocketError::ReservedBits => f.write_str("frame has reserved bits set"),
+			WebSocketError::ReservedOpcode => f.write_str("frame has reserved opcode"),
+			WebSocketError::InvalidLength => f.write_str("invalid frame length"),
+			WebSocketError::InvalidControlFrame => f.write_str("invalid control frame"),
+			WebSocketError::InvalidMasking => f.write_str("invalid frame masking"),
+			WebSocketError::InvalidFragmentation => f.write_str("invalid message fragmentation"),
+			WebSocketError::InvalidCloseFrame => f.write_str("invalid close frame"),
+			WebSocketError::InvalidUtf8 => f.write_str("text message is not UTF-8"),
+			WebSocketError::MessageTooLarge => f.write_str("message too large"),
+			WebSocketError::Closed => f.write_str("connection closed"),
+			WebSocketError::Io(ref err) => err.fmt(f),
+		}
+	}
+}
+
+impl Error for WebSocketError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match *self {
+			WebSocketError::Client(ref err) => Some(err),
+			WebSocketError::Io(ref err) => Some(err),
+			_ => None,
+		}
+	}
+}
+
+impl From<ClientError> for WebSocketError {
+	fn from(err: ClientError) -> WebSocketError {
+		WebSocketError::Client(err)
+	}
+}
+
+impl From<io::Error> for WebSocketError {
+	fn from(err: io::Error) -> WebSocketError {
+		WebSocketError::Io(err)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::net::SocketAddr;
+	use std::thread;
+
+	use client::Client;
+	use message::{Request, Response};
+	use method::Method;
+	use server::{Handler, Server};
+	use super::{CloseCode, CloseFrame, Message, Role, WebSocket, WebSocketError};
+	use typed_headers::Host;
+	use upgrade::Upgraded;
+	use uri::RequestTarget;
+
+	fn hex(bytes: &[u8]) -> String {
+		bytes.iter().map(|b| format!("{:02x}", b)).collect()
+	}
+
+	#[test]
+	fn sha1_test() {
+		use super::sha1;
+
+		let test_cases = vec![
+			("", "da39a3ee5e6b4b0d3255bfef95601890afd80709"),
+			("abc", "a9993e364706816aba3e25717850c26c9cd0d89d"),
+			("abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+				"84983e441c3bd26ebaae4aa1f95129e5e54670f1"),
+			("The quick brown fox jumps over the lazy dog", "2fd4e1c67a2d28fced849ee1bb76e7391b93eb12"),
+		];
+
+		for (data, expect) in test_cases {
+			assert_eq!(expect, hex(&sha1(data.as_bytes())), "{:?}", data);
+		}
+		assert_eq!("34aa973cd4c4daa4f61eeb2bdbad27316534016f", hex(&sha1(&vec![b'a'; 1_000_000])));
+	}
+
+	#[test]
+	fn base64_test() {
+		use super::{base64_decode, base64_encode};
+
+		// The test vectors from Section 10 of RFC 4648.
+		let test_cases = vec![
+			("", ""),
+			("f", "Zg=="),
+			("fo", "Zm8="),
+			("foo", "Zm9v"),
+			("foob", "Zm9vYg=="),
+			("fooba", "Zm9vYmE="),
+			("foobar", "Zm9vYmFy"),
+		];
+
+		for (data, encoded) in test_cases {
+			assert_eq!(encoded, base64_encode(data.as_bytes()));
+			assert_eq!(Some(data.as_bytes().to_vec()), base64_decode(encoded));
+		}
+
+		for s in ["Zg", "Zg=", "Z===", "Zg==Zg==", "Zm9v!A==", "Zm 9"] {
+			assert_eq!(None, base64_decode(s), "{:?}", s);
+		}
+	}
+
+	#[test]
+	fn accept_test() {
+		use header::{HeaderName, HeaderValue};
+		use statuscodes::StatusCode;
+		use super::accept_key;
+
+		// The example from Section 1.3 of RFC 6455.
+		assert_eq!("s3pPLMBiTxaQ9kYGzzhZRbK+xOo=", accept_key("dGhlIHNhbXBsZSBub25jZQ=="));
+
+		let handshake = |lines: &[(&'static str, &'static str)]| {
+			let mut builder = Request::builder();
+			for &(name, value) in lines {
+				builder = builder.header(HeaderName::from_static(name), HeaderValue::from_static(value));
+			}
+			WebSocket::accept(&builder.body(()))
+		};
+		let valid = [
+			("host", "server.example.com"),
+			("upgrade", "websocket"),
+			("connection", "Upgrade"),
+			("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ=="),
+			("sec-websocket-version", "13"),
+		];
+
+		let response = handshake(&valid);
+		assert_eq!(StatusCode::SwitchingProtocols, response.status);
+		assert_eq!(Some(&HeaderValue::from_static("s3pPLMBiTxaQ9kYGzzhZRbK+xOo=")),
+			response.headers.get(HeaderName::SEC_WEBSOCKET_ACCEPT));
+
+		let test_cases = vec![
+			("sec-websocket-key", Some("c2hvcnQ="), StatusCode::BadRequest),
+			("sec-websocket-key", None, StatusCode::BadRequest),
+			("sec-websocket-version", Some("8"), StatusCode::UpgradeRequired),
+			("sec-websocket-version", None, StatusCode::UpgradeRequired),
+			("upgrade", Some("h2c"), StatusCode::BadRequest),
+			("connection", Some("keep-alive"), StatusCode::BadRequest),
+		];
+
+		for (name, value, status) in test_cases {
+			let mut lines: Vec<_> = valid.iter().filter(|line| line.0 != name).cloned().collect();
+			if let Some(value) = value {
+				lines.push((name, value));
+			}
+			let response = handshake(&lines);
+			assert_eq!(status, response.status, "{}: {:?}", name, value);
+			if status == StatusCode::UpgradeRequired {
+				assert_eq!(Some(&HeaderValue::from_static("13")),
+					response.headers.get(HeaderName::SEC_WEBSOCKET_VERSION));
+			}
+		}
+	}
+
+	#[test]
+	fn frame_test() {
+		use super::{Frame, Opcode};
+
+		// The examples from Section 5.7 of RFC 6455.
+		let hello = Frame::new(Opcode::Text, b"Hello".to_vec());
+		let masked = Frame { mask: Some([0x37, 0xfa, 0x21, 0x3d]), ..hello.clone() };
+		let test_cases = vec![
+			(hello, vec![0x81, 0x05, 0x48, 0x65, 0x6c, 0x6c, 0x6f]),
+			(masked, vec![0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58]),
+			(Frame { fin: false, ..Frame::new(Opcode::Text, b"Hel".to_vec()) },
+				vec![0x01, 0x03, 0x48, 0x65, 0x6c]),
+			(Frame::new(Opcode::Continuation, b"lo".to_vec()), vec![0x80, 0x02, 0x6c, 0x6f]),
+			(Frame::new(Opcode::Ping, b"Hello".to_vec()), vec![0x89, 0x05, 0x48, 0x65, 0x6c, 0x6c, 0x6f]),
+		];
+
+		for (frame, encoded) in test_cases {
+			let mut actual = Vec::new();
+			frame.write(&mut actual).unwrap();
+			assert_eq!(encoded, actual);
+			assert_eq!(frame, Frame::read(&mut &encoded[..], 125).unwrap());
+		}
+
+		for &len in &[125, 126, 65535, 65536] {
+			let frame = Frame { mask: Some([1, 2, 3, 4]), ..Frame::new(Opcode::Binary, vec![7; len]) };
+			let mut encoded = Vec::new();
+			frame.write(&mut encoded).unwrap();
+			assert_eq!(frame, Frame::read(&mut &encoded[..], 65536).unwrap());
+			match Frame::read(&mut &encoded[..], len as u64 - 1) {
+				Err(WebSocketError::MessageTooLarge) => {}
+				other => panic!("{}: {:?}", len, other),
+			}
+		}
+
+		let test_cases: Vec<(&[u8], &str)> = vec![
+			(&[0xc1, 0x00], "frame has reserved bits set"),
+			(&[0x83, 0x00], "frame has reserved opcode"),
+			(&[0x09, 0x00], "invalid control frame"),
+			(&[0x89, 0x7e, 0x00, 0x7e], "invalid control frame"),
+			(&[0x82, 0x7f, 0x80, 0, 0, 0, 0, 0, 0, 0], "invalid frame length"),
+			(&[0x82, 0x05, 0x48], "failed to fill whole buffer"),
+		];
+
+		for (encoded, err) in test_cases {
+			assert_eq!(err, Frame::read(&mut &encoded[..], 1 << 20).unwrap_err().to_string());
+		}
+	}
+
+	struct Echo;
+
+	impl Handler for Echo {
+		fn handle(&self, request: Request) -> Response {
+			WebSocket::accept(&request)
+		}
+
+		fn upgrade(&self, _: Request, stream: Upgraded) {
+			let mut socket = WebSocket::from_upgraded(stream, Role::Server)
+				.max_message_size(4096)
+				.max_frame_size(7);
+			loop {
+				let reply = match socket.receive() {
+					Ok(Message::Text(text)) => Message::Text(text),
+					Ok(Message::Binary(data)) => Message::Binary(data),
+					Ok(_) => continue,
+					Err(_) => return,
+				};
+				if socket.send(reply).is_err() {
+					return;
+				}
+			}
+		}
+	}
+
+	fn connect(addr: SocketAddr) -> WebSocket {
+		let request = Request::builder()
+			.target(RequestTarget::parse(&Method::Get, b"/updates").unwrap())
+			.typed_header(Host::new("127.0.0.1", Some(addr.port())).unwrap())
+			.body(Vec::new());
+		WebSocket::connect(Client::connect(addr).unwrap(), request).unwrap()
+	}
+
+	#[test]
+	fn websocket_test() {
+		let server = Server::bind("127.0.0.1:0").unwrap();
+		let addr = server.local_addr().unwrap();
+		thread::spawn(move || server.serve(Echo));
+
+		let mut socket = connect(addr).max_frame_size(5);
+		let data: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+		let test_cases = vec![
+			Message::Text("hello, world".to_string()),
+			Message::Text(String::new()),
+			Message::Text("fragments of ünïcödé split mid-character".to_string()),
+			Message::Binary(data),
+		];
+
+		for message in test_cases {
+			socket.send(message.clone()).unwrap();
+			assert_eq!(message, socket.receive().unwrap());
+		}
+
+		socket.send(Message::Ping(b"are you there?".to_vec())).unwrap();
+		assert_eq!(Message::Pong(b"are you there?".to_vec()), socket.receive().unwrap());
+
+		// The server answers the Close frame with its status code, then closes the connection.
+		socket.close(CloseCode::GOING_AWAY, "bye").unwrap();
+		let close = CloseFrame { code: CloseCode::GOING_AWAY, reason: String::new() };
+		assert_eq!(Message::Close(Some(close)), socket.receive().unwrap());
+		match socket.send(Message::Text("too late".to_string())) {
+			Err(WebSocketError::Closed) => {}
+			other => panic!("{:?}", other),
+		}
+		match socket.receive() {
+			Err(WebSocketError::Closed) => {}
+			other => panic!("{:?}", other),
+		}
+	}
+
+	#[test]
+	fn websocket_error_test() {
+		use std::io::Write;
+		use super::{Frame, Opcode};
+
+		let server = Server::bind("127.0.0.1:0").unwrap();
+		let addr = server.local_addr().unwrap();
+		thread::spawn(move || server.serve(Echo));
+
+		let test_cases = vec![
+			(Frame::new(Opcode::Text, vec![b'a'; 4097]), CloseCode::MESSAGE_TOO_BIG),
+			(Frame::new(Opcode::Text, vec![0xff, 0xfe]), CloseCode::INVALID_PAYLOAD),
+			(Frame::new(Opcode::Continuation, b"?".to_vec()), CloseCode::PROTOCOL_ERROR),
+			(Frame::new(Opcode::Close, vec![0x03, 0xed]), CloseCode::PROTOCOL_ERROR),
+			(Frame { fin: false, ..Frame::new(Opcode::Ping, Vec::new()) }, CloseCode::PROTOCOL_ERROR),
+		];
+
+		for (frame, code) in test_cases {
+			let mut socket = connect(addr);
+			let masked = Frame { mask: Some([1, 2, 3, 4]), ..frame.clone() };
+			let mut stream = socket.get_ref().get_ref();
+			masked.write(&mut stream).unwrap();
+			stream.flush().unwrap();
+			let close = CloseFrame { code, reason: String::new() };
+			assert_eq!(Message::Close(Some(close)), socket.receive().unwrap(), "{:?}", frame);
+		}
+
+		// A server closes the connection when it receives an unmasked frame.
+		let mut socket = connect(addr);
+		let mut stream = socket.get_ref().get_ref();
+		Frame::new(Opcode::Text, b"plain".to_vec()).write(&mut stream).unwrap();
+		let close = CloseFrame { code: CloseCode::PROTOCOL_ERROR, reason: String::new() };
+		assert_eq!(Message::Close(Some(close)), socket.receive().unwrap());
+	}
+}
@@ -1,6 +1,132 @@
 extern crate http;
 
+use std::env;
+use std::fmt::Write as FmtWrite;
+use std::io::{self, Write};
+use std::process;
+
+use http::{StatusCode, StatusCodeClass};
+
+const USAGE: &str = "Usage:
+    fanoracle explain <status-code>
+    fanoracle explain --class <1xx|2xx|3xx|4xx|5xx>";
+
 fn main() {
-    println!("Hello, world!");
-    println!("Ok Status Code: {:?}", http::StatusCode::Ok);
+	let args: Vec<String> = env::args().skip(1).collect();
+	let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+
+	let result = match args[..] {
+		["explain", "--class", class] => explain_class(class),
+		["explain", code] => explain(code),
+		_ => Err(USAGE.to_string()),
+	};
+
+	let text = result.unwrap_or_else(|message| {
+		eprintln!("{}", message);
+		process::exit(2);
+	});
+
+	let stdout = io::stdout();
+	let mut out = stdout.lock();
+	match out.write_all(text.as_bytes()).and_then(|()| out.flush()) {
+		Ok(()) => {}
+		// The reader has gone away, e.g. `fanoracle explain --class 4xx | head`.
+		Err(ref err) if err.kind() == io::ErrorKind::BrokenPipe => {}
+		Err(err) => {
+			eprintln!("{}", err);
+			process::exit(1);
+		}
+	}
+}
+
+/// Describes the reason phrase, class, reference and documentation of a status code.
+fn explain(code: &str) -> Result<String, String> {
+	let status = code.parse()
+		.map_err(|_| format!("'{}' is not a status code", code))
+		.and_then(|n| StatusCode::try_from_u16(n).map_err(|err| err.to_string()))?;
+
+	let mut text = String::new();
+	writeln!(text, "{}", status).unwrap();
+	match status.class() {
+		Some(class) => writeln!(text, "Class:     {} ({})", class, class.name()).unwrap(),
+		None => writeln!(text, "Class:     none").unwrap(),
+	}
+
+	match status.reference() {
+		Some(reference) => {
+			writeln!(text, "Reference: {}", reference).unwrap();
+			writeln!(text).unwrap();
+			// The documentation of registered codes starts with the title line written above.
+			let lines = status.documentation().lines().skip(1);
+			let prose: Vec<&str> = lines.skip_while(|line| line.trim().is_empty()).collect();
+			text.push_str(&plain_text(&prose.join("\n")));
+		}
+		None => {
+			writeln!(text).unwrap();
+			writeln!(text, "{} is not a registered status code.", status.to_u16()).unwrap();
+			if status.class().is_some() {
+				writeln!(text, "It is treated as {}, except that the response MUST NOT be cached.",
+					status.effective()).unwrap();
+			}
+		}
+	}
+	Ok(text)
+}
+
+/// Lists every registered status code of a class.
+fn explain_class(class: &str) -> Result<String, String> {
+	let class: StatusCodeClass = class.parse().map_err(|err| format!("{}", err))?;
+
+	let mut text = String::new();
+	writeln!(text, "{} ({})", class, class.name()).unwrap();
+	writeln!(text).unwrap();
+	for n in 100..600 {
+		let status = StatusCode::from_u16(n);
+		if status.is_recognized() && status.class() == Some(class) {
+			writeln!(text, "{}", status).unwrap();
+		}
+	}
+	Ok(text)
+}
+
+/// Converts the Markdown of the status code documentation to plain text for a terminal.
+///
+/// Links are replaced by their text, escapes and inline code markers are removed, and code blocks
+/// are indented.
+fn plain_text(markdown: &str) -> String {
+	let mut text = String::new();
+	let mut in_code_block = false;
+	let mut in_link_target = false;
+
+	for line in markdown.lines() {
+		let line = line.strip_prefix(' ').unwrap_or(line);
+		if line.starts_with("```") {
+			in_code_block = !in_code_block;
+			continue;
+		}
+		if in_code_block {
+			if !line.is_empty() {
+				text.push_str("    ");
+				text.push_str(line);
+			}
+			text.push('\n');
+			continue;
+		}
+
+		let mut chars = line.chars().peekable();
+		while let Some(c) = chars.next() {
+			match c {
+				_ if in_link_target => in_link_target = c != ')',
+				'\\' => text.extend(chars.next()),
+				']' if chars.peek() == Some(&'(') => {
+					chars.next();
+					in_link_target = true;
+				}
+				'[' | ']' | '`' => {}
+				c => text.push(c),
+			}
+		}
+		text.push('\n');
+	}
+	text
 }